@@ -0,0 +1,41 @@
+-- Known issues database for auto-annotating matching error reports
+-- Compatible with PostgreSQL
+
+-- Create known_issues table
+CREATE TABLE known_issues (
+    id SERIAL PRIMARY KEY,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    title VARCHAR(200) NOT NULL,
+    explanation TEXT NOT NULL,
+    link VARCHAR(500),
+    error_details_pattern TEXT,
+    log_data_pattern TEXT,
+    failure_task_pattern TEXT,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE
+);
+
+-- Create error_report_known_issues table linking reports to matched issues
+CREATE TABLE error_report_known_issues (
+    id SERIAL PRIMARY KEY,
+    error_report_id INTEGER NOT NULL REFERENCES error_reports(id) ON DELETE CASCADE,
+    known_issue_id INTEGER NOT NULL REFERENCES known_issues(id) ON DELETE CASCADE,
+    matched_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (error_report_id, known_issue_id)
+);
+
+-- Create indexes for performance
+CREATE INDEX idx_known_issues_enabled ON known_issues(enabled);
+CREATE INDEX idx_error_report_known_issues_error_report_id ON error_report_known_issues(error_report_id);
+CREATE INDEX idx_error_report_known_issues_known_issue_id ON error_report_known_issues(known_issue_id);
+
+-- Comments for documentation
+COMMENT ON TABLE known_issues IS 'Well-known build failures with an explanation and workaround';
+COMMENT ON TABLE error_report_known_issues IS 'Known issues automatically matched against submitted error reports';
+
+COMMENT ON COLUMN known_issues.title IS 'Short summary of the known issue (e.g., host gcc too new)';
+COMMENT ON COLUMN known_issues.explanation IS 'Explanation of the failure and the recommended workaround';
+COMMENT ON COLUMN known_issues.link IS 'Optional link to a bug, mailing list thread or documentation';
+COMMENT ON COLUMN known_issues.error_details_pattern IS 'Optional regular expression matched against error_details';
+COMMENT ON COLUMN known_issues.log_data_pattern IS 'Optional regular expression matched against log_data';
+COMMENT ON COLUMN known_issues.failure_task_pattern IS 'Optional regular expression matched against failure_task';
+COMMENT ON COLUMN known_issues.enabled IS 'Whether incoming reports are matched against this issue';
@@ -0,0 +1,22 @@
+-- Record of redaction rules applied to submitted error reports
+-- Compatible with PostgreSQL
+
+-- Create report_redactions table
+CREATE TABLE report_redactions (
+    id SERIAL PRIMARY KEY,
+    error_report_id INTEGER NOT NULL REFERENCES error_reports(id) ON DELETE CASCADE,
+    rule_name VARCHAR(100) NOT NULL,
+    field VARCHAR(50) NOT NULL,
+    occurrences INTEGER NOT NULL
+);
+
+-- Create indexes for performance
+CREATE INDEX idx_report_redactions_error_report_id ON report_redactions(error_report_id);
+CREATE INDEX idx_report_redactions_rule_name ON report_redactions(rule_name);
+
+-- Comments for documentation
+COMMENT ON TABLE report_redactions IS 'Redaction rules that fired on each report before it was stored';
+
+COMMENT ON COLUMN report_redactions.rule_name IS 'Built-in or custom redaction rule name (e.g., url_credentials)';
+COMMENT ON COLUMN report_redactions.field IS 'Report field the rule was applied to (error_details or log_data)';
+COMMENT ON COLUMN report_redactions.occurrences IS 'Number of matches replaced in the field';
@@ -0,0 +1,28 @@
+-- Durable queue for post-processing that runs outside the submission request
+-- Compatible with PostgreSQL
+
+-- Create jobs table
+CREATE TABLE jobs (
+    id SERIAL PRIMARY KEY,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    kind VARCHAR(50) NOT NULL,
+    payload TEXT NOT NULL, -- JSON string describing the job
+    status VARCHAR(20) NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL,
+    run_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    locked_at TIMESTAMP WITH TIME ZONE,
+    last_error TEXT
+);
+
+-- Create indexes for performance
+CREATE INDEX idx_jobs_status_run_at ON jobs(status, run_at);
+CREATE INDEX idx_jobs_kind ON jobs(kind);
+
+-- Comments for documentation
+COMMENT ON TABLE jobs IS 'Background jobs queued on submission and processed by workers';
+
+COMMENT ON COLUMN jobs.kind IS 'Job type (e.g., annotate_known_issues)';
+COMMENT ON COLUMN jobs.status IS 'pending, running or dead (failed max_attempts times)';
+COMMENT ON COLUMN jobs.run_at IS 'Earliest time the job may run; pushed back after each failed attempt';
+COMMENT ON COLUMN jobs.locked_at IS 'When a worker claimed the job; stale locks are reclaimed';
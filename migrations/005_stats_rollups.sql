@@ -0,0 +1,21 @@
+-- Precomputed daily error counts used by the unfiltered statistics pages
+-- Compatible with PostgreSQL
+
+-- Create materialized rollup of error_reports per UTC day and dimension
+CREATE MATERIALIZED VIEW stats_daily_rollups AS
+SELECT
+    (created_at AT TIME ZONE 'UTC')::date AS day,
+    machine,
+    distro,
+    error_type,
+    failure_package,
+    COUNT(*)::BIGINT AS count
+FROM error_reports
+GROUP BY 1, 2, 3, 4, 5;
+
+-- A unique index is required for REFRESH MATERIALIZED VIEW CONCURRENTLY
+CREATE UNIQUE INDEX idx_stats_daily_rollups_key
+    ON stats_daily_rollups(day, machine, distro, error_type, failure_package);
+
+-- Comments for documentation
+COMMENT ON MATERIALIZED VIEW stats_daily_rollups IS 'Daily error counts per dimension, refreshed by the server when STATS_ROLLUP_REFRESH_SECS is set';
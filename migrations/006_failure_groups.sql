@@ -0,0 +1,56 @@
+-- Failure groups: reports failing the same task of the same recipe in the same way
+-- Compatible with PostgreSQL 11+
+
+-- Create failure_groups table
+CREATE TABLE failure_groups (
+    id SERIAL PRIMARY KEY,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    fingerprint VARCHAR(64) NOT NULL UNIQUE,
+    recipe VARCHAR(200) NOT NULL,
+    failure_task VARCHAR(200) NOT NULL,
+    error_type VARCHAR(100) NOT NULL
+);
+
+-- Link reports to their group
+ALTER TABLE error_reports
+    ADD COLUMN failure_group_id INTEGER REFERENCES failure_groups(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_error_reports_failure_group_id ON error_reports(failure_group_id);
+
+-- Group the existing reports. The recipe drops the "-<version>-r<revision>"
+-- suffix and the fingerprint is computed exactly as in services/failure_groups.rs
+CREATE TEMPORARY TABLE report_fingerprints AS
+SELECT
+    id AS error_report_id,
+    created_at,
+    regexp_replace(failure_package, '-[0-9][^-]*-r[0-9]+$', '') AS recipe,
+    failure_task,
+    error_type
+FROM error_reports;
+
+INSERT INTO failure_groups (created_at, fingerprint, recipe, failure_task, error_type)
+SELECT
+    MIN(created_at),
+    encode(sha256(convert_to(recipe || E'\n' || failure_task || E'\n' || error_type, 'UTF8')), 'hex'),
+    recipe,
+    failure_task,
+    error_type
+FROM report_fingerprints
+GROUP BY recipe, failure_task, error_type;
+
+UPDATE error_reports er
+SET failure_group_id = fg.id
+FROM report_fingerprints rf
+JOIN failure_groups fg
+    ON fg.recipe = rf.recipe
+   AND fg.failure_task = rf.failure_task
+   AND fg.error_type = rf.error_type
+WHERE er.id = rf.error_report_id;
+
+DROP TABLE report_fingerprints;
+
+-- Comments for documentation
+COMMENT ON TABLE failure_groups IS 'Reports grouped by recipe, failing task and error type';
+
+COMMENT ON COLUMN failure_groups.fingerprint IS 'SHA-256 of recipe, failure_task and error_type joined by newlines';
+COMMENT ON COLUMN failure_groups.recipe IS 'failure_package without its version and revision';
@@ -0,0 +1,30 @@
+-- Alerts raised when submission volume spikes above its rolling baseline
+-- Compatible with PostgreSQL
+
+-- Create alerts table
+CREATE TABLE alerts (
+    id SERIAL PRIMARY KEY,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    dimension VARCHAR(20) NOT NULL,
+    dimension_key VARCHAR(200) NOT NULL,
+    label VARCHAR(500) NOT NULL,
+    window_count BIGINT NOT NULL,
+    baseline_count BIGINT NOT NULL,
+    expected_count DOUBLE PRECISION NOT NULL,
+    acknowledged_at TIMESTAMP WITH TIME ZONE,
+    notified_at TIMESTAMP WITH TIME ZONE
+);
+
+-- Create indexes for performance
+CREATE INDEX idx_alerts_created_at ON alerts(created_at);
+CREATE INDEX idx_alerts_dimension_key ON alerts(dimension, dimension_key);
+
+-- Comments for documentation
+COMMENT ON TABLE alerts IS 'Volume spikes detected by the periodic alert check';
+
+COMMENT ON COLUMN alerts.dimension IS 'failure_group, recipe or machine';
+COMMENT ON COLUMN alerts.dimension_key IS 'Failure group ID, recipe name or machine name';
+COMMENT ON COLUMN alerts.window_count IS 'Reports in the alert window';
+COMMENT ON COLUMN alerts.baseline_count IS 'Reports in the baseline period before the window';
+COMMENT ON COLUMN alerts.expected_count IS 'Baseline volume scaled to the window length';
+COMMENT ON COLUMN alerts.notified_at IS 'When the alert was emailed to ALERT_EMAIL_TO';
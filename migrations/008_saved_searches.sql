@@ -0,0 +1,44 @@
+-- Named error list filters with short URLs and email subscriptions
+-- Compatible with PostgreSQL
+
+-- Create saved_searches table
+CREATE TABLE saved_searches (
+    id SERIAL PRIMARY KEY,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    slug VARCHAR(16) NOT NULL UNIQUE,
+    name VARCHAR(200) NOT NULL,
+    query TEXT NOT NULL,
+    owner VARCHAR(255),
+    shared BOOLEAN NOT NULL DEFAULT TRUE,
+    pinned BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+-- Create saved_search_subscriptions table
+CREATE TABLE saved_search_subscriptions (
+    id SERIAL PRIMARY KEY,
+    saved_search_id INTEGER NOT NULL REFERENCES saved_searches(id) ON DELETE CASCADE,
+    email VARCHAR(255) NOT NULL,
+    token VARCHAR(64) NOT NULL UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    confirmation_sent_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    confirmed_at TIMESTAMP WITH TIME ZONE,
+    UNIQUE (saved_search_id, email)
+);
+
+-- Create indexes for performance
+CREATE INDEX idx_saved_searches_owner ON saved_searches(owner);
+CREATE INDEX idx_saved_searches_pinned ON saved_searches(pinned);
+CREATE INDEX idx_saved_search_subscriptions_email ON saved_search_subscriptions(email, confirmation_sent_at);
+
+-- Comments for documentation
+COMMENT ON TABLE saved_searches IS 'Named error list filters, reachable under /s/<slug>';
+COMMENT ON TABLE saved_search_subscriptions IS 'Addresses emailed about new reports matching a saved search';
+
+COMMENT ON COLUMN saved_searches.slug IS 'Random identifier used in the short URL';
+COMMENT ON COLUMN saved_searches.query IS 'Query string of the /Errors filters and sort order';
+COMMENT ON COLUMN saved_searches.owner IS 'Name or email of the person who saved the search';
+COMMENT ON COLUMN saved_searches.shared IS 'Listed for the whole team rather than only under its owner';
+COMMENT ON COLUMN saved_searches.pinned IS 'Shown with its live report count on the index page';
+
+COMMENT ON COLUMN saved_search_subscriptions.token IS 'Random secret in the confirmation and unsubscribe links';
+COMMENT ON COLUMN saved_search_subscriptions.confirmed_at IS 'When the address owner confirmed; only confirmed subscriptions are notified';
@@ -0,0 +1,44 @@
+-- Tags and threaded comments for investigation notes
+-- Compatible with PostgreSQL
+
+-- Create tags table
+CREATE TABLE tags (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(50) NOT NULL UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Create error_report_tags table
+CREATE TABLE error_report_tags (
+    id SERIAL PRIMARY KEY,
+    error_report_id INTEGER NOT NULL REFERENCES error_reports(id) ON DELETE CASCADE,
+    tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (error_report_id, tag_id)
+);
+
+-- Create comments table
+CREATE TABLE comments (
+    id SERIAL PRIMARY KEY,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    error_report_id INTEGER REFERENCES error_reports(id) ON DELETE CASCADE,
+    failure_group_id INTEGER REFERENCES failure_groups(id) ON DELETE CASCADE,
+    parent_id INTEGER REFERENCES comments(id) ON DELETE CASCADE,
+    author VARCHAR(100),
+    body TEXT NOT NULL,
+    CHECK ((error_report_id IS NULL) <> (failure_group_id IS NULL))
+);
+
+-- Create indexes for performance
+CREATE INDEX idx_error_report_tags_tag_id ON error_report_tags(tag_id);
+CREATE INDEX idx_comments_error_report_id ON comments(error_report_id);
+CREATE INDEX idx_comments_failure_group_id ON comments(failure_group_id);
+
+-- Comments for documentation
+COMMENT ON TABLE tags IS 'Labels attached to error reports by triagers';
+COMMENT ON TABLE error_report_tags IS 'Tags of each error report';
+COMMENT ON TABLE comments IS 'Markdown notes on an error report or a failure group';
+
+COMMENT ON COLUMN comments.parent_id IS 'Comment this one replies to, on the same report or group';
+COMMENT ON COLUMN comments.author IS 'Name given by the commenter; not verified';
+COMMENT ON COLUMN comments.body IS 'Markdown source, sanitized when rendered';
@@ -0,0 +1,42 @@
+-- Manual curation of failure groups: fingerprint rules and an audit trail of
+-- merges and splits
+-- Compatible with PostgreSQL
+
+-- Create failure_group_rules table
+CREATE TABLE failure_group_rules (
+    id SERIAL PRIMARY KEY,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    fingerprint VARCHAR(64) NOT NULL UNIQUE,
+    failure_group_id INTEGER NOT NULL REFERENCES failure_groups(id) ON DELETE CASCADE,
+    recipe VARCHAR(200) NOT NULL,
+    failure_task VARCHAR(200) NOT NULL,
+    error_type VARCHAR(100) NOT NULL
+);
+
+-- Create failure_group_events table
+CREATE TABLE failure_group_events (
+    id SERIAL PRIMARY KEY,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    action VARCHAR(20) NOT NULL,
+    failure_group_id INTEGER NOT NULL,
+    other_group_id INTEGER,
+    report_count INTEGER NOT NULL DEFAULT 0,
+    actor VARCHAR(100),
+    details TEXT NOT NULL DEFAULT ''
+);
+
+-- Create indexes for performance
+CREATE INDEX idx_failure_group_rules_failure_group_id ON failure_group_rules(failure_group_id);
+CREATE INDEX idx_failure_group_events_failure_group_id ON failure_group_events(failure_group_id);
+CREATE INDEX idx_failure_group_events_other_group_id ON failure_group_events(other_group_id);
+CREATE INDEX idx_failure_group_events_created_at ON failure_group_events(created_at);
+
+-- Comments for documentation
+COMMENT ON TABLE failure_group_rules IS 'Fingerprints assigned to a failure group other than their own';
+COMMENT ON TABLE failure_group_events IS 'Merges, splits and rule changes made by triagers';
+
+COMMENT ON COLUMN failure_group_rules.fingerprint IS 'Fingerprint of recipe, failure task and error type, as computed for new reports';
+COMMENT ON COLUMN failure_group_events.action IS 'merge, split, add_rule or delete_rule';
+COMMENT ON COLUMN failure_group_events.failure_group_id IS 'Group that was changed; not a foreign key so history outlives the group';
+COMMENT ON COLUMN failure_group_events.other_group_id IS 'Group merged away, or the new group of a split';
+COMMENT ON COLUMN failure_group_events.actor IS 'Name given by the person making the change; not verified';
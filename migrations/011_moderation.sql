@@ -0,0 +1,71 @@
+-- Moderation: hidden reports, submission bans and a log of moderation actions
+-- Compatible with PostgreSQL
+
+-- Hidden reports are kept but left out of every public page, the API and statistics.
+-- The submitter address and token are only shown to moderators, for banning.
+ALTER TABLE error_reports
+    ADD COLUMN hidden_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN hidden_reason VARCHAR(500),
+    ADD COLUMN submitter_ip VARCHAR(45),
+    ADD COLUMN submitter_token VARCHAR(100);
+
+CREATE INDEX idx_error_reports_hidden_at ON error_reports(hidden_at);
+
+-- Statistics rollups only count visible reports
+DROP MATERIALIZED VIEW stats_daily_rollups;
+
+CREATE MATERIALIZED VIEW stats_daily_rollups AS
+SELECT
+    (created_at AT TIME ZONE 'UTC')::date AS day,
+    machine,
+    distro,
+    error_type,
+    failure_package,
+    COUNT(*)::BIGINT AS count
+FROM error_reports
+WHERE hidden_at IS NULL
+GROUP BY 1, 2, 3, 4, 5;
+
+CREATE UNIQUE INDEX idx_stats_daily_rollups_key
+    ON stats_daily_rollups(day, machine, distro, error_type, failure_package);
+
+COMMENT ON MATERIALIZED VIEW stats_daily_rollups IS 'Daily error counts per dimension, refreshed by the server when STATS_ROLLUP_REFRESH_SECS is set';
+
+-- Create submission_bans table
+CREATE TABLE submission_bans (
+    id SERIAL PRIMARY KEY,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    kind VARCHAR(10) NOT NULL,
+    value VARCHAR(255) NOT NULL,
+    reason TEXT NOT NULL DEFAULT '',
+    actor VARCHAR(100),
+    UNIQUE (kind, value)
+);
+
+-- Create moderation_actions table
+CREATE TABLE moderation_actions (
+    id SERIAL PRIMARY KEY,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    action VARCHAR(20) NOT NULL,
+    error_report_id INTEGER,
+    submission_ban_id INTEGER,
+    actor VARCHAR(100),
+    details TEXT NOT NULL DEFAULT ''
+);
+
+-- Create indexes for performance
+CREATE INDEX idx_moderation_actions_created_at ON moderation_actions(created_at);
+CREATE INDEX idx_moderation_actions_error_report_id ON moderation_actions(error_report_id);
+
+-- Comments for documentation
+COMMENT ON TABLE submission_bans IS 'Submitters whose new reports are rejected';
+COMMENT ON TABLE moderation_actions IS 'Hides, deletions and bans made by moderators';
+
+COMMENT ON COLUMN error_reports.hidden_at IS 'When a moderator hid the report; NULL for visible reports';
+COMMENT ON COLUMN error_reports.submitter_ip IS 'Address the report was submitted from';
+COMMENT ON COLUMN error_reports.submitter_token IS 'X-Submission-Token header sent with the report';
+COMMENT ON COLUMN submission_bans.kind IS 'ip, email or token';
+COMMENT ON COLUMN submission_bans.value IS 'IP address, lower case email address or submission token';
+COMMENT ON COLUMN moderation_actions.action IS 'hide, unhide, delete, ban or unban';
+COMMENT ON COLUMN moderation_actions.error_report_id IS 'Report acted on; not a foreign key so the log outlives deleted reports';
+COMMENT ON COLUMN moderation_actions.actor IS 'Name given by the moderator; not verified';
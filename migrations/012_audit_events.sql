@@ -0,0 +1,48 @@
+-- Append-only audit log of every change made through the web interface and API
+-- Compatible with PostgreSQL
+
+-- Create audit_events table
+CREATE TABLE audit_events (
+    id BIGSERIAL PRIMARY KEY,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    actor VARCHAR(100),
+    action VARCHAR(30) NOT NULL,
+    entity_type VARCHAR(30) NOT NULL,
+    entity_id VARCHAR(100),
+    before JSONB,
+    after JSONB,
+    ip VARCHAR(45),
+    user_agent VARCHAR(500),
+    method VARCHAR(10) NOT NULL,
+    path VARCHAR(2000) NOT NULL
+);
+
+-- Reject changes to recorded events
+CREATE FUNCTION audit_events_append_only() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_events_append_only
+    BEFORE UPDATE OR DELETE ON audit_events
+    FOR EACH ROW EXECUTE FUNCTION audit_events_append_only();
+
+CREATE TRIGGER audit_events_no_truncate
+    BEFORE TRUNCATE ON audit_events
+    FOR EACH STATEMENT EXECUTE FUNCTION audit_events_append_only();
+
+-- Create indexes for performance
+CREATE INDEX idx_audit_events_created_at ON audit_events(created_at);
+CREATE INDEX idx_audit_events_entity ON audit_events(entity_type, entity_id);
+CREATE INDEX idx_audit_events_action ON audit_events(action);
+CREATE INDEX idx_audit_events_actor ON audit_events(actor);
+
+-- Comments for documentation
+COMMENT ON TABLE audit_events IS 'Every change made through a mutating handler; rows can not be updated or deleted';
+
+COMMENT ON COLUMN audit_events.actor IS 'Name given by the person making the change; not verified';
+COMMENT ON COLUMN audit_events.entity_id IS 'ID of the changed entity; not a foreign key so history outlives the entity';
+COMMENT ON COLUMN audit_events.before IS 'Changed fields before the change, or the whole entity when it was deleted';
+COMMENT ON COLUMN audit_events.after IS 'Changed fields after the change, or the whole entity when it was created';
+COMMENT ON COLUMN audit_events.ip IS 'Client address, from X-Forwarded-For when TRUST_FORWARDED_FOR is set';
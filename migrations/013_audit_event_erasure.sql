@@ -0,0 +1,23 @@
+-- Let a submitter data erasure scrub email addresses from recorded audit events
+-- Compatible with PostgreSQL
+
+-- Events stay append-only, except that a transaction which has run
+-- SET LOCAL audit_events.erasure = 'on' may rewrite their entity states
+CREATE OR REPLACE FUNCTION audit_events_append_only() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'UPDATE'
+        AND current_setting('audit_events.erasure', true) = 'on'
+        AND (NEW.id, NEW.created_at, NEW.actor, NEW.action, NEW.entity_type, NEW.entity_id,
+             NEW.ip, NEW.user_agent, NEW.method, NEW.path)
+            IS NOT DISTINCT FROM
+            (OLD.id, OLD.created_at, OLD.actor, OLD.action, OLD.entity_type, OLD.entity_id,
+             OLD.ip, OLD.user_agent, OLD.method, OLD.path)
+    THEN
+        RETURN NEW;
+    END IF;
+
+    RAISE EXCEPTION 'audit_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+COMMENT ON TABLE audit_events IS 'Every change made through a mutating handler; rows can not be deleted, and only a submitter erasure may update their entity states';
@@ -0,0 +1,41 @@
+#!/bin/sh
+# Download the third-party frontend assets into static/vendor/ so the web UI
+# works without access to a CDN. Run from a machine with network access and
+# commit the result; bump the versions here to upgrade. The templates link to
+# these paths only, so a file missing here fails to load in the browser.
+set -eu
+
+BOOTSTRAP_VERSION=5.1.3
+PRISM_VERSION=1.24.1
+CHARTJS_VERSION=4.4.1
+
+cd "$(dirname "$0")/../static"
+
+fetch() {
+    mkdir -p "$(dirname "$2")"
+    echo "Fetching $2"
+    curl -fsSL -o "$2" "$1"
+}
+
+fetch "https://cdn.jsdelivr.net/npm/bootstrap@${BOOTSTRAP_VERSION}/dist/css/bootstrap.min.css" \
+    vendor/bootstrap/bootstrap.min.css
+fetch "https://cdn.jsdelivr.net/npm/bootstrap@${BOOTSTRAP_VERSION}/dist/js/bootstrap.bundle.min.js" \
+    vendor/bootstrap/bootstrap.bundle.min.js
+fetch "https://cdn.jsdelivr.net/npm/prismjs@${PRISM_VERSION}/themes/prism.min.css" \
+    vendor/prism/prism.min.css
+fetch "https://cdn.jsdelivr.net/npm/prismjs@${PRISM_VERSION}/components/prism-core.min.js" \
+    vendor/prism/prism-core.min.js
+# Grammars for build logs and tracebacks; clike must come before c
+for language in clike c bash python diff json; do
+    fetch "https://cdn.jsdelivr.net/npm/prismjs@${PRISM_VERSION}/components/prism-${language}.min.js" \
+        "vendor/prism/prism-${language}.min.js"
+done
+fetch "https://cdn.jsdelivr.net/npm/chart.js@${CHARTJS_VERSION}/dist/chart.umd.js" \
+    vendor/chart.js/chart.umd.js
+
+fetch "https://cdn.jsdelivr.net/npm/bootstrap@${BOOTSTRAP_VERSION}/LICENSE" \
+    vendor/bootstrap/LICENSE
+fetch "https://cdn.jsdelivr.net/npm/prismjs@${PRISM_VERSION}/LICENSE" \
+    vendor/prism/LICENSE
+fetch "https://cdn.jsdelivr.net/npm/chart.js@${CHARTJS_VERSION}/LICENSE.md" \
+    vendor/chart.js/LICENSE.md
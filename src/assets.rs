@@ -0,0 +1,269 @@
+//! Static files served below `/static`. Every file is also available under a
+//! name containing a hash of its content, which templates link to so that
+//! browsers can cache it indefinitely.
+
+use axum::{
+    body::Bytes,
+    extract::Path,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+use std::{borrow::Cow, collections::HashMap, sync::OnceLock};
+
+/// Hex digits of the content hash included in file names
+const HASH_LENGTH: usize = 12;
+/// Hashed names change with the content, so they never need revalidation
+const HASHED_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+const UNHASHED_CACHE_CONTROL: &str = "no-cache";
+
+static ASSETS: OnceLock<Assets> = OnceLock::new();
+
+#[cfg(feature = "embed-assets")]
+#[derive(rust_embed::RustEmbed)]
+#[folder = "static/"]
+struct EmbeddedStatic;
+
+struct Asset {
+    data: Bytes,
+    hash: String,
+    content_type: &'static str,
+}
+
+pub struct Assets {
+    /// Files by their path below the static directory
+    files: HashMap<String, Asset>,
+    /// Hashed paths and the file they stand for
+    hashed_paths: HashMap<String, String>,
+}
+
+impl Assets {
+    fn from_files(files: impl IntoIterator<Item = (String, Cow<'static, [u8]>)>) -> Self {
+        let mut assets = Self {
+            files: HashMap::new(),
+            hashed_paths: HashMap::new(),
+        };
+        for (path, data) in files {
+            let hash = hex::encode(Sha256::digest(&data))[..HASH_LENGTH].to_string();
+            assets
+                .hashed_paths
+                .insert(hashed_path(&path, &hash), path.clone());
+            let data = match data {
+                Cow::Borrowed(data) => Bytes::from_static(data),
+                Cow::Owned(data) => Bytes::from(data),
+            };
+            assets.files.insert(
+                path.clone(),
+                Asset {
+                    data,
+                    hash,
+                    content_type: content_type(&path),
+                },
+            );
+        }
+        assets
+    }
+
+    /// Files compiled into the binary; `STATIC_DIR` is not used
+    #[cfg(feature = "embed-assets")]
+    fn load(_static_dir: &str) -> Self {
+        Self::from_files(EmbeddedStatic::iter().filter_map(|path| {
+            let file = EmbeddedStatic::get(&path)?;
+            Some((path.into_owned(), file.data))
+        }))
+    }
+
+    /// Files read from `static_dir` once at startup
+    #[cfg(not(feature = "embed-assets"))]
+    fn load(static_dir: &str) -> Self {
+        let mut files = Vec::new();
+        if let Err(e) = read_files(std::path::Path::new(static_dir), "", &mut files) {
+            tracing::warn!("Failed to read static files from {}: {}", static_dir, e);
+        }
+        Self::from_files(files)
+    }
+
+    fn hashed(&self, path: &str) -> Option<String> {
+        self.files
+            .get(path)
+            .map(|asset| hashed_path(path, &asset.hash))
+    }
+
+    /// The file for a requested path, and whether it was requested by its hashed name
+    fn find(&self, requested: &str) -> Option<(&Asset, bool)> {
+        if let Some(asset) = self
+            .hashed_paths
+            .get(requested)
+            .and_then(|path| self.files.get(path))
+        {
+            return Some((asset, true));
+        }
+        self.files.get(requested).map(|asset| (asset, false))
+    }
+}
+
+#[cfg(not(feature = "embed-assets"))]
+fn read_files(
+    dir: &std::path::Path,
+    prefix: &str,
+    files: &mut Vec<(String, Cow<'static, [u8]>)>,
+) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let path = format!("{}{}", prefix, name);
+        if entry.file_type()?.is_dir() {
+            read_files(&entry.path(), &format!("{}/", path), files)?;
+        } else {
+            files.push((path, Cow::Owned(std::fs::read(entry.path())?)));
+        }
+    }
+    Ok(())
+}
+
+/// Load the static files; later calls have no effect
+pub fn init(static_dir: &str) {
+    ASSETS.get_or_init(|| Assets::load(static_dir));
+}
+
+/// Hashed path of a file below `/static`, for use in templates. Unknown files
+/// keep their path.
+pub fn asset_path(path: &str) -> String {
+    ASSETS
+        .get()
+        .and_then(|assets| assets.hashed(path))
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// Full URL of a file below `/static`, for use in templates
+pub fn asset_url(base_url: &str, path: &str) -> String {
+    url_for(ASSETS.get(), base_url, path)
+}
+
+fn url_for(assets: Option<&Assets>, base_url: &str, path: &str) -> String {
+    let path = assets
+        .and_then(|assets| assets.hashed(path))
+        .unwrap_or_else(|| path.to_string());
+    format!("{}/static/{}", base_url, path)
+}
+
+/// Serve a static file by its plain or hashed path
+pub async fn serve_asset(Path(path): Path<String>, headers: HeaderMap) -> Response {
+    let Some((asset, hashed)) = ASSETS.get().and_then(|assets| assets.find(&path)) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let etag = format!("\"{}\"", asset.hash);
+    let cache_control = if hashed {
+        HASHED_CACHE_CONTROL
+    } else {
+        UNHASHED_CACHE_CONTROL
+    };
+
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|tag| tag.trim() == etag));
+    if not_modified {
+        return (
+            StatusCode::NOT_MODIFIED,
+            [
+                (header::ETAG, etag),
+                (header::CACHE_CONTROL, cache_control.to_string()),
+            ],
+        )
+            .into_response();
+    }
+
+    (
+        [
+            (header::CONTENT_TYPE, asset.content_type.to_string()),
+            (header::ETAG, etag),
+            (header::CACHE_CONTROL, cache_control.to_string()),
+        ],
+        asset.data.clone(),
+    )
+        .into_response()
+}
+
+/// `css/site.css` with hash `abc` becomes `css/site.abc.css`
+fn hashed_path(path: &str, hash: &str) -> String {
+    let file_start = path.rfind('/').map_or(0, |index| index + 1);
+    match path[file_start..].rfind('.') {
+        Some(dot) if dot > 0 => {
+            let dot = file_start + dot;
+            format!("{}.{}{}", &path[..dot], hash, &path[dot..])
+        }
+        _ => format!("{}.{}", path, hash),
+    }
+}
+
+fn content_type(path: &str) -> &'static str {
+    let extension = path.rsplit_once('.').map_or("", |(_, extension)| extension);
+    match extension {
+        "css" => "text/css; charset=utf-8",
+        "js" => "text/javascript; charset=utf-8",
+        "json" | "map" => "application/json",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "txt" | "md" => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hashed_path() {
+        assert_eq!(hashed_path("css/custom.css", "abc"), "css/custom.abc.css");
+        assert_eq!(
+            hashed_path("vendor/bootstrap/bootstrap.min.css", "abc"),
+            "vendor/bootstrap/bootstrap.min.abc.css"
+        );
+        assert_eq!(hashed_path("LICENSE", "abc"), "LICENSE.abc");
+        assert_eq!(hashed_path("v1.0/.hidden", "abc"), "v1.0/.hidden.abc");
+    }
+
+    #[test]
+    fn test_find_by_plain_and_hashed_path() {
+        let assets = Assets::from_files([(
+            "css/custom.css".to_string(),
+            Cow::Borrowed(b"body {}".as_slice()),
+        )]);
+
+        let hashed = assets.hashed("css/custom.css").unwrap();
+        assert_ne!(hashed, "css/custom.css");
+        assert!(matches!(assets.find(&hashed), Some((_, true))));
+        assert!(matches!(assets.find("css/custom.css"), Some((_, false))));
+        assert!(assets.find("css/missing.css").is_none());
+        assert_eq!(
+            assets.find("css/custom.css").unwrap().0.content_type,
+            "text/css; charset=utf-8"
+        );
+    }
+
+    #[test]
+    fn test_asset_url() {
+        let assets = Assets::from_files([(
+            "vendor/bootstrap/bootstrap.min.css".to_string(),
+            Cow::Borrowed(b"body {}".as_slice()),
+        )]);
+
+        let vendored = url_for(
+            Some(&assets),
+            "/errors",
+            "vendor/bootstrap/bootstrap.min.css",
+        );
+        assert!(vendored.starts_with("/errors/static/vendor/bootstrap/bootstrap.min."));
+        // A missing file is linked as is, so it shows up as a 404 rather than loading elsewhere
+        assert_eq!(
+            url_for(Some(&assets), "/errors", "vendor/chart.js/chart.umd.js"),
+            "/errors/static/vendor/chart.js/chart.umd.js"
+        );
+    }
+}
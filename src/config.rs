@@ -12,6 +12,16 @@ pub struct Config {
     pub max_upload_size: usize,
     pub bugzilla_url: String,
     pub email: EmailConfig,
+    pub redaction: RedactionConfig,
+    pub submitter_email_visibility: EmailVisibility,
+    /// Secret that published email hashes are keyed with
+    pub email_hash_key: Option<String>,
+    /// Take the submitter address from `X-Forwarded-For`, set by a reverse proxy
+    pub trust_forwarded_for: bool,
+    pub retention: RetentionConfig,
+    pub jobs: JobsConfig,
+    pub stats: StatsConfig,
+    pub alerts: AlertsConfig,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -23,8 +33,109 @@ pub struct EmailConfig {
     pub from_address: String,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct RedactionConfig {
+    pub enabled: bool,
+    pub custom_rules: Vec<CustomRedactionRule>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CustomRedactionRule {
+    pub name: String,
+    pub pattern: String,
+}
+
+/// Retention rules applied by the background purge task and `purge` subcommand.
+/// A rule left unset keeps data forever.
+#[derive(Debug, Deserialize, Clone)]
+pub struct RetentionConfig {
+    pub log_data_days: Option<u32>,
+    pub report_days: Option<u32>,
+    pub keep_bug_linked: bool,
+    pub purge_interval_hours: u64,
+}
+
+impl RetentionConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.log_data_days.is_some() || self.report_days.is_some()
+    }
+}
+
+/// Background job queue settings. `workers` is the number of workers started
+/// inside the web server; set it to 0 when running `worker` processes separately.
+#[derive(Debug, Deserialize, Clone)]
+pub struct JobsConfig {
+    pub workers: usize,
+    pub max_attempts: i32,
+    pub poll_interval_secs: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct StatsConfig {
+    /// Default length of the "top N" lists, overridable per request with `?top=`
+    pub top_n: u64,
+    /// How long computed statistics are reused; 0 disables the cache
+    pub cache_ttl_secs: u64,
+    /// Refresh interval of the rollup view; rollups are not used when unset
+    pub rollup_refresh_secs: Option<u64>,
+}
+
+/// Spike detection on submission volume. Detection is off unless
+/// `check_interval_minutes` is set; alerts are emailed to `recipients`.
+#[derive(Debug, Deserialize, Clone)]
+pub struct AlertsConfig {
+    pub check_interval_minutes: Option<u64>,
+    /// Recent period whose volume is checked
+    pub window_hours: u32,
+    /// Period before the window used as the rolling baseline
+    pub baseline_days: u32,
+    /// Alert when the window volume exceeds the expected volume this many times
+    pub threshold: f64,
+    /// Ignore windows with fewer reports than this
+    pub min_count: i64,
+    pub recipients: Vec<String>,
+}
+
+impl AlertsConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.check_interval_minutes.is_some()
+    }
+}
+
+/// How submitter email addresses are shown in public views and the JSON API
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailVisibility {
+    Show,
+    Hide,
+    Hash,
+}
+
+impl std::str::FromStr for EmailVisibility {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "show" => Ok(EmailVisibility::Show),
+            "hide" => Ok(EmailVisibility::Hide),
+            "hash" => Ok(EmailVisibility::Hash),
+            _ => Err(ConfigError::InvalidEmailVisibility),
+        }
+    }
+}
+
 impl Config {
     pub fn from_env() -> Result<Self, ConfigError> {
+        let submitter_email_visibility: EmailVisibility = env::var("SUBMITTER_EMAIL_VISIBILITY")
+            .unwrap_or_else(|_| "show".to_string())
+            .parse()?;
+        let email_hash_key = env::var("EMAIL_HASH_KEY")
+            .ok()
+            .filter(|key| !key.is_empty());
+        if submitter_email_visibility == EmailVisibility::Hash && email_hash_key.is_none() {
+            return Err(ConfigError::MissingEnvVar("EMAIL_HASH_KEY"));
+        }
+
         Ok(Config {
             database_url: env::var("DATABASE_URL")
                 .map_err(|_| ConfigError::MissingEnvVar("DATABASE_URL"))?,
@@ -53,10 +164,171 @@ impl Config {
                 from_address: env::var("EMAIL_FROM")
                     .map_err(|_| ConfigError::MissingEnvVar("EMAIL_FROM"))?,
             },
+            redaction: RedactionConfig {
+                enabled: env::var("REDACTION_ENABLED")
+                    .unwrap_or_else(|_| "true".to_string())
+                    .parse()
+                    .map_err(|_| ConfigError::InvalidRedactionEnabled)?,
+                custom_rules: match env::var("REDACTION_RULES_FILE") {
+                    Ok(path) if !path.is_empty() => load_redaction_rules(&path)?,
+                    _ => Vec::new(),
+                },
+            },
+            submitter_email_visibility,
+            email_hash_key,
+            trust_forwarded_for: env::var("TRUST_FORWARDED_FOR")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .map_err(|_| ConfigError::InvalidTrustForwardedFor)?,
+            retention: RetentionConfig {
+                log_data_days: optional_days("RETENTION_LOG_DATA_DAYS")?,
+                report_days: optional_days("RETENTION_REPORT_DAYS")?,
+                keep_bug_linked: env::var("RETENTION_KEEP_BUG_LINKED")
+                    .unwrap_or_else(|_| "true".to_string())
+                    .parse()
+                    .map_err(|_| ConfigError::InvalidRetention("RETENTION_KEEP_BUG_LINKED"))?,
+                purge_interval_hours: env::var("RETENTION_PURGE_INTERVAL_HOURS")
+                    .unwrap_or_else(|_| "24".to_string())
+                    .parse()
+                    .ok()
+                    .filter(|hours| *hours > 0)
+                    .ok_or(ConfigError::InvalidRetention(
+                        "RETENTION_PURGE_INTERVAL_HOURS",
+                    ))?,
+            },
+            jobs: JobsConfig {
+                workers: env::var("JOB_WORKERS")
+                    .unwrap_or_else(|_| "1".to_string())
+                    .parse()
+                    .map_err(|_| ConfigError::InvalidJobs("JOB_WORKERS"))?,
+                max_attempts: env::var("JOB_MAX_ATTEMPTS")
+                    .unwrap_or_else(|_| "5".to_string())
+                    .parse()
+                    .ok()
+                    .filter(|attempts| *attempts > 0)
+                    .ok_or(ConfigError::InvalidJobs("JOB_MAX_ATTEMPTS"))?,
+                poll_interval_secs: env::var("JOB_POLL_INTERVAL_SECS")
+                    .unwrap_or_else(|_| "5".to_string())
+                    .parse()
+                    .ok()
+                    .filter(|secs| *secs > 0)
+                    .ok_or(ConfigError::InvalidJobs("JOB_POLL_INTERVAL_SECS"))?,
+            },
+            stats: StatsConfig {
+                top_n: env::var("STATS_TOP_N")
+                    .unwrap_or_else(|_| "10".to_string())
+                    .parse()
+                    .ok()
+                    .filter(|top_n| (1..=100).contains(top_n))
+                    .ok_or(ConfigError::InvalidStats("STATS_TOP_N"))?,
+                cache_ttl_secs: env::var("STATS_CACHE_TTL_SECS")
+                    .unwrap_or_else(|_| "60".to_string())
+                    .parse()
+                    .map_err(|_| ConfigError::InvalidStats("STATS_CACHE_TTL_SECS"))?,
+                rollup_refresh_secs: match env::var("STATS_ROLLUP_REFRESH_SECS") {
+                    Ok(value) if !value.is_empty() => Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|secs| *secs > 0)
+                            .ok_or(ConfigError::InvalidStats("STATS_ROLLUP_REFRESH_SECS"))?,
+                    ),
+                    _ => None,
+                },
+            },
+            alerts: AlertsConfig {
+                check_interval_minutes: match env::var("ALERT_CHECK_INTERVAL_MINUTES") {
+                    Ok(value) if !value.is_empty() => {
+                        Some(
+                            value.parse().ok().filter(|minutes| *minutes > 0).ok_or(
+                                ConfigError::InvalidAlerts("ALERT_CHECK_INTERVAL_MINUTES"),
+                            )?,
+                        )
+                    }
+                    _ => None,
+                },
+                window_hours: env::var("ALERT_WINDOW_HOURS")
+                    .unwrap_or_else(|_| "24".to_string())
+                    .parse()
+                    .ok()
+                    .filter(|hours| *hours > 0)
+                    .ok_or(ConfigError::InvalidAlerts("ALERT_WINDOW_HOURS"))?,
+                baseline_days: env::var("ALERT_BASELINE_DAYS")
+                    .unwrap_or_else(|_| "14".to_string())
+                    .parse()
+                    .ok()
+                    .filter(|days| *days > 0)
+                    .ok_or(ConfigError::InvalidAlerts("ALERT_BASELINE_DAYS"))?,
+                threshold: env::var("ALERT_THRESHOLD")
+                    .unwrap_or_else(|_| "3.0".to_string())
+                    .parse()
+                    .ok()
+                    .filter(|threshold: &f64| *threshold > 1.0)
+                    .ok_or(ConfigError::InvalidAlerts("ALERT_THRESHOLD"))?,
+                min_count: env::var("ALERT_MIN_COUNT")
+                    .unwrap_or_else(|_| "10".to_string())
+                    .parse()
+                    .ok()
+                    .filter(|count| *count > 0)
+                    .ok_or(ConfigError::InvalidAlerts("ALERT_MIN_COUNT"))?,
+                recipients: env::var("ALERT_EMAIL_TO")
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|address| !address.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            },
         })
     }
 }
 
+/// Parse an optional number of days; unset or empty means "keep forever"
+fn optional_days(name: &'static str) -> Result<Option<u32>, ConfigError> {
+    match env::var(name) {
+        Ok(value) if !value.is_empty() => value
+            .parse()
+            .map(Some)
+            .map_err(|_| ConfigError::InvalidRetention(name)),
+        _ => Ok(None),
+    }
+}
+
+/// Load custom redaction rules from a file containing one `name = regex` rule
+/// per line. Blank lines and lines starting with `#` are ignored.
+fn load_redaction_rules(path: &str) -> Result<Vec<CustomRedactionRule>, ConfigError> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|_| ConfigError::InvalidRedactionRules(format!("cannot read {}", path)))?;
+
+    parse_redaction_rules(&contents)
+}
+
+fn parse_redaction_rules(contents: &str) -> Result<Vec<CustomRedactionRule>, ConfigError> {
+    contents
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line_number, line)| {
+            let (name, pattern) = line
+                .split_once('=')
+                .map(|(name, pattern)| (name.trim(), pattern.trim()))
+                .filter(|(name, pattern)| !name.is_empty() && !pattern.is_empty())
+                .ok_or_else(|| {
+                    ConfigError::InvalidRedactionRules(format!(
+                        "line {} is not of the form `name = regex`",
+                        line_number
+                    ))
+                })?;
+
+            Ok(CustomRedactionRule {
+                name: name.to_string(),
+                pattern: pattern.to_string(),
+            })
+        })
+        .collect()
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ConfigError {
     #[error("Missing required environment variable: {0}")]
@@ -67,4 +339,45 @@ pub enum ConfigError {
     InvalidEmailPort,
     #[error("Invalid upload size")]
     InvalidUploadSize,
+    #[error("Invalid REDACTION_ENABLED value (expected true or false)")]
+    InvalidRedactionEnabled,
+    #[error("Invalid redaction rules: {0}")]
+    InvalidRedactionRules(String),
+    #[error("Invalid SUBMITTER_EMAIL_VISIBILITY value (expected show, hide or hash)")]
+    InvalidEmailVisibility,
+    #[error("Invalid TRUST_FORWARDED_FOR value (expected true or false)")]
+    InvalidTrustForwardedFor,
+    #[error("Invalid retention setting: {0}")]
+    InvalidRetention(&'static str),
+    #[error("Invalid job queue setting: {0}")]
+    InvalidJobs(&'static str),
+    #[error("Invalid statistics setting: {0}")]
+    InvalidStats(&'static str),
+    #[error("Invalid alert setting: {0}")]
+    InvalidAlerts(&'static str),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_redaction_rules() {
+        let rules = parse_redaction_rules(
+            "# internal hosts\n\ninternal_host = [a-z0-9-]+\\.corp\\.example\\.com\nticket = TKT-[0-9]+=x\n",
+        )
+        .unwrap();
+
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].name, "internal_host");
+        assert_eq!(rules[0].pattern, r"[a-z0-9-]+\.corp\.example\.com");
+        // Only the first `=` separates the name from the pattern
+        assert_eq!(rules[1].pattern, "TKT-[0-9]+=x");
+    }
+
+    #[test]
+    fn test_parse_redaction_rules_rejects_malformed_line() {
+        assert!(parse_redaction_rules("just-a-pattern").is_err());
+        assert!(parse_redaction_rules("name =").is_err());
+    }
 }
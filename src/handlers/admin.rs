@@ -1,8 +1,66 @@
 use askama::Template;
-use axum::{extract::State, http::StatusCode, response::Html};
-use tracing::error;
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Redirect, Response},
+    Form, Json,
+};
+use axum_extra::extract::Query as FilterQuery;
+use chrono::Utc;
+use futures::StreamExt;
+use serde::Deserialize;
+use tracing::{error, info};
 
-use crate::{services::stats::StatsService, AppState};
+use crate::{
+    config::EmailVisibility,
+    handlers::{begin, commit, web::ErrorFilters},
+    models::{
+        alert,
+        audit_event::{self, AuditQuery},
+        error_report::{self, ErrorListResponse, ErrorQuery, Visibility},
+        failure_group::{self, MergeGroupData, SplitGroupData},
+        failure_group_event,
+        failure_group_rule::{self, FailureGroupRuleData},
+        job,
+        known_issue::{self, KnownIssueData},
+        moderation_action::{self, ModerationData},
+        submission_ban::{self, BanData},
+    },
+    services::{
+        alerts::AlertService,
+        audit::{AuditEntry, AuditPage, AuditService},
+        error_list::ErrorListService,
+        failure_groups::{CurationError, FailureGroupService, GroupActivity, GroupSummary},
+        jobs::{JobCounts, JobService},
+        known_issues::KnownIssueService,
+        moderation::{ModerationError, ModerationService},
+        saved_searches::{SavedSearchEntry, SavedSearchService},
+        stats::{StatsOptions, StatsService},
+        submitters::{ErasureSummary, SubmitterService},
+    },
+    utils::{
+        client::RequestMeta,
+        validation::{
+            validate_ban, validate_group_rule, validate_known_issue, validate_merge_group,
+            validate_moderation, validate_split_group,
+        },
+    },
+    AppState,
+};
+
+/// Groups listed on the failure group admin page
+const GROUP_LIST_LIMIT: u64 = 100;
+/// Reports listed for splitting on a failure group's admin page
+const GROUP_REPORT_LIMIT: u64 = 200;
+/// Changes listed on the failure group admin page
+const GROUP_EVENT_LIMIT: u64 = 20;
+/// Reports per page of the moderation list
+const MODERATION_PAGE_SIZE: u64 = 50;
+/// Entries of the moderation log shown
+const MODERATION_LOG_LIMIT: u64 = 50;
+/// Events per page of the audit log
+const AUDIT_PAGE_SIZE: u64 = 50;
 
 #[derive(Template)]
 #[template(path = "admin_dashboard.html")]
@@ -11,16 +69,215 @@ pub struct AdminDashboardTemplate {
     pub base_url: String,
 }
 
+#[derive(Template)]
+#[template(path = "admin_known_issues.html")]
+pub struct AdminKnownIssuesTemplate {
+    pub issues: Vec<known_issue::Model>,
+    pub base_url: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin_known_issue_form.html")]
+pub struct AdminKnownIssueFormTemplate {
+    pub form: KnownIssueForm,
+    pub error_message: Option<String>,
+    pub base_url: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin_submitters.html")]
+pub struct AdminSubmittersTemplate {
+    pub erasure: Option<ErasureSummary>,
+    pub base_url: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin_jobs.html")]
+pub struct AdminJobsTemplate {
+    pub counts: JobCounts,
+    pub dead_jobs: Vec<job::Model>,
+    pub base_url: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin_alerts.html")]
+pub struct AdminAlertsTemplate {
+    pub alerts: Vec<alert::Model>,
+    pub detection_enabled: bool,
+    pub notifications_enabled: bool,
+    pub base_url: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin_groups.html")]
+pub struct AdminGroupsTemplate {
+    pub groups: Vec<GroupSummary>,
+    pub recipe: String,
+    pub events: Vec<failure_group_event::Model>,
+    pub base_url: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin_group.html")]
+pub struct AdminGroupTemplate {
+    pub group: failure_group::Model,
+    pub activity: GroupActivity,
+    pub reports: Vec<error_report::Model>,
+    pub rules: Vec<failure_group_rule::Model>,
+    pub events: Vec<failure_group_event::Model>,
+    pub error_message: Option<String>,
+    pub base_url: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin_moderation.html")]
+pub struct AdminModerationTemplate {
+    pub reports: ErrorListResponse,
+    pub filters: ErrorFilters,
+    pub submitter: String,
+    pub status: &'static str,
+    /// Query string of the filters, sort order and page size, without the
+    /// visibility
+    pub listing_query: String,
+    pub actions: Vec<moderation_action::Model>,
+    pub error_message: Option<String>,
+    pub base_url: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin_bans.html")]
+pub struct AdminBansTemplate {
+    pub bans: Vec<submission_ban::Model>,
+    pub kinds: [&'static str; 3],
+    pub kind: String,
+    pub value: String,
+    pub error_message: Option<String>,
+    pub base_url: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin_saved_searches.html")]
+pub struct AdminSavedSearchesTemplate {
+    pub searches: Vec<SavedSearchEntry>,
+    pub base_url: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin_audit.html")]
+pub struct AdminAuditTemplate {
+    pub audit: AuditPage,
+    pub query: AuditQuery,
+    pub actions: [&'static str; 15],
+    pub entity_types: [&'static str; 10],
+    /// Query string of the filters for pagination and export links
+    pub filter_query: String,
+    pub base_url: String,
+}
+
+impl AdminAuditTemplate {
+    fn value<'a>(&self, value: &'a Option<String>) -> &'a str {
+        value.as_deref().unwrap_or_default()
+    }
+
+    fn is_selected(&self, value: &Option<String>, option: &str) -> bool {
+        value.as_deref() == Some(option)
+    }
+}
+
+impl AdminBansTemplate {
+    fn is_kind(&self, kind: &str) -> bool {
+        self.kind == kind
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModerationParams {
+    pub status: Option<Visibility>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BanParams {
+    pub kind: Option<String>,
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GroupListParams {
+    pub recipe: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ActorForm {
+    pub actor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubmitterEmailForm {
+    pub email: String,
+    pub actor: Option<String>,
+}
+
+/// Values shown in the known issue form, for both creating and editing
+#[derive(Debug, Default)]
+pub struct KnownIssueForm {
+    pub id: Option<i32>,
+    pub title: String,
+    pub explanation: String,
+    pub link: String,
+    pub error_details_pattern: String,
+    pub log_data_pattern: String,
+    pub failure_task_pattern: String,
+    pub enabled: bool,
+    pub actor: String,
+}
+
+impl KnownIssueForm {
+    pub fn from_model(issue: &known_issue::Model) -> Self {
+        Self {
+            id: Some(issue.id),
+            title: issue.title.clone(),
+            explanation: issue.explanation.clone(),
+            link: issue.link.clone().unwrap_or_default(),
+            error_details_pattern: issue.error_details_pattern.clone().unwrap_or_default(),
+            log_data_pattern: issue.log_data_pattern.clone().unwrap_or_default(),
+            failure_task_pattern: issue.failure_task_pattern.clone().unwrap_or_default(),
+            enabled: issue.enabled,
+            actor: String::new(),
+        }
+    }
+
+    pub fn from_data(id: Option<i32>, data: &KnownIssueData) -> Self {
+        Self {
+            id,
+            title: data.title.clone(),
+            explanation: data.explanation.clone(),
+            link: data.link.clone().unwrap_or_default(),
+            error_details_pattern: data.error_details_pattern.clone().unwrap_or_default(),
+            log_data_pattern: data.log_data_pattern.clone().unwrap_or_default(),
+            failure_task_pattern: data.failure_task_pattern.clone().unwrap_or_default(),
+            enabled: data.enabled.is_some(),
+            actor: data.actor.clone().unwrap_or_default(),
+        }
+    }
+}
+
 /// Admin dashboard with comprehensive statistics
 pub async fn admin_dashboard(
     State(app_state): State<AppState>,
 ) -> Result<Html<String>, StatusCode> {
-    let stats_service = StatsService::new(app_state.db.clone());
+    let stats_service = StatsService::new(app_state.db.clone())
+        .with_rollups(app_state.config.stats.rollup_refresh_secs.is_some());
 
-    let stats = stats_service.get_error_stats().await.map_err(|e| {
-        error!("Failed to get admin stats: {:?}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    let stats = stats_service
+        .get_cached_error_stats(
+            &app_state.stats_cache,
+            &StatsOptions::unfiltered(app_state.config.stats.top_n),
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to get admin stats: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
     let template = AdminDashboardTemplate {
         stats,
@@ -34,3 +291,1289 @@ pub async fn admin_dashboard(
 
     Ok(Html(html))
 }
+
+/// List all known issues
+pub async fn known_issues_page(
+    State(app_state): State<AppState>,
+) -> Result<Html<String>, StatusCode> {
+    let issues = KnownIssueService::new(app_state.db.clone())
+        .list()
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch known issues: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let template = AdminKnownIssuesTemplate {
+        issues,
+        base_url: app_state.config.base_url.clone(),
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Admin template rendering failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Html(html))
+}
+
+/// Form for creating a new known issue
+pub async fn new_known_issue_page(
+    State(app_state): State<AppState>,
+) -> Result<Html<String>, StatusCode> {
+    let form = KnownIssueForm {
+        enabled: true,
+        ..Default::default()
+    };
+    render_known_issue_form(&app_state, form, None)
+}
+
+/// Form for editing an existing known issue
+pub async fn edit_known_issue_page(
+    State(app_state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<Html<String>, StatusCode> {
+    let issue = KnownIssueService::new(app_state.db.clone())
+        .get(id)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch known issue {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    render_known_issue_form(&app_state, KnownIssueForm::from_model(&issue), None)
+}
+
+/// Create a known issue from the admin form
+pub async fn create_known_issue(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Form(data): Form<KnownIssueData>,
+) -> Result<Response, StatusCode> {
+    if let Err(validation_error) = validate_known_issue(&data) {
+        let form = KnownIssueForm::from_data(None, &data);
+        let html = render_known_issue_form(&app_state, form, Some(validation_error.to_string()))?;
+        return Ok((StatusCode::BAD_REQUEST, html).into_response());
+    }
+
+    let actor = data.actor.clone();
+    let txn = begin(&app_state.db).await?;
+    let issue = KnownIssueService::new(app_state.db.clone())
+        .create(&txn, data)
+        .await
+        .map_err(|e| {
+            error!("Failed to save known issue: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("Created known issue {}: {}", issue.id, issue.title);
+    AuditService::record(
+        &txn,
+        &meta,
+        AuditEntry::new(audit_event::ACTION_CREATE, audit_event::ENTITY_KNOWN_ISSUE)
+            .entity(issue.id)
+            .actor(actor.as_deref())
+            .after(&issue),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to record audit event: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    commit(txn).await?;
+
+    Ok(Redirect::to(&format!("{}/admin/known-issues", app_state.config.base_url)).into_response())
+}
+
+/// Update a known issue from the admin form
+pub async fn update_known_issue(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path(id): Path<i32>,
+    Form(data): Form<KnownIssueData>,
+) -> Result<Response, StatusCode> {
+    if let Err(validation_error) = validate_known_issue(&data) {
+        let form = KnownIssueForm::from_data(Some(id), &data);
+        let html = render_known_issue_form(&app_state, form, Some(validation_error.to_string()))?;
+        return Ok((StatusCode::BAD_REQUEST, html).into_response());
+    }
+
+    let known_issue_service = KnownIssueService::new(app_state.db.clone());
+    let before = known_issue_service
+        .get(id)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch known issue {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let actor = data.actor.clone();
+    let txn = begin(&app_state.db).await?;
+    let issue = known_issue_service
+        .update(&txn, id, data)
+        .await
+        .map_err(|e| {
+            error!("Failed to update known issue {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    info!("Updated known issue {}", id);
+    AuditService::record(
+        &txn,
+        &meta,
+        AuditEntry::new(audit_event::ACTION_UPDATE, audit_event::ENTITY_KNOWN_ISSUE)
+            .entity(id)
+            .actor(actor.as_deref())
+            .before(&before)
+            .after(&issue),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to record audit event: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    commit(txn).await?;
+
+    Ok(Redirect::to(&format!("{}/admin/known-issues", app_state.config.base_url)).into_response())
+}
+
+/// Delete a known issue and its report annotations
+pub async fn delete_known_issue(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path(id): Path<i32>,
+    Form(form): Form<ActorForm>,
+) -> Result<Redirect, StatusCode> {
+    let known_issue_service = KnownIssueService::new(app_state.db.clone());
+    let issue = known_issue_service
+        .get(id)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch known issue {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let txn = begin(&app_state.db).await?;
+    let deleted = known_issue_service.delete(&txn, id).await.map_err(|e| {
+        error!("Failed to delete known issue {}: {:?}", id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if !deleted {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    info!("Deleted known issue {}", id);
+    AuditService::record(
+        &txn,
+        &meta,
+        AuditEntry::new(audit_event::ACTION_DELETE, audit_event::ENTITY_KNOWN_ISSUE)
+            .entity(id)
+            .actor(form.actor.as_deref())
+            .before(&issue),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to record audit event: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    commit(txn).await?;
+
+    Ok(Redirect::to(&format!(
+        "{}/admin/known-issues",
+        app_state.config.base_url
+    )))
+}
+
+fn render_known_issue_form(
+    app_state: &AppState,
+    form: KnownIssueForm,
+    error_message: Option<String>,
+) -> Result<Html<String>, StatusCode> {
+    let template = AdminKnownIssueFormTemplate {
+        form,
+        error_message,
+        base_url: app_state.config.base_url.clone(),
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Admin template rendering failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Html(html))
+}
+
+/// Submitter data export and erasure tools
+pub async fn submitters_page(
+    State(app_state): State<AppState>,
+) -> Result<Html<String>, StatusCode> {
+    render_submitters_page(&app_state, None)
+}
+
+/// Download everything stored about a submitter email as JSON
+pub async fn export_submitter(
+    State(app_state): State<AppState>,
+    Query(params): Query<SubmitterEmailForm>,
+) -> Result<Response, StatusCode> {
+    if params.email.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let export = SubmitterService::new(app_state.db.clone())
+        .export(&params.email)
+        .await
+        .map_err(|e| {
+            error!("Failed to export submitter data: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!(
+        "Exported {} reports for a submitter data request",
+        export.reports.len()
+    );
+
+    Ok((
+        [(
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"submitter-export.json\"",
+        )],
+        Json(export),
+    )
+        .into_response())
+}
+
+/// Anonymize a submitter email across reports, subscriptions, bans and logs
+pub async fn erase_submitter(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Form(form): Form<SubmitterEmailForm>,
+) -> Result<Html<String>, StatusCode> {
+    if form.email.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let txn = begin(&app_state.db).await?;
+    let summary = SubmitterService::new(app_state.db.clone())
+        .erase(&txn, &form.email)
+        .await
+        .map_err(|e| {
+            error!("Failed to erase submitter data: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    // Cached top submitter lists may still contain the erased email
+    app_state.stats_cache.invalidate();
+
+    info!(
+        "Erased submitter data: {} reports anonymized, {} scrubbed",
+        summary.reports_anonymized, summary.reports_scrubbed
+    );
+    // The summary leaves out the email, or it would survive the erasure
+    AuditService::record(
+        &txn,
+        &meta,
+        AuditEntry::new(audit_event::ACTION_ERASE, audit_event::ENTITY_SUBMITTER)
+            .actor(form.actor.as_deref())
+            .after(&summary),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to record audit event: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    commit(txn).await?;
+    // Statistics computed while the erasure was uncommitted still had the email
+    app_state.stats_cache.invalidate();
+
+    render_submitters_page(&app_state, Some(summary))
+}
+
+fn render_submitters_page(
+    app_state: &AppState,
+    erasure: Option<ErasureSummary>,
+) -> Result<Html<String>, StatusCode> {
+    let template = AdminSubmittersTemplate {
+        erasure,
+        base_url: app_state.config.base_url.clone(),
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Admin template rendering failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Html(html))
+}
+
+/// Background job queue status and dead-letter list
+pub async fn jobs_page(State(app_state): State<AppState>) -> Result<Html<String>, StatusCode> {
+    let job_service = JobService::new(app_state.db.clone(), app_state.config.jobs.clone());
+
+    let counts = job_service.counts().await.map_err(|e| {
+        error!("Failed to count jobs: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let dead_jobs = job_service.dead_jobs().await.map_err(|e| {
+        error!("Failed to fetch dead jobs: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let template = AdminJobsTemplate {
+        counts,
+        dead_jobs,
+        base_url: app_state.config.base_url.clone(),
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Admin template rendering failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Html(html))
+}
+
+/// Requeue a dead job
+pub async fn retry_job(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path(id): Path<i32>,
+    Form(form): Form<ActorForm>,
+) -> Result<Redirect, StatusCode> {
+    let txn = begin(&app_state.db).await?;
+    let retried = JobService::new(app_state.db.clone(), app_state.config.jobs.clone())
+        .retry(&txn, id)
+        .await
+        .map_err(|e| {
+            error!("Failed to retry job {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if !retried {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    info!("Requeued dead job {}", id);
+    AuditService::record(
+        &txn,
+        &meta,
+        AuditEntry::new(audit_event::ACTION_RETRY, audit_event::ENTITY_JOB)
+            .entity(id)
+            .actor(form.actor.as_deref()),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to record audit event: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    commit(txn).await?;
+
+    Ok(Redirect::to(&format!(
+        "{}/admin/jobs",
+        app_state.config.base_url
+    )))
+}
+
+/// Discard a dead job
+pub async fn delete_job(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path(id): Path<i32>,
+    Form(form): Form<ActorForm>,
+) -> Result<Redirect, StatusCode> {
+    let txn = begin(&app_state.db).await?;
+    let deleted = JobService::new(app_state.db.clone(), app_state.config.jobs.clone())
+        .delete(&txn, id)
+        .await
+        .map_err(|e| {
+            error!("Failed to delete job {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if !deleted {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    info!("Deleted dead job {}", id);
+    AuditService::record(
+        &txn,
+        &meta,
+        AuditEntry::new(audit_event::ACTION_DELETE, audit_event::ENTITY_JOB)
+            .entity(id)
+            .actor(form.actor.as_deref()),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to record audit event: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    commit(txn).await?;
+
+    Ok(Redirect::to(&format!(
+        "{}/admin/jobs",
+        app_state.config.base_url
+    )))
+}
+
+fn alert_service(app_state: &AppState) -> AlertService {
+    let job_service = JobService::new(app_state.db.clone(), app_state.config.jobs.clone());
+    AlertService::new(
+        app_state.db.clone(),
+        app_state.config.alerts.clone(),
+        job_service,
+    )
+}
+
+/// Recent volume spike alerts
+pub async fn alerts_page(State(app_state): State<AppState>) -> Result<Html<String>, StatusCode> {
+    let alerts = alert_service(&app_state).recent().await.map_err(|e| {
+        error!("Failed to fetch alerts: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let template = AdminAlertsTemplate {
+        alerts,
+        detection_enabled: app_state.config.alerts.is_enabled(),
+        notifications_enabled: !app_state.config.alerts.recipients.is_empty(),
+        base_url: app_state.config.base_url.clone(),
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Admin template rendering failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Html(html))
+}
+
+/// Run the spike detection now instead of waiting for the next interval
+pub async fn check_alerts(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Form(form): Form<ActorForm>,
+) -> Result<Redirect, StatusCode> {
+    let txn = begin(&app_state.db).await?;
+    let raised = alert_service(&app_state).check(&txn).await.map_err(|e| {
+        error!("Alert check failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    info!("Manual alert check raised {} alerts", raised.len());
+    let raised: Vec<i32> = raised.iter().map(|alert| alert.id).collect();
+    AuditService::record(
+        &txn,
+        &meta,
+        AuditEntry::new(audit_event::ACTION_CHECK, audit_event::ENTITY_ALERT)
+            .actor(form.actor.as_deref())
+            .after(&serde_json::json!({ "raised": raised })),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to record audit event: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    commit(txn).await?;
+
+    Ok(Redirect::to(&format!(
+        "{}/admin/alerts",
+        app_state.config.base_url
+    )))
+}
+
+/// Mark an alert as handled
+pub async fn acknowledge_alert(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path(id): Path<i32>,
+    Form(form): Form<ActorForm>,
+) -> Result<Redirect, StatusCode> {
+    let txn = begin(&app_state.db).await?;
+    let acknowledged = alert_service(&app_state)
+        .acknowledge(&txn, id)
+        .await
+        .map_err(|e| {
+            error!("Failed to acknowledge alert {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if !acknowledged {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    AuditService::record(
+        &txn,
+        &meta,
+        AuditEntry::new(audit_event::ACTION_ACKNOWLEDGE, audit_event::ENTITY_ALERT)
+            .entity(id)
+            .actor(form.actor.as_deref()),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to record audit event: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    commit(txn).await?;
+
+    Ok(Redirect::to(&format!(
+        "{}/admin/alerts",
+        app_state.config.base_url
+    )))
+}
+
+/// Failure groups by size, with the latest merges, splits and rule changes
+pub async fn groups_page(
+    State(app_state): State<AppState>,
+    Query(params): Query<GroupListParams>,
+) -> Result<Html<String>, StatusCode> {
+    let group_service = FailureGroupService::new(app_state.db.clone());
+    let recipe = params.recipe.unwrap_or_default();
+
+    let groups = group_service
+        .list(Some(&recipe), GROUP_LIST_LIMIT)
+        .await
+        .map_err(|e| {
+            error!("Failed to list failure groups: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let events = group_service
+        .recent_events(GROUP_EVENT_LIMIT)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch failure group events: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let template = AdminGroupsTemplate {
+        groups,
+        recipe,
+        events,
+        base_url: app_state.config.base_url.clone(),
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Admin template rendering failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Html(html))
+}
+
+/// Merge, split and rule forms of a failure group, with its history
+pub async fn group_page(
+    State(app_state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<Html<String>, StatusCode> {
+    render_group_page(&app_state, id, None).await
+}
+
+/// Merge a failure group into another
+pub async fn merge_group(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path(id): Path<i32>,
+    Form(data): Form<MergeGroupData>,
+) -> Result<Response, StatusCode> {
+    if let Err(validation_error) = validate_merge_group(&data) {
+        return group_form_error(&app_state, id, validation_error.to_string()).await;
+    }
+
+    let txn = begin(&app_state.db).await?;
+    let result = FailureGroupService::new(app_state.db.clone())
+        .merge(
+            &txn,
+            id,
+            data.target_id,
+            data.actor.as_deref(),
+            data.note.as_deref(),
+        )
+        .await;
+    let event = match result {
+        Ok(event) => event,
+        Err(e) => return curation_error(&app_state, id, e).await,
+    };
+
+    info!(
+        "Merged failure group {} into {} ({} reports)",
+        id, data.target_id, event.report_count
+    );
+    AuditService::record(
+        &txn,
+        &meta,
+        AuditEntry::new(audit_event::ACTION_MERGE, audit_event::ENTITY_FAILURE_GROUP)
+            .entity(id)
+            .actor(data.actor.as_deref())
+            .after(&serde_json::json!({
+                "target_id": data.target_id,
+                "report_count": event.report_count,
+                "note": data.note,
+            })),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to record audit event: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    commit(txn).await?;
+    app_state.stats_cache.invalidate();
+
+    Ok(Redirect::to(&format!(
+        "{}/admin/groups/{}",
+        app_state.config.base_url, data.target_id
+    ))
+    .into_response())
+}
+
+/// Move the selected reports of a failure group into a new group
+pub async fn split_group(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path(id): Path<i32>,
+    axum_extra::extract::Form(data): axum_extra::extract::Form<SplitGroupData>,
+) -> Result<Response, StatusCode> {
+    if let Err(validation_error) = validate_split_group(&data) {
+        return group_form_error(&app_state, id, validation_error.to_string()).await;
+    }
+
+    let txn = begin(&app_state.db).await?;
+    let result = FailureGroupService::new(app_state.db.clone())
+        .split(
+            &txn,
+            id,
+            &data.report_id,
+            data.actor.as_deref(),
+            data.note.as_deref(),
+        )
+        .await;
+    let group = match result {
+        Ok(group) => group,
+        Err(e) => return curation_error(&app_state, id, e).await,
+    };
+
+    info!("Split failure group {} into {}", id, group.id);
+    AuditService::record(
+        &txn,
+        &meta,
+        AuditEntry::new(audit_event::ACTION_SPLIT, audit_event::ENTITY_FAILURE_GROUP)
+            .entity(id)
+            .actor(data.actor.as_deref())
+            .after(&serde_json::json!({
+                "new_group_id": group.id,
+                "report_ids": data.report_id,
+                "note": data.note,
+            })),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to record audit event: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    commit(txn).await?;
+    app_state.stats_cache.invalidate();
+
+    Ok(Redirect::to(&format!(
+        "{}/admin/groups/{}",
+        app_state.config.base_url, group.id
+    ))
+    .into_response())
+}
+
+/// Send future reports with a fingerprint to a failure group
+pub async fn add_group_rule(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path(id): Path<i32>,
+    Form(data): Form<FailureGroupRuleData>,
+) -> Result<Response, StatusCode> {
+    if let Err(validation_error) = validate_group_rule(&data) {
+        return group_form_error(&app_state, id, validation_error.to_string()).await;
+    }
+
+    let actor = data.actor.clone();
+    let txn = begin(&app_state.db).await?;
+    let rule = match FailureGroupService::new(app_state.db.clone())
+        .add_rule(&txn, id, data)
+        .await
+    {
+        Ok(rule) => rule,
+        Err(e) => return curation_error(&app_state, id, e).await,
+    };
+
+    info!("Added rule {} for failure group {}", rule.id, id);
+    AuditService::record(
+        &txn,
+        &meta,
+        AuditEntry::new(
+            audit_event::ACTION_CREATE,
+            audit_event::ENTITY_FAILURE_GROUP_RULE,
+        )
+        .entity(rule.id)
+        .actor(actor.as_deref())
+        .after(&rule),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to record audit event: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    commit(txn).await?;
+
+    Ok(Redirect::to(&format!(
+        "{}/admin/groups/{}",
+        app_state.config.base_url, id
+    ))
+    .into_response())
+}
+
+/// Remove a fingerprint rule
+pub async fn delete_group_rule(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path(rule_id): Path<i32>,
+    Form(form): Form<ActorForm>,
+) -> Result<Redirect, StatusCode> {
+    let txn = begin(&app_state.db).await?;
+    let group_id = FailureGroupService::new(app_state.db.clone())
+        .delete_rule(&txn, rule_id, form.actor.as_deref())
+        .await
+        .map_err(|e| {
+            error!("Failed to delete failure group rule {}: {:?}", rule_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    info!("Deleted rule {} of failure group {}", rule_id, group_id);
+    AuditService::record(
+        &txn,
+        &meta,
+        AuditEntry::new(
+            audit_event::ACTION_DELETE,
+            audit_event::ENTITY_FAILURE_GROUP_RULE,
+        )
+        .entity(rule_id)
+        .actor(form.actor.as_deref())
+        .before(&serde_json::json!({ "failure_group_id": group_id })),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to record audit event: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    commit(txn).await?;
+
+    Ok(Redirect::to(&format!(
+        "{}/admin/groups/{}",
+        app_state.config.base_url, group_id
+    )))
+}
+
+/// Show a rejected change on the group page
+async fn curation_error(
+    app_state: &AppState,
+    id: i32,
+    e: CurationError,
+) -> Result<Response, StatusCode> {
+    match e {
+        CurationError::GroupNotFound(group_id) if group_id == id => Err(StatusCode::NOT_FOUND),
+        CurationError::Database(e) => {
+            error!("Failed to change failure group {}: {:?}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+        e => group_form_error(app_state, id, e.to_string()).await,
+    }
+}
+
+async fn group_form_error(
+    app_state: &AppState,
+    id: i32,
+    message: String,
+) -> Result<Response, StatusCode> {
+    let html = render_group_page(app_state, id, Some(message)).await?;
+    Ok((StatusCode::BAD_REQUEST, html).into_response())
+}
+
+async fn render_group_page(
+    app_state: &AppState,
+    id: i32,
+    error_message: Option<String>,
+) -> Result<Html<String>, StatusCode> {
+    let group_service = FailureGroupService::new(app_state.db.clone());
+    let db_error = |e| {
+        error!("Failed to fetch failure group {}: {:?}", id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+
+    let group = group_service
+        .get(id)
+        .await
+        .map_err(db_error)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let activity = group_service.activity(id).await.map_err(db_error)?;
+    let reports = group_service
+        .reports(id, GROUP_REPORT_LIMIT)
+        .await
+        .map_err(db_error)?;
+    let rules = group_service.rules(id).await.map_err(db_error)?;
+    let events = group_service.events(id).await.map_err(db_error)?;
+
+    let template = AdminGroupTemplate {
+        group,
+        activity,
+        reports,
+        rules,
+        events,
+        error_message,
+        base_url: app_state.config.base_url.clone(),
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Admin template rendering failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Html(html))
+}
+
+/// Reports matching the error list filters, including hidden ones, with bulk
+/// hide, unhide and delete actions
+pub async fn moderation_page(
+    State(app_state): State<AppState>,
+    FilterQuery(query): FilterQuery<ErrorQuery>,
+    FilterQuery(params): FilterQuery<ModerationParams>,
+) -> Result<Html<String>, StatusCode> {
+    render_moderation_page(&app_state, query, params.status.unwrap_or_default(), None).await
+}
+
+/// Apply a moderation action to the selected reports, or to every report
+/// matching the filters
+pub async fn moderate_reports(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    axum_extra::extract::Form(data): axum_extra::extract::Form<ModerationData>,
+) -> Result<Response, StatusCode> {
+    let status = data.status.unwrap_or_default();
+    let mut query =
+        ErrorQuery::from_query_string(&data.query).map_err(|_| StatusCode::BAD_REQUEST)?;
+    query.visibility = status;
+    // Moderators see submitter emails, so they can select reports by them
+    query.email_visibility = Some(EmailVisibility::Show);
+
+    if let Err(validation_error) = validate_moderation(&data) {
+        return moderation_form_error(&app_state, query, status, validation_error.to_string())
+            .await;
+    }
+
+    let moderation_service = ModerationService::new(app_state.db.clone());
+    let ids = if data.all_matching.is_some() {
+        match moderation_service.matching_ids(&query).await {
+            Ok(ids) => ids,
+            Err(ModerationError::Database(e)) => {
+                error!("Failed to select reports to moderate: {:?}", e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+            Err(e) => return moderation_form_error(&app_state, query, status, e.to_string()).await,
+        }
+    } else {
+        data.report_id.clone()
+    };
+
+    let actor = data.actor.as_deref();
+    let reason = data.reason.as_deref();
+    let txn = begin(&app_state.db).await?;
+    let details = serde_json::json!({ "reason": reason });
+    let entries: Vec<AuditEntry> = match data.action.as_str() {
+        moderation_action::ACTION_HIDE => moderation_service
+            .hide(&txn, &ids, actor, reason)
+            .await
+            .map(|changed| moderation_entries(audit_event::ACTION_HIDE, &changed, actor, &details)),
+        moderation_action::ACTION_UNHIDE => {
+            moderation_service
+                .unhide(&txn, &ids, actor)
+                .await
+                .map(|changed| {
+                    moderation_entries(audit_event::ACTION_UNHIDE, &changed, actor, &details)
+                })
+        }
+        // Deleted reports are gone afterwards, so the log keeps what they were
+        _ => moderation_service
+            .delete(&txn, &ids, actor, reason)
+            .await
+            .map(|reports| {
+                reports
+                    .iter()
+                    .map(|report| {
+                        AuditEntry::new(
+                            audit_event::ACTION_DELETE,
+                            audit_event::ENTITY_ERROR_REPORT,
+                        )
+                        .entity(report.id)
+                        .actor(actor)
+                        .before(report)
+                        .after(&details)
+                    })
+                    .collect()
+            }),
+    }
+    .map_err(|e| {
+        error!("Failed to {} reports: {:?}", data.action, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    info!(
+        "Moderation: {} applied to {} reports",
+        data.action,
+        entries.len()
+    );
+    AuditService::record_all(&txn, &meta, entries)
+        .await
+        .map_err(|e| {
+            error!("Failed to record audit event: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    commit(txn).await?;
+    app_state.stats_cache.invalidate();
+
+    Ok(Redirect::to(&format!(
+        "{}/admin/moderation?{}",
+        app_state.config.base_url,
+        moderation_query(&query, status)
+    ))
+    .into_response())
+}
+
+/// One audit entry per report a moderation action changed
+fn moderation_entries(
+    action: &'static str,
+    ids: &[i32],
+    actor: Option<&str>,
+    details: &serde_json::Value,
+) -> Vec<AuditEntry> {
+    ids.iter()
+        .map(|id| {
+            AuditEntry::new(action, audit_event::ENTITY_ERROR_REPORT)
+                .entity(id)
+                .actor(actor)
+                .after(details)
+        })
+        .collect()
+}
+
+/// Submission bans, with a form prefilled from the query string
+pub async fn bans_page(
+    State(app_state): State<AppState>,
+    Query(params): Query<BanParams>,
+) -> Result<Html<String>, StatusCode> {
+    render_bans_page(&app_state, params, None).await
+}
+
+/// Reject future submissions from an address, email or submission token
+pub async fn create_ban(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Form(data): Form<BanData>,
+) -> Result<Response, StatusCode> {
+    if let Err(validation_error) = validate_ban(&data) {
+        let params = BanParams {
+            kind: Some(data.kind),
+            value: Some(data.value),
+        };
+        let html = render_bans_page(&app_state, params, Some(validation_error.to_string())).await?;
+        return Ok((StatusCode::BAD_REQUEST, html).into_response());
+    }
+
+    let actor = data.actor.clone();
+    let txn = begin(&app_state.db).await?;
+    let ban = ModerationService::new(app_state.db.clone())
+        .ban(&txn, data)
+        .await
+        .map_err(|e| {
+            error!("Failed to save submission ban: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("Banned {} {}", ban.kind, ban.id);
+    AuditService::record(
+        &txn,
+        &meta,
+        AuditEntry::new(
+            audit_event::ACTION_CREATE,
+            audit_event::ENTITY_SUBMISSION_BAN,
+        )
+        .entity(ban.id)
+        .actor(actor.as_deref())
+        .after(&ban),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to record audit event: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    commit(txn).await?;
+
+    Ok(Redirect::to(&format!("{}/admin/bans", app_state.config.base_url)).into_response())
+}
+
+/// Lift a submission ban
+pub async fn delete_ban(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path(id): Path<i32>,
+    Form(form): Form<ActorForm>,
+) -> Result<Redirect, StatusCode> {
+    let txn = begin(&app_state.db).await?;
+    let ban = ModerationService::new(app_state.db.clone())
+        .unban(&txn, id, form.actor.as_deref())
+        .await
+        .map_err(|e| {
+            error!("Failed to delete submission ban {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    info!("Lifted submission ban {}", id);
+    AuditService::record(
+        &txn,
+        &meta,
+        AuditEntry::new(
+            audit_event::ACTION_DELETE,
+            audit_event::ENTITY_SUBMISSION_BAN,
+        )
+        .entity(id)
+        .actor(form.actor.as_deref())
+        .before(&ban),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to record audit event: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    commit(txn).await?;
+
+    Ok(Redirect::to(&format!(
+        "{}/admin/bans",
+        app_state.config.base_url
+    )))
+}
+
+/// Every saved search, including private ones and those without an owner
+pub async fn saved_searches_page(
+    State(app_state): State<AppState>,
+) -> Result<Html<String>, StatusCode> {
+    let searches = SavedSearchService::new(app_state.db.clone())
+        .list_all()
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch saved searches: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let template = AdminSavedSearchesTemplate {
+        searches,
+        base_url: app_state.config.base_url.clone(),
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Admin template rendering failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Html(html))
+}
+
+/// Delete any saved search and its subscriptions
+pub async fn delete_saved_search(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path(id): Path<i32>,
+    Form(form): Form<ActorForm>,
+) -> Result<Redirect, StatusCode> {
+    let saved_searches = SavedSearchService::new(app_state.db.clone());
+    let search = saved_searches
+        .get(id)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch saved search {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let txn = begin(&app_state.db).await?;
+    let deleted = saved_searches.delete(&txn, id).await.map_err(|e| {
+        error!("Failed to delete saved search {}: {:?}", id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if !deleted {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    info!("Deleted saved search {}", id);
+    AuditService::record(
+        &txn,
+        &meta,
+        AuditEntry::new(audit_event::ACTION_DELETE, audit_event::ENTITY_SAVED_SEARCH)
+            .entity(id)
+            .actor(form.actor.as_deref())
+            .before(&search),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to record audit event: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    commit(txn).await?;
+
+    Ok(Redirect::to(&format!(
+        "{}/admin/searches",
+        app_state.config.base_url
+    )))
+}
+
+/// The moderation list query string for the given filters and visibility
+fn moderation_query(query: &ErrorQuery, status: Visibility) -> String {
+    let listing_query = query.listing_query();
+    let status = format!("status={}", status.name());
+    if listing_query.is_empty() {
+        status
+    } else {
+        format!("{}&{}", listing_query, status)
+    }
+}
+
+async fn moderation_form_error(
+    app_state: &AppState,
+    query: ErrorQuery,
+    status: Visibility,
+    message: String,
+) -> Result<Response, StatusCode> {
+    let html = render_moderation_page(app_state, query, status, Some(message)).await?;
+    Ok((StatusCode::BAD_REQUEST, html).into_response())
+}
+
+async fn render_moderation_page(
+    app_state: &AppState,
+    mut query: ErrorQuery,
+    status: Visibility,
+    error_message: Option<String>,
+) -> Result<Html<String>, StatusCode> {
+    query.visibility = status;
+    query.email_visibility = Some(EmailVisibility::Show);
+
+    let reports = ErrorListService::new(app_state.db.clone())
+        .list(&query, MODERATION_PAGE_SIZE)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch reports to moderate: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let actions = ModerationService::new(app_state.db.clone())
+        .recent_actions(MODERATION_LOG_LIMIT)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch moderation log: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let template = AdminModerationTemplate {
+        reports,
+        filters: ErrorFilters::from_query(&query),
+        submitter: query.submitter.first().cloned().unwrap_or_default(),
+        status: status.name(),
+        listing_query: query.listing_query(),
+        actions,
+        error_message,
+        base_url: app_state.config.base_url.clone(),
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Admin template rendering failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Html(html))
+}
+
+async fn render_bans_page(
+    app_state: &AppState,
+    params: BanParams,
+    error_message: Option<String>,
+) -> Result<Html<String>, StatusCode> {
+    let bans = ModerationService::new(app_state.db.clone())
+        .bans()
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch submission bans: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let template = AdminBansTemplate {
+        bans,
+        kinds: submission_ban::KINDS,
+        kind: params.kind.unwrap_or_default(),
+        value: params.value.unwrap_or_default(),
+        error_message,
+        base_url: app_state.config.base_url.clone(),
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Admin template rendering failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Html(html))
+}
+
+/// Every change made through the web interface and API, newest first
+pub async fn audit_page(
+    State(app_state): State<AppState>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Html<String>, StatusCode> {
+    let audit = AuditService::new(app_state.db.clone())
+        .list(&query, AUDIT_PAGE_SIZE)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch audit events: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let template = AdminAuditTemplate {
+        audit,
+        filter_query: query.filter_query(),
+        query,
+        actions: audit_event::ACTIONS,
+        entity_types: audit_event::ENTITY_TYPES,
+        base_url: app_state.config.base_url.clone(),
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Admin template rendering failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Html(html))
+}
+
+/// Download the audit events matching the filters as a JSON array, oldest first
+pub async fn export_audit(
+    State(app_state): State<AppState>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Response, StatusCode> {
+    let stream = AuditService::new(app_state.db.clone())
+        .export(query)
+        .map(|chunk| {
+            // Headers are already sent, so a failure can only cut the export short
+            chunk.inspect_err(|e| error!("Failed to export audit events: {:?}", e))
+        });
+
+    let disposition = format!(
+        "attachment; filename=\"audit-{}.json\"",
+        Utc::now().format("%Y%m%d%H%M%S")
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        Body::from_stream(stream),
+    )
+        .into_response())
+}
@@ -1,31 +1,58 @@
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    body::Body,
+    extract::{ConnectInfo, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
 };
+use axum_extra::extract::Query as FilterQuery;
 use chrono::Utc;
-use sea_orm::{
-    ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, Set,
-};
+use futures::StreamExt;
+use sea_orm::{ActiveModelTrait, EntityTrait, QueryFilter, Set};
+use std::net::SocketAddr;
 use tracing::{error, info};
 
 use crate::{
+    handlers::{admin::ActorForm, begin, commit},
     models::{
+        audit_event,
+        comment::CommentData,
         error_report::{
-            self, ErrorListResponse, ErrorQuery, ErrorSubmissionData, PaginationInfo,
-            SubmissionResponse,
+            self, ErrorListResponse, ErrorQuery, ErrorSubmissionData, SubmissionResponse,
+        },
+        tag::{normalize_name, TagData},
+        ErrorReport, FailureGroup,
+    },
+    services::{
+        audit::{AuditEntry, AuditService},
+        comments::{CommentError, CommentService, CommentTarget, CommentView},
+        compare::{CompareService, ReportComparison},
+        error_list::ErrorListService,
+        export::{ExportOptions, ExportParams, ExportService},
+        facets::{Facet, FacetParams, FacetService},
+        failure_groups::FailureGroupService,
+        jobs::{JobPayload, JobService},
+        matrix::{ErrorMatrix, MatrixParams, MatrixService},
+        moderation::{ModerationService, SubmissionSource},
+        regressions::{RegressionParams, RegressionReport, RegressionService},
+        stats::{ErrorStats, StatsOptions, StatsParams},
+        tags::{TagCount, TagService},
+    },
+    utils::{
+        client::{client_ip, submission_token, RequestMeta},
+        privacy::{mask_report, mask_stats},
+        validation::{
+            validate_comment, validate_error_submission, validate_submission_token, validate_tag,
         },
-        ErrorReport,
     },
-    services::stats::ErrorStats,
-    utils::validation::validate_error_submission,
     AppState,
 };
 
 /// Submit a new error report - maintains compatibility with Django endpoint
 pub async fn submit_error_report(
     State(app_state): State<AppState>,
-    Json(payload): Json<ErrorSubmissionData>,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Json(mut payload): Json<ErrorSubmissionData>,
 ) -> Result<Json<SubmissionResponse>, StatusCode> {
     info!(
         "Received error report submission for machine: {}",
@@ -38,6 +65,54 @@ pub async fn submit_error_report(
         return Err(StatusCode::BAD_REQUEST);
     }
 
+    let submitter_ip = client_ip(
+        &headers,
+        peer.map(|ConnectInfo(peer)| peer),
+        app_state.config.trust_forwarded_for,
+    );
+    let submitter_token = submission_token(&headers);
+    if let Some(Err(validation_error)) = submitter_token.as_deref().map(validate_submission_token) {
+        error!("Validation failed: {:?}", validation_error);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // Reject banned submitters before anything is stored
+    let ban = ModerationService::new(app_state.db.clone())
+        .find_ban(SubmissionSource {
+            ip: submitter_ip.as_deref(),
+            email: payload.submitter_email.as_deref(),
+            token: submitter_token.as_deref(),
+        })
+        .await
+        .map_err(|e| {
+            error!("Failed to check submission bans: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if let Some(ban) = ban {
+        info!("Rejected submission matching {} ban {}", ban.kind, ban.id);
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    // Strip secrets and personal data from the logs before anything is stored
+    let redactions = app_state.redactor.redact_submission(&mut payload);
+
+    // Group the report with earlier reports of the same failure
+    let failure_group_id = match FailureGroupService::new(app_state.db.clone())
+        .resolve(
+            &payload.failure_package,
+            &payload.failure_task,
+            &payload.error_type,
+        )
+        .await
+    {
+        Ok(group) => Some(group.id),
+        Err(e) => {
+            error!("Failed to resolve failure group: {:?}", e);
+            // Continue anyway as this is not critical
+            None
+        }
+    };
+
     // Create error report
     let error_report = error_report::ActiveModel {
         machine: Set(payload.machine.clone()),
@@ -56,17 +131,23 @@ pub async fn submit_error_report(
         branch_commit: Set(payload.branch_commit.clone()),
         created_at: Set(Utc::now()),
         bugzilla_link: Set(None),
+        failure_group_id: Set(failure_group_id),
+        submitter_ip: Set(submitter_ip),
+        submitter_token: Set(submitter_token),
         ..Default::default()
     };
 
-    let saved_report = error_report.insert(&app_state.db).await.map_err(|e| {
+    // The report, its build configuration, redaction records and jobs are
+    // stored together, so a failure leaves nothing half-saved
+    let txn = begin(&app_state.db).await?;
+    let saved_report = error_report.insert(&txn).await.map_err(|e| {
         error!("Failed to save error report: {:?}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
     // Save build configuration if provided
     if let Some(build_config) = payload.build_configuration {
-        use crate::models::{build_configuration, BuildConfiguration};
+        use crate::models::build_configuration;
 
         let meta_layers_json =
             serde_json::to_string(&build_config.meta_layers).unwrap_or_else(|_| "[]".to_string());
@@ -80,12 +161,51 @@ pub async fn submit_error_report(
             ..Default::default()
         };
 
-        if let Err(e) = build_config_model.insert(&app_state.db).await {
+        build_config_model.insert(&txn).await.map_err(|e| {
             error!("Failed to save build configuration: {:?}", e);
-            // Continue anyway as this is not critical
-        }
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+
+    // Record which redaction rules fired on this report
+    for hit in redactions {
+        use crate::models::report_redaction;
+
+        let redaction_model = report_redaction::ActiveModel {
+            error_report_id: Set(saved_report.id),
+            rule_name: Set(hit.rule),
+            field: Set(hit.field),
+            occurrences: Set(hit.occurrences as i32),
+            ..Default::default()
+        };
+
+        redaction_model.insert(&txn).await.map_err(|e| {
+            error!("Failed to save redaction record: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
     }
 
+    // Known issue and saved search matching run in the background job workers
+    let job_service = JobService::new(app_state.db.clone(), app_state.config.jobs.clone());
+    let jobs = [
+        JobPayload::AnnotateKnownIssues {
+            error_report_id: saved_report.id,
+        },
+        JobPayload::MatchSavedSearches {
+            error_report_id: saved_report.id,
+        },
+    ];
+    for job in &jobs {
+        job_service.enqueue(&txn, job).await.map_err(|e| {
+            error!("Failed to queue {} job: {:?}", job.kind(), e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+    commit(txn).await?;
+
+    // Statistics now include the new report
+    app_state.stats_cache.invalidate();
+
     info!(
         "Successfully saved error report with ID: {}",
         saved_report.id
@@ -106,79 +226,61 @@ pub async fn submit_error_report(
 /// List errors with filtering and pagination
 pub async fn list_errors(
     State(app_state): State<AppState>,
-    Query(params): Query<ErrorQuery>,
+    FilterQuery(mut params): FilterQuery<ErrorQuery>,
 ) -> Result<Json<ErrorListResponse>, StatusCode> {
-    let page = params.page.unwrap_or(1);
-    let per_page = params.per_page.unwrap_or(50).min(100); // Cap at 100 per page
-
-    let mut query = ErrorReport::find();
-
-    // Apply filters
-    if let Some(machine) = &params.machine {
-        query = query.filter(error_report::Column::Machine.eq(machine));
-    }
-    if let Some(distro) = &params.distro {
-        query = query.filter(error_report::Column::Distro.eq(distro));
-    }
-    if let Some(distro_version) = &params.distro_version {
-        query = query.filter(error_report::Column::DistroVersion.eq(distro_version));
-    }
-    if let Some(error_type) = &params.error_type {
-        query = query.filter(error_report::Column::ErrorType.eq(error_type));
-    }
-    if let Some(failure_package) = &params.failure_package {
-        query = query.filter(error_report::Column::FailurePackage.eq(failure_package));
-    }
+    params.email_visibility = Some(app_state.config.submitter_email_visibility);
+    let mut response = ErrorListService::new(app_state.db.clone())
+        .list(&params, 50)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch errors: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
-    // Search functionality
-    if let Some(search_term) = &params.search {
-        query = query.filter(
-            error_report::Column::ErrorDetails
-                .contains(search_term)
-                .or(error_report::Column::FailurePackage.contains(search_term))
-                .or(error_report::Column::FailureTask.contains(search_term)),
-        );
+    for error in &mut response.errors {
+        mask_report(app_state.config.submitter_email_visibility, error);
     }
 
-    // Date filtering
-    if let Some(date_from) = &params.date_from {
-        if let Ok(parsed_date) = chrono::DateTime::parse_from_rfc3339(date_from) {
-            query =
-                query.filter(error_report::Column::CreatedAt.gte(parsed_date.with_timezone(&Utc)));
-        }
-    }
-    if let Some(date_to) = &params.date_to {
-        if let Ok(parsed_date) = chrono::DateTime::parse_from_rfc3339(date_to) {
-            query =
-                query.filter(error_report::Column::CreatedAt.lte(parsed_date.with_timezone(&Utc)));
-        }
-    }
-
-    let paginator = query
-        .order_by_desc(error_report::Column::CreatedAt)
-        .paginate(&app_state.db, per_page);
+    Ok(Json(response))
+}
 
-    let errors = paginator.fetch_page(page - 1).await.map_err(|e| {
-        error!("Failed to fetch errors: {:?}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
+/// Stream every error matching the filters as CSV or NDJSON
+pub async fn export_errors(
+    State(app_state): State<AppState>,
+    FilterQuery(mut filters): FilterQuery<ErrorQuery>,
+    Query(params): Query<ExportParams>,
+) -> Result<Response, StatusCode> {
+    filters.email_visibility = Some(app_state.config.submitter_email_visibility);
+    let options = ExportOptions::new(&params, &filters).map_err(|e| {
+        error!("Invalid export parameters: {}", e);
+        StatusCode::BAD_REQUEST
     })?;
+    let format = options.format;
 
-    let total = paginator.num_items().await.map_err(|e| {
-        error!("Failed to count errors: {:?}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    let stream = ExportService::new(
+        app_state.db.clone(),
+        app_state.config.submitter_email_visibility,
+    )
+    .stream(options)
+    .map(|chunk| {
+        // Headers are already sent, so a failure can only cut the export short
+        chunk.inspect_err(|e| error!("Failed to export errors: {:?}", e))
+    });
 
-    let response = ErrorListResponse {
-        errors,
-        pagination: PaginationInfo {
-            page,
-            per_page,
-            total,
-            total_pages: (total + per_page - 1) / per_page,
-        },
-    };
+    let disposition = format!(
+        "attachment; filename=\"errors-{}.{}\"",
+        Utc::now().format("%Y%m%d%H%M%S"),
+        format.extension()
+    );
 
-    Ok(Json(response))
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        Body::from_stream(stream),
+    )
+        .into_response())
 }
 
 /// Get a specific error by ID
@@ -186,7 +288,8 @@ pub async fn get_error(
     State(app_state): State<AppState>,
     Path(id): Path<i32>,
 ) -> Result<Json<error_report::Model>, StatusCode> {
-    let error = ErrorReport::find_by_id(id)
+    let mut error = ErrorReport::find_by_id(id)
+        .filter(error_report::visible())
         .one(&app_state.db)
         .await
         .map_err(|e| {
@@ -195,17 +298,349 @@ pub async fn get_error(
         })?
         .ok_or(StatusCode::NOT_FOUND)?;
 
+    mask_report(app_state.config.submitter_email_visibility, &mut error);
+
     Ok(Json(error))
 }
 
+/// Differences between two errors
+pub async fn compare_errors(
+    State(app_state): State<AppState>,
+    Path((a, b)): Path<(i32, i32)>,
+) -> Result<Json<ReportComparison>, StatusCode> {
+    let comparison = CompareService::new(app_state.db.clone())
+        .compare(a, b)
+        .await
+        .map_err(|e| {
+            error!("Failed to compare errors {} and {}: {:?}", a, b, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(comparison))
+}
+
+/// Value counts of each facet among the errors matching the filters
+pub async fn get_error_facets(
+    State(app_state): State<AppState>,
+    FilterQuery(mut filters): FilterQuery<ErrorQuery>,
+    Query(params): Query<FacetParams>,
+) -> Result<Json<Vec<Facet>>, StatusCode> {
+    filters.email_visibility = Some(app_state.config.submitter_email_visibility);
+    let size = params.size().map_err(|e| {
+        error!("Invalid facet parameters: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let facets = FacetService::new(app_state.db.clone())
+        .get_facets(&filters, size)
+        .await
+        .map_err(|e| {
+            error!("Failed to get facets: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(facets))
+}
+
 /// Get error statistics
-pub async fn get_stats(State(app_state): State<AppState>) -> Result<Json<ErrorStats>, StatusCode> {
-    let stats_service = crate::services::stats::StatsService::new(app_state.db.clone());
+pub async fn get_stats(
+    State(app_state): State<AppState>,
+    FilterQuery(mut filters): FilterQuery<ErrorQuery>,
+    Query(params): Query<StatsParams>,
+) -> Result<Json<ErrorStats>, StatusCode> {
+    filters.email_visibility = Some(app_state.config.submitter_email_visibility);
+    let options =
+        StatsOptions::new(&params, &filters, app_state.config.stats.top_n).map_err(|e| {
+            error!("Invalid stats parameters: {}", e);
+            StatusCode::BAD_REQUEST
+        })?;
+
+    let stats_service = crate::services::stats::StatsService::new(app_state.db.clone())
+        .with_rollups(app_state.config.stats.rollup_refresh_secs.is_some());
+
+    let mut stats = stats_service
+        .get_cached_error_stats(&app_state.stats_cache, &options)
+        .await
+        .map_err(|e| {
+            error!("Failed to get stats: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
-    let stats = stats_service.get_error_stats().await.map_err(|e| {
-        error!("Failed to get stats: {:?}", e);
+    mask_stats(app_state.config.submitter_email_visibility, &mut stats);
+
+    Ok(Json(stats))
+}
+
+/// Failing recipes by machine or distro version
+pub async fn get_matrix(
+    State(app_state): State<AppState>,
+    FilterQuery(mut filters): FilterQuery<ErrorQuery>,
+    Query(params): Query<MatrixParams>,
+) -> Result<Json<ErrorMatrix>, StatusCode> {
+    filters.email_visibility = Some(app_state.config.submitter_email_visibility);
+    let (rows, cols) = params.size().map_err(|e| {
+        error!("Invalid matrix parameters: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let matrix = MatrixService::new(app_state.db.clone())
+        .get_matrix(params.columns(), &filters.condition(), rows, cols)
+        .await
+        .map_err(|e| {
+            error!("Failed to get failure matrix: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(matrix))
+}
+
+/// Failure groups new for a branch or distro version
+pub async fn get_regressions(
+    State(app_state): State<AppState>,
+    Query(params): Query<RegressionParams>,
+) -> Result<Json<RegressionReport>, StatusCode> {
+    let days = params.days().map_err(|e| {
+        error!("Invalid regression parameters: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let report = RegressionService::new(app_state.db.clone())
+        .find_regressions(params.scope(), days)
+        .await
+        .map_err(|e| {
+            error!("Failed to find regressions: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(report))
+}
+
+/// Tags in use with the number of reports carrying each
+pub async fn list_tags(
+    State(app_state): State<AppState>,
+) -> Result<Json<Vec<TagCount>>, StatusCode> {
+    let tags = TagService::new(app_state.db.clone())
+        .list()
+        .await
+        .map_err(|e| {
+            error!("Failed to list tags: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(tags))
+}
+
+/// Tags of an error
+pub async fn get_error_tags(
+    State(app_state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<String>>, StatusCode> {
+    find_report(&app_state, id).await?;
+    report_tags(&app_state, id).await.map(Json)
+}
+
+/// Attach a tag to an error, returning its tags
+pub async fn add_error_tag(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path(id): Path<i32>,
+    Json(payload): Json<TagData>,
+) -> Result<Json<Vec<String>>, StatusCode> {
+    if let Err(validation_error) = validate_tag(&payload.name) {
+        error!("Validation failed: {:?}", validation_error);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    find_report(&app_state, id).await?;
+
+    let txn = begin(&app_state.db).await?;
+    TagService::new(app_state.db.clone())
+        .add(&txn, id, &payload.name)
+        .await
+        .map_err(|e| {
+            error!("Failed to tag error {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    AuditService::record(
+        &txn,
+        &meta,
+        AuditEntry::new(audit_event::ACTION_TAG, audit_event::ENTITY_ERROR_REPORT)
+            .entity(id)
+            .actor(payload.actor.as_deref())
+            .after(&serde_json::json!({ "tag": normalize_name(&payload.name) })),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to record audit event: {:?}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
+    commit(txn).await?;
+    // Statistics may be filtered by tag
+    app_state.stats_cache.invalidate();
 
-    Ok(Json(stats))
+    report_tags(&app_state, id).await.map(Json)
+}
+
+/// Detach a tag from an error, returning its remaining tags
+pub async fn remove_error_tag(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path((id, name)): Path<(i32, String)>,
+    Query(params): Query<ActorForm>,
+) -> Result<Json<Vec<String>>, StatusCode> {
+    find_report(&app_state, id).await?;
+
+    let txn = begin(&app_state.db).await?;
+    let removed = TagService::new(app_state.db.clone())
+        .remove(&txn, id, &name)
+        .await
+        .map_err(|e| {
+            error!("Failed to untag error {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if removed {
+        AuditService::record(
+            &txn,
+            &meta,
+            AuditEntry::new(audit_event::ACTION_UNTAG, audit_event::ENTITY_ERROR_REPORT)
+                .entity(id)
+                .actor(params.actor.as_deref())
+                .before(&serde_json::json!({ "tag": normalize_name(&name) })),
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to record audit event: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+    commit(txn).await?;
+    app_state.stats_cache.invalidate();
+
+    report_tags(&app_state, id).await.map(Json)
+}
+
+async fn report_tags(app_state: &AppState, id: i32) -> Result<Vec<String>, StatusCode> {
+    TagService::new(app_state.db.clone())
+        .tags_for_report(id)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch tags of error {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+/// Comments on an error in thread order
+pub async fn get_error_comments(
+    State(app_state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<CommentView>>, StatusCode> {
+    find_report(&app_state, id).await?;
+    comment_thread(&app_state, CommentTarget::Report(id))
+        .await
+        .map(Json)
+}
+
+/// Comment on an error, or reply to one of its comments with `parent_id`
+pub async fn add_error_comment(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path(id): Path<i32>,
+    Json(payload): Json<CommentData>,
+) -> Result<(StatusCode, Json<CommentView>), StatusCode> {
+    find_report(&app_state, id).await?;
+    add_comment(&app_state, &meta, CommentTarget::Report(id), payload).await
+}
+
+/// Comments on a failure group in thread order
+pub async fn get_group_comments(
+    State(app_state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<CommentView>>, StatusCode> {
+    find_group(&app_state, id).await?;
+    comment_thread(&app_state, CommentTarget::Group(id))
+        .await
+        .map(Json)
+}
+
+/// Comment on a failure group, or reply to one of its comments with `parent_id`
+pub async fn add_group_comment(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path(id): Path<i32>,
+    Json(payload): Json<CommentData>,
+) -> Result<(StatusCode, Json<CommentView>), StatusCode> {
+    find_group(&app_state, id).await?;
+    add_comment(&app_state, &meta, CommentTarget::Group(id), payload).await
+}
+
+async fn comment_thread(
+    app_state: &AppState,
+    target: CommentTarget,
+) -> Result<Vec<CommentView>, StatusCode> {
+    CommentService::new(app_state.db.clone())
+        .thread(target)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch comments on {:?}: {:?}", target, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+async fn add_comment(
+    app_state: &AppState,
+    meta: &RequestMeta,
+    target: CommentTarget,
+    payload: CommentData,
+) -> Result<(StatusCode, Json<CommentView>), StatusCode> {
+    if let Err(validation_error) = validate_comment(&payload) {
+        error!("Validation failed: {:?}", validation_error);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let txn = begin(&app_state.db).await?;
+    let comment = CommentService::new(app_state.db.clone())
+        .create(&txn, target, payload)
+        .await
+        .map_err(|e| match e {
+            CommentError::InvalidParent => StatusCode::BAD_REQUEST,
+            CommentError::Database(e) => {
+                error!("Failed to save comment on {:?}: {:?}", target, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })?;
+
+    info!("Added comment {} on {:?}", comment.id, target);
+    AuditService::record(&txn, meta, AuditEntry::comment(target, &comment))
+        .await
+        .map_err(|e| {
+            error!("Failed to record audit event: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    commit(txn).await?;
+
+    Ok((StatusCode::CREATED, Json(comment)))
+}
+
+async fn find_report(app_state: &AppState, id: i32) -> Result<error_report::Model, StatusCode> {
+    ErrorReport::find_by_id(id)
+        .filter(error_report::visible())
+        .one(&app_state.db)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch error {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn find_group(app_state: &AppState, id: i32) -> Result<(), StatusCode> {
+    FailureGroup::find_by_id(id)
+        .one(&app_state.db)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch failure group {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)
+        .map(|_| ())
 }
@@ -1,3 +1,23 @@
+use axum::http::StatusCode;
+use sea_orm::{DatabaseConnection, DatabaseTransaction, TransactionTrait};
+use tracing::error;
+
 pub mod admin;
 pub mod api;
 pub mod web;
+
+/// Start the transaction a change and its audit event are written in
+pub(crate) async fn begin(db: &DatabaseConnection) -> Result<DatabaseTransaction, StatusCode> {
+    db.begin().await.map_err(|e| {
+        error!("Failed to start transaction: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// Commit a change together with its audit event
+pub(crate) async fn commit(txn: DatabaseTransaction) -> Result<(), StatusCode> {
+    txn.commit().await.map_err(|e| {
+        error!("Failed to commit transaction: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
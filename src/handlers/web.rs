@@ -1,19 +1,56 @@
 use askama::Template;
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Html,
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Redirect, Response},
+    Form,
 };
-use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect};
-use tracing::error;
+use axum_extra::extract::Query as FilterQuery;
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    sea_query::Expr, ColumnTrait, EntityTrait, FromQueryResult, PaginatorTrait, QueryFilter,
+    QueryOrder, QuerySelect,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::{error, info};
 
 use crate::{
+    handlers::{admin::ActorForm, begin, commit},
     models::{
+        audit_event,
         build_configuration::{self},
-        error_report::{self, ErrorQuery},
-        BuildConfiguration, ErrorReport,
+        comment::CommentData,
+        error_report::{self, ErrorQuery, ErrorSort, SortField},
+        failure_group, known_issue, report_redaction,
+        saved_search::{self, SavedSearchData},
+        saved_search_subscription::{self, SubscriptionData},
+        tag::{normalize_name, TagData},
+        BuildConfiguration, ErrorReport, FailureGroup, ReportRedaction,
+    },
+    services::{
+        audit::{AuditEntry, AuditService},
+        comments::{CommentError, CommentService, CommentTarget, CommentView},
+        compare::{CompareService, ReportComparison},
+        error_list::ErrorListService,
+        facets::{Facet, FacetService, DEFAULT_FACET_SIZE},
+        failure_groups::{FailureGroupService, GroupActivity, HostCorrelation},
+        jobs::{JobPayload, JobService},
+        known_issues::KnownIssueService,
+        log_viewer::{LogChunk, LogParams},
+        matrix::{ErrorMatrix, MatrixParams, MatrixService},
+        regressions::{RegressionParams, RegressionReport, RegressionService},
+        saved_searches::{parse_query, PinnedSearch, SavedSearchEntry, SavedSearchService},
+        stats::{StatsOptions, StatsParams, StatsService},
+        tags::TagService,
+    },
+    utils::{
+        client::RequestMeta,
+        privacy::{mask_report, mask_stats},
+        validation::{
+            validate_comment, validate_saved_search, validate_subscription_email, validate_tag,
+        },
     },
-    services::stats::StatsService,
     AppState,
 };
 
@@ -22,6 +59,7 @@ use crate::{
 pub struct IndexTemplate {
     pub recent_errors: Vec<error_report::Model>,
     pub total_errors: u64,
+    pub pinned_searches: Vec<PinnedSearch>,
     pub base_url: String,
 }
 
@@ -31,26 +69,274 @@ pub struct ErrorListTemplate {
     pub errors: Vec<error_report::Model>,
     pub pagination: crate::models::error_report::PaginationInfo,
     pub filters: ErrorFilters,
+    pub facets: Vec<Facet>,
+    pub sort_options: Vec<SortOption>,
+    /// Query string of the current filters for the feed links
+    pub filter_query: String,
+    /// Query string of the filters, sort order and page size for pagination links
+    pub listing_query: String,
+    /// Tags of the listed reports by report ID
+    pub tags: HashMap<i32, Vec<String>>,
     pub base_url: String,
 }
 
+impl ErrorListTemplate {
+    fn tags_of(&self, error_report_id: &i32) -> &[String] {
+        self.tags
+            .get(error_report_id)
+            .map_or(&[], |tags| tags.as_slice())
+    }
+}
+
+/// Entry of the sort order selector on the error list
+#[derive(Debug)]
+pub struct SortOption {
+    pub value: String,
+    pub label: &'static str,
+    pub selected: bool,
+}
+
+impl SortOption {
+    fn all(current: ErrorSort) -> Vec<Self> {
+        let options = [
+            (SortField::CreatedAt, true, "Newest first"),
+            (SortField::CreatedAt, false, "Oldest first"),
+            (SortField::Id, true, "Highest ID first"),
+            (SortField::Id, false, "Lowest ID first"),
+            (SortField::Machine, false, "Machine"),
+            (SortField::Distro, false, "Distro"),
+            (SortField::DistroVersion, false, "Distro version"),
+            (SortField::ErrorType, false, "Error type"),
+            (SortField::FailurePackage, false, "Package"),
+            (SortField::FailureTask, false, "Task"),
+        ];
+        options
+            .into_iter()
+            .map(|(field, descending, label)| {
+                let sort = ErrorSort { field, descending };
+                Self {
+                    value: sort.to_string(),
+                    label,
+                    selected: sort == current,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Number of reports in a feed unless `per_page` asks for another amount
+const FEED_DEFAULT_ENTRIES: u64 = 50;
+/// Length of the `error_details` excerpt used as entry summary
+const FEED_SUMMARY_CHARS: usize = 500;
+
+#[derive(Template)]
+#[template(path = "errors_atom.xml")]
+pub struct ErrorsAtomTemplate {
+    pub feed: ErrorFeed,
+}
+
+#[derive(Template)]
+#[template(path = "errors_rss.xml")]
+pub struct ErrorsRssTemplate {
+    pub feed: ErrorFeed,
+}
+
+/// Newest reports matching a filter, shared by the Atom and RSS feeds
+pub struct ErrorFeed {
+    pub title: String,
+    pub self_url: String,
+    pub alternate_url: String,
+    pub updated: DateTime<Utc>,
+    pub entries: Vec<FeedEntry>,
+}
+
+pub struct FeedEntry {
+    pub title: String,
+    pub link: String,
+    pub summary: String,
+    pub created_at: DateTime<Utc>,
+    /// `(kind, value)` pairs for the machine, distro and package
+    pub categories: Vec<(&'static str, String)>,
+}
+
+/// Fields of a report shown in a feed. `error_details` holds one character
+/// more than the summary, to tell whether it was cut short.
+#[derive(Debug, FromQueryResult)]
+struct FeedReport {
+    id: i32,
+    created_at: DateTime<Utc>,
+    machine: String,
+    distro: String,
+    failure_task: String,
+    failure_package: String,
+    error_details: String,
+}
+
+impl FeedEntry {
+    fn from_report(report: &FeedReport, base_url: &str) -> Self {
+        let mut summary: String = report
+            .error_details
+            .chars()
+            .take(FEED_SUMMARY_CHARS)
+            .collect();
+        if summary.len() < report.error_details.len() {
+            summary.push('…');
+        }
+
+        Self {
+            title: format!(
+                "{}: {} failed on {}",
+                report.failure_package, report.failure_task, report.machine
+            ),
+            link: format!("{}/Errors/Details/{}/", base_url, report.id),
+            summary,
+            created_at: report.created_at,
+            categories: vec![
+                ("machine", report.machine.clone()),
+                ("distro", report.distro.clone()),
+                ("package", report.failure_package.clone()),
+            ],
+        }
+    }
+}
+
 #[derive(Template)]
 #[template(path = "error_detail.html")]
 pub struct ErrorDetailTemplate {
     pub error: error_report::Model,
     pub build_config: Option<build_configuration::Model>,
     pub similar_errors: Vec<error_report::Model>,
+    pub known_issues: Vec<known_issue::Model>,
+    pub redactions: Vec<report_redaction::Model>,
+    /// End of the build log, the full log has its own page
+    pub log_chunk: LogChunk,
+    pub tags: Vec<String>,
+    pub comments: Vec<CommentView>,
+    pub comment_action: String,
+    /// Why a submitted tag or comment was rejected
+    pub error_message: Option<String>,
     pub base_url: String,
     pub bugzilla_url: String,
 }
 
+/// Lines of the build log previewed on the error detail page
+const LOG_PREVIEW_LINES: usize = 40;
+
+#[derive(Template)]
+#[template(path = "error_log.html")]
+pub struct ErrorLogTemplate {
+    pub error: error_report::Model,
+    pub log_chunk: LogChunk,
+    pub from_error_details: bool,
+    pub base_url: String,
+}
+
+#[derive(Template)]
+#[template(path = "log_chunk.html")]
+pub struct LogChunkTemplate {
+    pub log_chunk: LogChunk,
+}
+
+#[derive(Template)]
+#[template(path = "compare.html")]
+pub struct CompareTemplate {
+    pub comparison: ReportComparison,
+    pub base_url: String,
+}
+
 #[derive(Template)]
 #[template(path = "stats.html")]
 pub struct StatsTemplate {
     pub stats: crate::services::stats::ErrorStats,
+    pub filters: ErrorFilters,
+    pub hidden_filters: Vec<(&'static str, String)>,
+    /// Current filters, for links that keep them
+    pub query: ErrorQuery,
+    pub top: u64,
+    pub base_url: String,
+}
+
+impl StatsTemplate {
+    /// Query string of the statistics restricted to one value of a filter
+    fn drill_down(&self, name: &str, value: &str) -> String {
+        let mut query = self.query.clone();
+        query.narrow(name, vec![value.to_string()]);
+        query.filter_query()
+    }
+
+    /// Share of all matching reports, in percent
+    fn percentage(&self, count: &u64) -> f64 {
+        if self.stats.total_errors == 0 {
+            return 0.0;
+        }
+        *count as f64 * 100.0 / self.stats.total_errors as f64
+    }
+}
+
+#[derive(Template)]
+#[template(path = "failure_group.html")]
+pub struct FailureGroupTemplate {
+    pub group: failure_group::Model,
+    pub activity: GroupActivity,
+    pub host_correlation: Vec<HostCorrelation>,
+    pub recent_reports: Vec<error_report::Model>,
+    pub comments: Vec<CommentView>,
+    pub comment_action: String,
+    /// Why a submitted comment was rejected
+    pub error_message: Option<String>,
+    pub base_url: String,
+}
+
+#[derive(Template)]
+#[template(path = "stats_matrix.html")]
+pub struct StatsMatrixTemplate {
+    pub matrix: ErrorMatrix,
+    pub filters: ErrorFilters,
+    pub hidden_filters: Vec<(&'static str, String)>,
+    /// Current filters, for links that keep them
+    pub query: ErrorQuery,
+    pub base_url: String,
+}
+
+#[derive(Template)]
+#[template(path = "regressions.html")]
+pub struct RegressionsTemplate {
+    pub report: RegressionReport,
+    pub base_url: String,
+}
+
+#[derive(Template)]
+#[template(path = "saved_searches.html")]
+pub struct SavedSearchesTemplate {
+    pub searches: Vec<SavedSearchEntry>,
+    pub owner: String,
+    pub error_message: Option<String>,
+    pub notice: Option<String>,
+    pub base_url: String,
+}
+
+/// Confirming or cancelling a saved search subscription from an emailed link
+#[derive(Template)]
+#[template(path = "saved_search_subscription.html")]
+pub struct SubscriptionTemplate {
+    pub search: saved_search::Model,
+    pub email: String,
+    pub token: String,
+    /// Confirming rather than unsubscribing
+    pub confirming: bool,
+    /// The action has been carried out
+    pub done: bool,
     pub base_url: String,
 }
 
+/// Owner whose private searches are listed along with the shared ones
+#[derive(Debug, Default, Deserialize)]
+pub struct SavedSearchParams {
+    pub owner: Option<String>,
+}
+
+/// Current filters for the filter forms. Text inputs hold a single value, so a
+/// filter given several values is left out; see `hidden_filters`.
 #[derive(Debug)]
 pub struct ErrorFilters {
     pub machine: Option<String>,
@@ -58,26 +344,72 @@ pub struct ErrorFilters {
     pub distro_version: Option<String>,
     pub error_type: Option<String>,
     pub failure_package: Option<String>,
+    pub nativelsbstring: Option<String>,
+    pub build_sys: Option<String>,
     pub search: Option<String>,
+    pub date_from: Option<String>,
+    pub date_to: Option<String>,
 }
 
 impl ErrorFilters {
     pub fn from_query(query: &ErrorQuery) -> Self {
         Self {
-            machine: query.machine.clone(),
-            distro: query.distro.clone(),
-            distro_version: query.distro_version.clone(),
-            error_type: query.error_type.clone(),
-            failure_package: query.failure_package.clone(),
+            machine: single(&query.machine),
+            distro: single(&query.distro),
+            distro_version: single(&query.distro_version),
+            error_type: single(&query.error_type),
+            failure_package: single(&query.failure_package),
+            nativelsbstring: single(&query.nativelsbstring),
+            build_sys: single(&query.build_sys),
             search: query.search.clone(),
+            date_from: query.date_from.clone(),
+            date_to: query.date_to.clone(),
         }
     }
 }
 
+fn single(values: &[String]) -> Option<String> {
+    match values {
+        [value] => Some(value.clone()),
+        _ => None,
+    }
+}
+
+/// Filters a form has no text input for, or that have several values, as
+/// `(parameter, value)` pairs. Forms submit them as hidden inputs so that
+/// applying the form keeps them.
+pub fn hidden_filters(query: &ErrorQuery, inputs: &[&str]) -> Vec<(&'static str, String)> {
+    let pairs = query.filter_pairs();
+    let value_count = |name: &str| pairs.iter().filter(|(other, _)| *other == name).count();
+    pairs
+        .iter()
+        .filter(|(name, _)| !inputs.contains(name) || value_count(name) > 1)
+        .cloned()
+        .collect()
+}
+
+/// Filters with a text input on the statistics page
+const STATS_FORM_INPUTS: [&str; 10] = [
+    "machine",
+    "distro",
+    "distro_version",
+    "error_type",
+    "failure_package",
+    "nativelsbstring",
+    "build_sys",
+    "search",
+    "date_from",
+    "date_to",
+];
+
+/// Filters with a text input on the failure matrix page
+const MATRIX_FORM_INPUTS: [&str; 4] = ["distro", "error_type", "date_from", "date_to"];
+
 /// Homepage with recent errors and basic stats
 pub async fn index(State(app_state): State<AppState>) -> Result<Html<String>, StatusCode> {
     // Get recent errors
     let recent_errors = ErrorReport::find()
+        .filter(error_report::visible())
         .order_by_desc(error_report::Column::CreatedAt)
         .limit(10)
         .all(&app_state.db)
@@ -89,6 +421,7 @@ pub async fn index(State(app_state): State<AppState>) -> Result<Html<String>, St
 
     // Get total count
     let total_errors = ErrorReport::find()
+        .filter(error_report::visible())
         .count(&app_state.db)
         .await
         .map_err(|e| {
@@ -96,9 +429,19 @@ pub async fn index(State(app_state): State<AppState>) -> Result<Html<String>, St
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
+    let pinned_searches = SavedSearchService::new(app_state.db.clone())
+        .with_email_visibility(app_state.config.submitter_email_visibility)
+        .pinned_with_counts()
+        .await
+        .map_err(|e| {
+            error!("Failed to count pinned searches: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
     let template = IndexTemplate {
         recent_errors,
         total_errors,
+        pinned_searches,
         base_url: app_state.config.base_url.clone(),
     };
 
@@ -113,79 +456,172 @@ pub async fn index(State(app_state): State<AppState>) -> Result<Html<String>, St
 /// Error list page with filtering and pagination
 pub async fn error_list_page(
     State(app_state): State<AppState>,
-    Query(params): Query<ErrorQuery>,
+    FilterQuery(mut params): FilterQuery<ErrorQuery>,
 ) -> Result<Html<String>, StatusCode> {
-    let page = params.page.unwrap_or(1);
-    let per_page = params.per_page.unwrap_or(25).min(100);
+    params.email_visibility = Some(app_state.config.submitter_email_visibility);
+    // Same filters and ordering as the API
+    let list = ErrorListService::new(app_state.db.clone())
+        .list(&params, 25)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch errors: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
-    let mut query = ErrorReport::find();
+    let facets = FacetService::new(app_state.db.clone())
+        .get_facets(&params, DEFAULT_FACET_SIZE)
+        .await
+        .map_err(|e| {
+            error!("Failed to get facets: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
-    // Apply filters (same logic as API)
-    if let Some(machine) = &params.machine {
-        query = query.filter(error_report::Column::Machine.eq(machine));
-    }
-    if let Some(distro) = &params.distro {
-        query = query.filter(error_report::Column::Distro.eq(distro));
-    }
-    if let Some(distro_version) = &params.distro_version {
-        query = query.filter(error_report::Column::DistroVersion.eq(distro_version));
-    }
-    if let Some(error_type) = &params.error_type {
-        query = query.filter(error_report::Column::ErrorType.eq(error_type));
-    }
-    if let Some(failure_package) = &params.failure_package {
-        query = query.filter(error_report::Column::FailurePackage.eq(failure_package));
-    }
-    if let Some(search_term) = &params.search {
-        query = query.filter(
-            error_report::Column::ErrorDetails
-                .contains(search_term)
-                .or(error_report::Column::FailurePackage.contains(search_term))
-                .or(error_report::Column::FailureTask.contains(search_term)),
-        );
-    }
+    let ids: Vec<i32> = list.errors.iter().map(|error| error.id).collect();
+    let tags = TagService::new(app_state.db.clone())
+        .tags_for_reports(&ids)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch tags: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
-    let paginator = query
-        .order_by_desc(error_report::Column::CreatedAt)
-        .paginate(&app_state.db, per_page);
+    let sort = params.sort.unwrap_or_default();
+    let template = ErrorListTemplate {
+        errors: list.errors,
+        pagination: list.pagination,
+        filters: ErrorFilters::from_query(&params),
+        facets,
+        sort_options: SortOption::all(sort),
+        filter_query: params.filter_query(),
+        listing_query: params.listing_query(),
+        tags,
+        base_url: app_state.config.base_url.clone(),
+    };
 
-    let errors = paginator.fetch_page(page - 1).await.map_err(|e| {
-        error!("Failed to fetch errors: {:?}", e);
+    let html = template.render().map_err(|e| {
+        error!("Template rendering failed: {:?}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    let total = paginator.num_items().await.map_err(|e| {
-        error!("Failed to count errors: {:?}", e);
+    Ok(Html(html))
+}
+
+/// Atom feed of the newest errors matching the filters
+pub async fn error_feed_atom(
+    State(app_state): State<AppState>,
+    FilterQuery(mut params): FilterQuery<ErrorQuery>,
+) -> Result<Response, StatusCode> {
+    params.email_visibility = Some(app_state.config.submitter_email_visibility);
+    let feed = error_feed(&app_state, &params, "feed.atom").await?;
+    let xml = ErrorsAtomTemplate { feed }.render().map_err(|e| {
+        error!("Template rendering failed: {:?}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    let template = ErrorListTemplate {
-        errors,
-        pagination: crate::models::error_report::PaginationInfo {
-            page,
-            per_page,
-            total,
-            total_pages: (total + per_page - 1) / per_page,
-        },
-        filters: ErrorFilters::from_query(&params),
-        base_url: app_state.config.base_url.clone(),
-    };
+    Ok((
+        [(header::CONTENT_TYPE, "application/atom+xml; charset=utf-8")],
+        xml,
+    )
+        .into_response())
+}
 
-    let html = template.render().map_err(|e| {
+/// RSS feed of the newest errors matching the filters
+pub async fn error_feed_rss(
+    State(app_state): State<AppState>,
+    FilterQuery(mut params): FilterQuery<ErrorQuery>,
+) -> Result<Response, StatusCode> {
+    params.email_visibility = Some(app_state.config.submitter_email_visibility);
+    let feed = error_feed(&app_state, &params, "feed.rss").await?;
+    let xml = ErrorsRssTemplate { feed }.render().map_err(|e| {
         error!("Template rendering failed: {:?}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    Ok(Html(html))
+    Ok((
+        [(header::CONTENT_TYPE, "application/rss+xml; charset=utf-8")],
+        xml,
+    )
+        .into_response())
+}
+
+async fn error_feed(
+    app_state: &AppState,
+    params: &ErrorQuery,
+    feed_path: &str,
+) -> Result<ErrorFeed, StatusCode> {
+    let limit = params.per_page.unwrap_or(FEED_DEFAULT_ENTRIES).min(100);
+
+    let reports = ErrorReport::find()
+        .select_only()
+        .columns([
+            error_report::Column::Id,
+            error_report::Column::CreatedAt,
+            error_report::Column::Machine,
+            error_report::Column::Distro,
+            error_report::Column::FailureTask,
+            error_report::Column::FailurePackage,
+        ])
+        .column_as(
+            Expr::cust_with_values(
+                "left(error_reports.error_details, $1)",
+                [FEED_SUMMARY_CHARS as i32 + 1],
+            ),
+            "error_details",
+        )
+        .filter(params.condition())
+        .order_by_desc(error_report::Column::CreatedAt)
+        .limit(limit)
+        .into_model::<FeedReport>()
+        .all(&app_state.db)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch errors for feed: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let base_url = &app_state.config.base_url;
+    let query = params.filter_query();
+    let filters: Vec<String> = params
+        .filter_pairs()
+        .into_iter()
+        .map(|(name, value)| format!("{}: {}", name, value))
+        .collect();
+    let title = if filters.is_empty() {
+        "Yocto Project Error Reports".to_string()
+    } else {
+        format!("Yocto Project Error Reports ({})", filters.join(", "))
+    };
+
+    Ok(ErrorFeed {
+        title,
+        self_url: format!("{}/Errors/{}?{}", base_url, feed_path, query),
+        alternate_url: format!("{}/Errors?{}", base_url, query),
+        updated: reports
+            .first()
+            .map_or_else(Utc::now, |report| report.created_at),
+        entries: reports
+            .iter()
+            .map(|report| FeedEntry::from_report(report, base_url))
+            .collect(),
+    })
 }
 
 /// Error detail page
 pub async fn error_detail_page(
     State(app_state): State<AppState>,
     Path(id): Path<i32>,
+) -> Result<Html<String>, StatusCode> {
+    render_error_detail(&app_state, id, None).await
+}
+
+async fn render_error_detail(
+    app_state: &AppState,
+    id: i32,
+    error_message: Option<String>,
 ) -> Result<Html<String>, StatusCode> {
     // Get the error
-    let error = ErrorReport::find_by_id(id)
+    let mut error = ErrorReport::find_by_id(id)
+        .filter(error_report::visible())
         .one(&app_state.db)
         .await
         .map_err(|e| {
@@ -206,6 +642,7 @@ pub async fn error_detail_page(
 
     // Find similar errors (same error type and package)
     let similar_errors = ErrorReport::find()
+        .filter(error_report::visible())
         .filter(error_report::Column::ErrorType.eq(&error.error_type))
         .filter(error_report::Column::FailurePackage.eq(&error.failure_package))
         .filter(error_report::Column::Id.ne(id))
@@ -218,10 +655,60 @@ pub async fn error_detail_page(
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
+    // Get known issues matched when the report was submitted
+    let known_issues = KnownIssueService::new(app_state.db.clone())
+        .issues_for_report(&error)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch known issues for error {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    // Get redaction rules that fired on submission
+    let redactions = ReportRedaction::find()
+        .filter(report_redaction::Column::ErrorReportId.eq(id))
+        .order_by_asc(report_redaction::Column::Id)
+        .all(&app_state.db)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch redactions for error {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let tags = TagService::new(app_state.db.clone())
+        .tags_for_report(id)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch tags of error {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let comments = CommentService::new(app_state.db.clone())
+        .thread(CommentTarget::Report(id))
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch comments on error {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    mask_report(app_state.config.submitter_email_visibility, &mut error);
+
+    let log_chunk = LogChunk::tail(&error.log_data, LOG_PREVIEW_LINES);
+
     let template = ErrorDetailTemplate {
         error,
         build_config,
         similar_errors,
+        known_issues,
+        redactions,
+        log_chunk,
+        tags,
+        comments,
+        comment_action: format!(
+            "{}/Errors/Details/{}/comments",
+            app_state.config.base_url, id
+        ),
+        error_message,
         base_url: app_state.config.base_url.clone(),
         bugzilla_url: app_state.config.bugzilla_url.clone(),
     };
@@ -234,17 +721,145 @@ pub async fn error_detail_page(
     Ok(Html(html))
 }
 
-/// Statistics page
-pub async fn stats_page(State(app_state): State<AppState>) -> Result<Html<String>, StatusCode> {
-    let stats_service = StatsService::new(app_state.db.clone());
+/// Side-by-side comparison of two errors
+pub async fn compare_page(
+    State(app_state): State<AppState>,
+    Path((a, b)): Path<(i32, i32)>,
+) -> Result<Html<String>, StatusCode> {
+    let comparison = CompareService::new(app_state.db.clone())
+        .compare(a, b)
+        .await
+        .map_err(|e| {
+            error!("Failed to compare errors {} and {}: {:?}", a, b, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let template = CompareTemplate {
+        comparison,
+        base_url: app_state.config.base_url.clone(),
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Template rendering failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Html(html))
+}
+
+/// Full build log of an error, rendered in chunks
+pub async fn error_log_page(
+    State(app_state): State<AppState>,
+    Path(id): Path<i32>,
+    Query(params): Query<LogParams>,
+) -> Result<Html<String>, StatusCode> {
+    let (start, lines) = params.range().map_err(|e| {
+        error!("Invalid log parameters: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let mut error = find_error(&app_state, id).await?;
+    mask_report(app_state.config.submitter_email_visibility, &mut error);
+
+    // Reports without a stored log (purged, or imported) still have the error details
+    let from_error_details = error.log_data.trim().is_empty();
+    let log = if from_error_details {
+        &error.error_details
+    } else {
+        &error.log_data
+    };
+    let log_chunk = LogChunk::new(log, start, lines);
 
-    let stats = stats_service.get_error_stats().await.map_err(|e| {
-        error!("Failed to get stats: {:?}", e);
+    let template = ErrorLogTemplate {
+        error,
+        log_chunk,
+        from_error_details,
+        base_url: app_state.config.base_url.clone(),
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Template rendering failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Html(html))
+}
+
+/// Further lines of a build log, loaded by the log page as it scrolls
+pub async fn error_log_chunk(
+    State(app_state): State<AppState>,
+    Path(id): Path<i32>,
+    Query(params): Query<LogParams>,
+) -> Result<Html<String>, StatusCode> {
+    let (start, lines) = params.range().map_err(|e| {
+        error!("Invalid log parameters: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let error = find_error(&app_state, id).await?;
+    let log = if error.log_data.trim().is_empty() {
+        &error.error_details
+    } else {
+        &error.log_data
+    };
+
+    let template = LogChunkTemplate {
+        log_chunk: LogChunk::new(log, start, lines),
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Template rendering failed: {:?}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
+    Ok(Html(html))
+}
+
+async fn find_error(app_state: &AppState, id: i32) -> Result<error_report::Model, StatusCode> {
+    ErrorReport::find_by_id(id)
+        .filter(error_report::visible())
+        .one(&app_state.db)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch error {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Statistics page
+pub async fn stats_page(
+    State(app_state): State<AppState>,
+    FilterQuery(mut filters): FilterQuery<ErrorQuery>,
+    Query(params): Query<StatsParams>,
+) -> Result<Html<String>, StatusCode> {
+    filters.email_visibility = Some(app_state.config.submitter_email_visibility);
+    let options =
+        StatsOptions::new(&params, &filters, app_state.config.stats.top_n).map_err(|e| {
+            error!("Invalid stats parameters: {}", e);
+            StatusCode::BAD_REQUEST
+        })?;
+
+    let stats_service = StatsService::new(app_state.db.clone())
+        .with_rollups(app_state.config.stats.rollup_refresh_secs.is_some());
+
+    let mut stats = stats_service
+        .get_cached_error_stats(&app_state.stats_cache, &options)
+        .await
+        .map_err(|e| {
+            error!("Failed to get stats: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    mask_stats(app_state.config.submitter_email_visibility, &mut stats);
+
     let template = StatsTemplate {
         stats,
+        filters: ErrorFilters::from_query(&filters),
+        hidden_filters: hidden_filters(&filters, &STATS_FORM_INPUTS),
+        top: options.top,
+        query: filters,
         base_url: app_state.config.base_url.clone(),
     };
 
@@ -255,3 +870,738 @@ pub async fn stats_page(State(app_state): State<AppState>) -> Result<Html<String
 
     Ok(Html(html))
 }
+
+/// Failure group detail page with its host distribution
+pub async fn failure_group_page(
+    State(app_state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<Html<String>, StatusCode> {
+    render_failure_group(&app_state, id, None).await
+}
+
+async fn render_failure_group(
+    app_state: &AppState,
+    id: i32,
+    error_message: Option<String>,
+) -> Result<Html<String>, StatusCode> {
+    let group = FailureGroup::find_by_id(id)
+        .one(&app_state.db)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch failure group {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let group_service = FailureGroupService::new(app_state.db.clone());
+
+    let activity = group_service.activity(id).await.map_err(|e| {
+        error!("Failed to fetch activity of failure group {}: {:?}", id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Compare against everything submitted while the failure has been around
+    let host_correlation = match activity.first_seen {
+        Some(first_seen) => group_service
+            .host_correlation(id, first_seen)
+            .await
+            .map_err(|e| {
+                error!("Failed to correlate hosts of failure group {}: {:?}", id, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?,
+        None => Vec::new(),
+    };
+
+    let recent_reports = ErrorReport::find()
+        .filter(error_report::visible())
+        .filter(error_report::Column::FailureGroupId.eq(id))
+        .order_by_desc(error_report::Column::CreatedAt)
+        .limit(20)
+        .all(&app_state.db)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch reports of failure group {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let comments = CommentService::new(app_state.db.clone())
+        .thread(CommentTarget::Group(id))
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch comments on failure group {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let template = FailureGroupTemplate {
+        group,
+        activity,
+        host_correlation,
+        recent_reports,
+        comments,
+        comment_action: format!("{}/Groups/{}/comments", app_state.config.base_url, id),
+        error_message,
+        base_url: app_state.config.base_url.clone(),
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Template rendering failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Html(html))
+}
+
+/// Heatmap of failing recipes by machine or distro version
+pub async fn matrix_page(
+    State(app_state): State<AppState>,
+    FilterQuery(mut filters): FilterQuery<ErrorQuery>,
+    Query(params): Query<MatrixParams>,
+) -> Result<Html<String>, StatusCode> {
+    filters.email_visibility = Some(app_state.config.submitter_email_visibility);
+    let (rows, cols) = params.size().map_err(|e| {
+        error!("Invalid matrix parameters: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let matrix = MatrixService::new(app_state.db.clone())
+        .get_matrix(params.columns(), &filters.condition(), rows, cols)
+        .await
+        .map_err(|e| {
+            error!("Failed to get failure matrix: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let template = StatsMatrixTemplate {
+        matrix,
+        filters: ErrorFilters::from_query(&filters),
+        hidden_filters: hidden_filters(&filters, &MATRIX_FORM_INPUTS),
+        query: filters,
+        base_url: app_state.config.base_url.clone(),
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Template rendering failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Html(html))
+}
+
+/// Failure groups new for a branch or distro version
+pub async fn regressions_page(
+    State(app_state): State<AppState>,
+    Query(params): Query<RegressionParams>,
+) -> Result<Html<String>, StatusCode> {
+    let days = params.days().map_err(|e| {
+        error!("Invalid regression parameters: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let report = RegressionService::new(app_state.db.clone())
+        .find_regressions(params.scope(), days)
+        .await
+        .map_err(|e| {
+            error!("Failed to find regressions: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let template = RegressionsTemplate {
+        report,
+        base_url: app_state.config.base_url.clone(),
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Template rendering failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Html(html))
+}
+
+/// Saved searches shared with the team, plus the private ones of `owner`
+pub async fn saved_searches_page(
+    State(app_state): State<AppState>,
+    Query(params): Query<SavedSearchParams>,
+) -> Result<Html<String>, StatusCode> {
+    render_saved_searches(&app_state, params.owner.unwrap_or_default(), None, None).await
+}
+
+async fn render_saved_searches(
+    app_state: &AppState,
+    owner: String,
+    error_message: Option<String>,
+    notice: Option<String>,
+) -> Result<Html<String>, StatusCode> {
+    let searches = SavedSearchService::new(app_state.db.clone())
+        .list(Some(&owner))
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch saved searches: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let template = SavedSearchesTemplate {
+        searches,
+        owner,
+        error_message,
+        notice,
+        base_url: app_state.config.base_url.clone(),
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Template rendering failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Html(html))
+}
+
+/// Save the filters of the error list under a name
+pub async fn create_saved_search(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Form(data): Form<SavedSearchData>,
+) -> Result<Response, StatusCode> {
+    let owner = data.owner.clone().unwrap_or_default();
+    if let Err(validation_error) = validate_saved_search(&data) {
+        let html =
+            render_saved_searches(&app_state, owner, Some(validation_error.to_string()), None)
+                .await?;
+        return Ok((StatusCode::BAD_REQUEST, html).into_response());
+    }
+
+    let txn = begin(&app_state.db).await?;
+    let search = SavedSearchService::new(app_state.db.clone())
+        .create(&txn, data)
+        .await
+        .map_err(|e| {
+            error!("Failed to save search: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("Saved search {} as /s/{}", search.name, search.slug);
+    AuditService::record(
+        &txn,
+        &meta,
+        AuditEntry::new(audit_event::ACTION_CREATE, audit_event::ENTITY_SAVED_SEARCH)
+            .entity(search.id)
+            .actor(search.owner.as_deref())
+            .after(&search),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to record audit event: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    commit(txn).await?;
+
+    // Private searches are only listed for their owner
+    let owner_query = serde_urlencoded::to_string([("owner", owner.trim())]).unwrap_or_default();
+    Ok(Redirect::to(&format!(
+        "{}/Searches/?{}",
+        app_state.config.base_url, owner_query
+    ))
+    .into_response())
+}
+
+/// Delete a saved search and its subscriptions, which only its owner may do
+pub async fn delete_saved_search(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path(id): Path<i32>,
+    Form(form): Form<ActorForm>,
+) -> Result<Response, StatusCode> {
+    let saved_searches = SavedSearchService::new(app_state.db.clone());
+    let search = saved_searches
+        .get(id)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch saved search {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    // Searches without an owner are only deleted from the admin pages
+    if !search.is_owned_by(form.actor.as_deref()) {
+        let message = match &search.owner {
+            Some(_) => {
+                "Only the owner of a saved search can delete it; enter the owner it was saved with"
+            }
+            None => "This saved search has no owner and can only be deleted by an administrator",
+        };
+        let html = render_saved_searches(
+            &app_state,
+            form.actor.unwrap_or_default(),
+            Some(message.to_string()),
+            None,
+        )
+        .await?;
+        return Ok((StatusCode::FORBIDDEN, html).into_response());
+    }
+
+    let txn = begin(&app_state.db).await?;
+    let deleted = saved_searches.delete(&txn, id).await.map_err(|e| {
+        error!("Failed to delete saved search {}: {:?}", id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if !deleted {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    info!("Deleted saved search {}", id);
+    AuditService::record(
+        &txn,
+        &meta,
+        AuditEntry::new(audit_event::ACTION_DELETE, audit_event::ENTITY_SAVED_SEARCH)
+            .entity(id)
+            .actor(form.actor.as_deref())
+            .before(&search),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to record audit event: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    commit(txn).await?;
+
+    Ok(Redirect::to(&format!("{}/Searches/", app_state.config.base_url)).into_response())
+}
+
+/// Ask the owner of the given address to confirm a subscription to a saved
+/// search. Nothing else is sent until they do.
+pub async fn subscribe_saved_search(
+    State(app_state): State<AppState>,
+    Path(id): Path<i32>,
+    Form(data): Form<SubscriptionData>,
+) -> Result<Response, StatusCode> {
+    if let Err(validation_error) = validate_subscription_email(&data.email) {
+        let html = render_saved_searches(
+            &app_state,
+            String::new(),
+            Some(validation_error.to_string()),
+            None,
+        )
+        .await?;
+        return Ok((StatusCode::BAD_REQUEST, html).into_response());
+    }
+
+    let saved_searches = SavedSearchService::new(app_state.db.clone());
+    saved_searches
+        .get(id)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch saved search {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let pending = saved_searches
+        .subscribe(id, &data.email)
+        .await
+        .map_err(|e| {
+            error!("Failed to subscribe to saved search {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if let Some(subscription) = pending {
+        JobService::new(app_state.db.clone(), app_state.config.jobs.clone())
+            .enqueue(
+                &app_state.db,
+                &JobPayload::ConfirmSubscription {
+                    subscription_id: subscription.id,
+                },
+            )
+            .await
+            .map_err(|e| {
+                error!(
+                    "Failed to queue confirmation for subscription {}: {:?}",
+                    subscription.id, e
+                );
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+    }
+
+    // The same answer whether or not a message was sent, so the form doesn't
+    // tell who is subscribed
+    let html = render_saved_searches(
+        &app_state,
+        String::new(),
+        None,
+        Some(
+            "Unless one was sent recently, a confirmation link is on its way to that address. \
+             Notifications start once it has been followed."
+                .to_string(),
+        ),
+    )
+    .await?;
+    Ok(html.into_response())
+}
+
+/// Page behind the link in a confirmation message. Confirming takes a POST so
+/// that mail scanners following the link don't subscribe anyone.
+pub async fn confirm_subscription_page(
+    State(app_state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Html<String>, StatusCode> {
+    let (subscription, search) = find_subscription(&app_state, &token).await?;
+    let done = subscription.confirmed_at.is_some();
+    render_subscription(&app_state, search, subscription, true, done)
+}
+
+/// Start notifying a subscription
+pub async fn confirm_subscription(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path(token): Path<String>,
+) -> Result<Html<String>, StatusCode> {
+    let (subscription, search) = find_subscription(&app_state, &token).await?;
+    let was_confirmed = subscription.confirmed_at.is_some();
+    let txn = begin(&app_state.db).await?;
+    let subscription = SavedSearchService::new(app_state.db.clone())
+        .confirm(&txn, subscription)
+        .await
+        .map_err(|e| {
+            error!("Failed to confirm subscription: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if !was_confirmed {
+        // Subscriber addresses stay out of the log, like submitter emails
+        AuditService::record(
+            &txn,
+            &meta,
+            AuditEntry::new(
+                audit_event::ACTION_SUBSCRIBE,
+                audit_event::ENTITY_SAVED_SEARCH,
+            )
+            .entity(search.id),
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to record audit event: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+    commit(txn).await?;
+
+    render_subscription(&app_state, search, subscription, true, true)
+}
+
+/// Page behind the unsubscribe link in every notification
+pub async fn unsubscribe_page(
+    State(app_state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Html<String>, StatusCode> {
+    let (subscription, search) = find_subscription(&app_state, &token).await?;
+    render_subscription(&app_state, search, subscription, false, false)
+}
+
+pub async fn unsubscribe_saved_search(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path(token): Path<String>,
+) -> Result<Html<String>, StatusCode> {
+    let (subscription, search) = find_subscription(&app_state, &token).await?;
+    let txn = begin(&app_state.db).await?;
+    let removed = SavedSearchService::new(app_state.db.clone())
+        .unsubscribe(&txn, subscription.id)
+        .await
+        .map_err(|e| {
+            error!(
+                "Failed to unsubscribe from saved search {}: {:?}",
+                search.id, e
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if removed {
+        AuditService::record(
+            &txn,
+            &meta,
+            AuditEntry::new(
+                audit_event::ACTION_UNSUBSCRIBE,
+                audit_event::ENTITY_SAVED_SEARCH,
+            )
+            .entity(search.id),
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to record audit event: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+    commit(txn).await?;
+
+    render_subscription(&app_state, search, subscription, false, true)
+}
+
+async fn find_subscription(
+    app_state: &AppState,
+    token: &str,
+) -> Result<(saved_search_subscription::Model, saved_search::Model), StatusCode> {
+    SavedSearchService::new(app_state.db.clone())
+        .find_subscription(token)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch subscription: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+fn render_subscription(
+    app_state: &AppState,
+    search: saved_search::Model,
+    subscription: saved_search_subscription::Model,
+    confirming: bool,
+    done: bool,
+) -> Result<Html<String>, StatusCode> {
+    let template = SubscriptionTemplate {
+        search,
+        email: subscription.email,
+        token: subscription.token,
+        confirming,
+        done,
+        base_url: app_state.config.base_url.clone(),
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Template rendering failed: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Html(html))
+}
+
+/// Short link of a saved search, opening the error list with its filters
+pub async fn saved_search_link(
+    State(app_state): State<AppState>,
+    Path(slug): Path<String>,
+) -> Result<Redirect, StatusCode> {
+    let search = find_saved_search(&app_state, &slug).await?;
+    Ok(Redirect::to(&with_query(
+        format!("{}/Errors", app_state.config.base_url),
+        &search.query,
+    )))
+}
+
+/// Statistics restricted to the reports of a saved search
+pub async fn saved_search_stats(
+    State(app_state): State<AppState>,
+    Path(slug): Path<String>,
+) -> Result<Redirect, StatusCode> {
+    let search = find_saved_search(&app_state, &slug).await?;
+    // Sort order and page size don't apply to statistics
+    let filters = parse_query(&search)
+        .map(|query| query.filter_query())
+        .unwrap_or_default();
+    Ok(Redirect::to(&with_query(
+        format!("{}/Stats", app_state.config.base_url),
+        &filters,
+    )))
+}
+
+async fn find_saved_search(
+    app_state: &AppState,
+    slug: &str,
+) -> Result<crate::models::saved_search::Model, StatusCode> {
+    SavedSearchService::new(app_state.db.clone())
+        .get_by_slug(slug)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch saved search {}: {:?}", slug, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+fn with_query(url: String, query: &str) -> String {
+    if query.is_empty() {
+        url
+    } else {
+        format!("{}?{}", url, query)
+    }
+}
+
+/// Attach a tag to an error from its detail page
+pub async fn add_error_tag(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path(id): Path<i32>,
+    Form(data): Form<TagData>,
+) -> Result<Response, StatusCode> {
+    if let Err(validation_error) = validate_tag(&data.name) {
+        let html = render_error_detail(&app_state, id, Some(validation_error.to_string())).await?;
+        return Ok((StatusCode::BAD_REQUEST, html).into_response());
+    }
+
+    ErrorReport::find_by_id(id)
+        .filter(error_report::visible())
+        .one(&app_state.db)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch error {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let txn = begin(&app_state.db).await?;
+    TagService::new(app_state.db.clone())
+        .add(&txn, id, &data.name)
+        .await
+        .map_err(|e| {
+            error!("Failed to tag error {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    AuditService::record(
+        &txn,
+        &meta,
+        AuditEntry::new(audit_event::ACTION_TAG, audit_event::ENTITY_ERROR_REPORT)
+            .entity(id)
+            .actor(data.actor.as_deref())
+            .after(&serde_json::json!({ "tag": normalize_name(&data.name) })),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to record audit event: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    commit(txn).await?;
+    // Statistics may be filtered by tag
+    app_state.stats_cache.invalidate();
+
+    Ok(Redirect::to(&format!(
+        "{}/Errors/Details/{}/#tags",
+        app_state.config.base_url, id
+    ))
+    .into_response())
+}
+
+/// Detach a tag from an error
+pub async fn remove_error_tag(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path((id, name)): Path<(i32, String)>,
+    Form(form): Form<ActorForm>,
+) -> Result<Redirect, StatusCode> {
+    let txn = begin(&app_state.db).await?;
+    let removed = TagService::new(app_state.db.clone())
+        .remove(&txn, id, &name)
+        .await
+        .map_err(|e| {
+            error!("Failed to untag error {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if removed {
+        AuditService::record(
+            &txn,
+            &meta,
+            AuditEntry::new(audit_event::ACTION_UNTAG, audit_event::ENTITY_ERROR_REPORT)
+                .entity(id)
+                .actor(form.actor.as_deref())
+                .before(&serde_json::json!({ "tag": normalize_name(&name) })),
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to record audit event: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+    commit(txn).await?;
+    app_state.stats_cache.invalidate();
+
+    Ok(Redirect::to(&format!(
+        "{}/Errors/Details/{}/#tags",
+        app_state.config.base_url, id
+    )))
+}
+
+/// Comment on an error, or reply to one of its comments
+pub async fn add_error_comment(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path(id): Path<i32>,
+    Form(data): Form<CommentData>,
+) -> Result<Response, StatusCode> {
+    ErrorReport::find_by_id(id)
+        .filter(error_report::visible())
+        .one(&app_state.db)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch error {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    match add_comment(&app_state, &meta, CommentTarget::Report(id), data).await? {
+        Ok(comment_id) => Ok(Redirect::to(&format!(
+            "{}/Errors/Details/{}/#comment-{}",
+            app_state.config.base_url, id, comment_id
+        ))
+        .into_response()),
+        Err(message) => {
+            let html = render_error_detail(&app_state, id, Some(message)).await?;
+            Ok((StatusCode::BAD_REQUEST, html).into_response())
+        }
+    }
+}
+
+/// Comment on a failure group, or reply to one of its comments
+pub async fn add_group_comment(
+    State(app_state): State<AppState>,
+    meta: RequestMeta,
+    Path(id): Path<i32>,
+    Form(data): Form<CommentData>,
+) -> Result<Response, StatusCode> {
+    FailureGroup::find_by_id(id)
+        .one(&app_state.db)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch failure group {}: {:?}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    match add_comment(&app_state, &meta, CommentTarget::Group(id), data).await? {
+        Ok(comment_id) => Ok(Redirect::to(&format!(
+            "{}/Groups/{}/#comment-{}",
+            app_state.config.base_url, id, comment_id
+        ))
+        .into_response()),
+        Err(message) => {
+            let html = render_failure_group(&app_state, id, Some(message)).await?;
+            Ok((StatusCode::BAD_REQUEST, html).into_response())
+        }
+    }
+}
+
+/// The ID of the new comment, or why it was rejected
+async fn add_comment(
+    app_state: &AppState,
+    meta: &RequestMeta,
+    target: CommentTarget,
+    data: CommentData,
+) -> Result<Result<i32, String>, StatusCode> {
+    if let Err(validation_error) = validate_comment(&data) {
+        return Ok(Err(validation_error.to_string()));
+    }
+
+    let txn = begin(&app_state.db).await?;
+    match CommentService::new(app_state.db.clone())
+        .create(&txn, target, data)
+        .await
+    {
+        Ok(comment) => {
+            info!("Added comment {} on {:?}", comment.id, target);
+            AuditService::record(&txn, meta, AuditEntry::comment(target, &comment))
+                .await
+                .map_err(|e| {
+                    error!("Failed to record audit event: {:?}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+            commit(txn).await?;
+            Ok(Ok(comment.id))
+        }
+        Err(CommentError::InvalidParent) => Ok(Err(CommentError::InvalidParent.to_string())),
+        Err(CommentError::Database(e)) => {
+            error!("Failed to save comment on {:?}: {:?}", target, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
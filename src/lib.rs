@@ -5,6 +5,7 @@
 //! reports from Yocto Project builds, providing both an API for submitting errors
 //! and a web interface for browsing them.
 
+pub mod assets;
 pub mod config;
 pub mod handlers;
 pub mod models;
@@ -12,26 +13,32 @@ pub mod services;
 pub mod utils;
 
 pub use config::Config;
+pub use services::stats_cache::StatsCache;
+pub use utils::redaction::Redactor;
 
 use axum::{
     extract::State,
     http::StatusCode,
-    routing::{get, post},
+    routing::{delete, get, post},
     Router,
 };
 use sea_orm::DatabaseConnection;
 use std::sync::Arc;
-use tower_http::{cors::CorsLayer, services::ServeDir, trace::TraceLayer};
+use tower_http::{cors::CorsLayer, trace::TraceLayer};
 
 /// Application state shared across all handlers
 #[derive(Clone)]
 pub struct AppState {
     pub db: DatabaseConnection,
     pub config: Arc<Config>,
+    pub redactor: Arc<Redactor>,
+    pub stats_cache: Arc<StatsCache>,
 }
 
 /// Create the main application router with all routes configured
 pub fn create_app(app_state: AppState) -> Router {
+    assets::init(&app_state.config.static_dir);
+
     Router::new()
         // API routes - maintaining Django compatibility
         .route(
@@ -39,25 +46,175 @@ pub fn create_app(app_state: AppState) -> Router {
             post(handlers::api::submit_error_report),
         )
         .route("/api/errors", get(handlers::api::list_errors))
+        .route("/api/errors/export", get(handlers::api::export_errors))
+        .route("/api/errors/facets", get(handlers::api::get_error_facets))
         .route("/api/errors/:id", get(handlers::api::get_error))
+        .route(
+            "/api/errors/:id/tags",
+            get(handlers::api::get_error_tags).post(handlers::api::add_error_tag),
+        )
+        .route(
+            "/api/errors/:id/tags/:name",
+            delete(handlers::api::remove_error_tag),
+        )
+        .route(
+            "/api/errors/:id/comments",
+            get(handlers::api::get_error_comments).post(handlers::api::add_error_comment),
+        )
+        .route(
+            "/api/groups/:id/comments",
+            get(handlers::api::get_group_comments).post(handlers::api::add_group_comment),
+        )
+        .route("/api/tags", get(handlers::api::list_tags))
+        .route(
+            "/api/errors/compare/:a/:b",
+            get(handlers::api::compare_errors),
+        )
         .route("/api/stats", get(handlers::api::get_stats))
+        .route("/api/stats/matrix", get(handlers::api::get_matrix))
+        .route("/api/regressions", get(handlers::api::get_regressions))
         // Web interface routes
         .route("/", get(handlers::web::index))
         .route("/Errors", get(handlers::web::error_list_page))
         .route("/Errors/", get(handlers::web::error_list_page))
+        .route("/Errors/feed.atom", get(handlers::web::error_feed_atom))
+        .route("/Errors/feed.rss", get(handlers::web::error_feed_rss))
         .route(
             "/Errors/Details/:id/",
             get(handlers::web::error_detail_page),
         )
+        .route(
+            "/Errors/Details/:id/tags",
+            post(handlers::web::add_error_tag),
+        )
+        .route(
+            "/Errors/Details/:id/tags/:name/delete",
+            post(handlers::web::remove_error_tag),
+        )
+        .route(
+            "/Errors/Details/:id/comments",
+            post(handlers::web::add_error_comment),
+        )
+        .route(
+            "/Errors/Details/:id/Log/",
+            get(handlers::web::error_log_page),
+        )
+        .route(
+            "/Errors/Details/:id/Log/chunk",
+            get(handlers::web::error_log_chunk),
+        )
+        .route("/Errors/Compare/:a/:b/", get(handlers::web::compare_page))
+        .route("/Groups/:id/", get(handlers::web::failure_group_page))
+        .route(
+            "/Groups/:id/comments",
+            post(handlers::web::add_group_comment),
+        )
         .route("/Stats", get(handlers::web::stats_page))
         .route("/Stats/", get(handlers::web::stats_page))
+        .route("/Stats/Matrix", get(handlers::web::matrix_page))
+        .route("/Regressions", get(handlers::web::regressions_page))
+        .route("/Regressions/", get(handlers::web::regressions_page))
+        .route(
+            "/Searches",
+            get(handlers::web::saved_searches_page).post(handlers::web::create_saved_search),
+        )
+        .route(
+            "/Searches/",
+            get(handlers::web::saved_searches_page).post(handlers::web::create_saved_search),
+        )
+        .route(
+            "/Searches/:id/delete",
+            post(handlers::web::delete_saved_search),
+        )
+        .route(
+            "/Searches/:id/subscribe",
+            post(handlers::web::subscribe_saved_search),
+        )
+        .route(
+            "/Searches/subscriptions/:token/confirm",
+            get(handlers::web::confirm_subscription_page).post(handlers::web::confirm_subscription),
+        )
+        .route(
+            "/Searches/subscriptions/:token/unsubscribe",
+            get(handlers::web::unsubscribe_page).post(handlers::web::unsubscribe_saved_search),
+        )
+        .route("/s/:slug", get(handlers::web::saved_search_link))
+        .route("/s/:slug/stats", get(handlers::web::saved_search_stats))
         // Admin routes
         .route("/admin", get(handlers::admin::admin_dashboard))
         .route("/admin/", get(handlers::admin::admin_dashboard))
+        .route(
+            "/admin/known-issues",
+            get(handlers::admin::known_issues_page).post(handlers::admin::create_known_issue),
+        )
+        .route(
+            "/admin/known-issues/new",
+            get(handlers::admin::new_known_issue_page),
+        )
+        .route(
+            "/admin/known-issues/:id",
+            get(handlers::admin::edit_known_issue_page).post(handlers::admin::update_known_issue),
+        )
+        .route(
+            "/admin/known-issues/:id/delete",
+            post(handlers::admin::delete_known_issue),
+        )
+        .route("/admin/submitters", get(handlers::admin::submitters_page))
+        .route(
+            "/admin/submitters/export",
+            get(handlers::admin::export_submitter),
+        )
+        .route(
+            "/admin/submitters/erase",
+            post(handlers::admin::erase_submitter),
+        )
+        .route("/admin/jobs", get(handlers::admin::jobs_page))
+        .route("/admin/jobs/:id/retry", post(handlers::admin::retry_job))
+        .route("/admin/jobs/:id/delete", post(handlers::admin::delete_job))
+        .route("/admin/alerts", get(handlers::admin::alerts_page))
+        .route("/admin/alerts/check", post(handlers::admin::check_alerts))
+        .route(
+            "/admin/alerts/:id/acknowledge",
+            post(handlers::admin::acknowledge_alert),
+        )
+        .route("/admin/groups", get(handlers::admin::groups_page))
+        .route("/admin/groups/:id", get(handlers::admin::group_page))
+        .route(
+            "/admin/groups/:id/merge",
+            post(handlers::admin::merge_group),
+        )
+        .route(
+            "/admin/groups/:id/split",
+            post(handlers::admin::split_group),
+        )
+        .route(
+            "/admin/groups/:id/rules",
+            post(handlers::admin::add_group_rule),
+        )
+        .route(
+            "/admin/groups/rules/:id/delete",
+            post(handlers::admin::delete_group_rule),
+        )
+        .route(
+            "/admin/moderation",
+            get(handlers::admin::moderation_page).post(handlers::admin::moderate_reports),
+        )
+        .route(
+            "/admin/bans",
+            get(handlers::admin::bans_page).post(handlers::admin::create_ban),
+        )
+        .route("/admin/bans/:id/delete", post(handlers::admin::delete_ban))
+        .route("/admin/searches", get(handlers::admin::saved_searches_page))
+        .route(
+            "/admin/searches/:id/delete",
+            post(handlers::admin::delete_saved_search),
+        )
+        .route("/admin/audit", get(handlers::admin::audit_page))
+        .route("/admin/audit/export", get(handlers::admin::export_audit))
         // Health check endpoint
         .route("/health", get(health_check))
-        // Static file serving
-        .nest_service("/static", ServeDir::new(&app_state.config.static_dir))
+        // Static files, also under content-hashed names
+        .route("/static/*path", get(assets::serve_asset))
         .layer(CorsLayer::permissive())
         .layer(TraceLayer::new_for_http())
         .with_state(app_state)
@@ -1,22 +1,158 @@
-use error_report_web_rs::{create_app, init_logging, AppState, Config};
-use sea_orm::Database;
-use std::sync::Arc;
+use clap::{Parser, Subcommand};
+use error_report_web_rs::{
+    create_app, init_logging,
+    services::{
+        alerts::AlertService,
+        jobs::JobService,
+        legacy_import::{ImportProgress, LegacyImportService, LegacySource},
+        notifications::Notifier,
+        retention::RetentionService,
+        stats::StatsService,
+        submitters::SubmitterService,
+    },
+    utils::privacy,
+    AppState, Config, Redactor, StatsCache,
+};
+use sea_orm::{Database, DatabaseConnection};
+use std::{net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
+
+#[derive(Parser)]
+#[command(
+    name = "error-report-web",
+    version,
+    about = "Yocto Project Error Reporting Web Application"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the web server (default)
+    Serve,
+    /// Export or erase the personal data tied to a submitter email
+    Submitter {
+        #[command(subcommand)]
+        action: SubmitterAction,
+    },
+    /// Apply the configured data retention rules once
+    Purge {
+        /// Report what would be removed without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Process background jobs without serving HTTP
+    Worker {
+        /// Number of concurrent workers (defaults to JOB_WORKERS, at least 1)
+        #[arg(long)]
+        workers: Option<usize>,
+    },
+    /// Import reports from the legacy Django error-report-web application,
+    /// keeping their IDs
+    Import {
+        /// JSON written by `manage.py dumpdata`
+        #[arg(
+            long,
+            required_unless_present = "database",
+            conflicts_with = "database"
+        )]
+        dumpdata: Option<PathBuf>,
+        /// PostgreSQL URL of a database the legacy SQL dump was loaded into
+        #[arg(long)]
+        database: Option<String>,
+        /// Prefix of the legacy tables in that database (the Django app label)
+        #[arg(long, default_value = "Post")]
+        table_prefix: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum SubmitterAction {
+    /// Print every report submitted with EMAIL as JSON
+    Export { email: String },
+    /// Remove EMAIL and the associated name from all reports
+    Erase { email: String },
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+
     // Initialize logging
     init_logging();
 
     // Load configuration
     dotenvy::dotenv().ok();
     let config = Arc::new(Config::from_env()?);
+    if let Some(key) = &config.email_hash_key {
+        privacy::init_email_hash_key(key);
+    }
 
     // Connect to database
     let db = Database::connect(&config.database_url).await?;
 
+    match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => serve(db, config).await,
+        Command::Submitter { action } => run_submitter_action(db, action).await,
+        Command::Purge { dry_run } => run_purge(db, config, dry_run).await,
+        Command::Worker { workers } => {
+            let workers = workers.unwrap_or(config.jobs.workers).max(1);
+            run_workers(db, config, workers).await
+        }
+        Command::Import {
+            dumpdata,
+            database,
+            table_prefix,
+        } => run_import(db, config, dumpdata, database, table_prefix).await,
+    }
+}
+
+async fn serve(
+    db: DatabaseConnection,
+    config: Arc<Config>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Compile redaction rules up front so invalid custom patterns fail at startup
+    let redactor = Arc::new(Redactor::new(&config.redaction)?);
+
+    let stats_cache = Arc::new(StatsCache::new(Duration::from_secs(
+        config.stats.cache_ttl_secs,
+    )));
+
+    // Periodically apply the data retention rules in the background
+    if config.retention.is_enabled() {
+        let retention_service = RetentionService::new(db.clone(), config.retention.clone());
+        tokio::spawn(retention_service.run_periodically(stats_cache.clone()));
+    }
+
+    // Post-processing workers; JOB_WORKERS=0 leaves this to `worker` processes
+    let job_service = JobService::new(db.clone(), config.jobs.clone())
+        .with_notifier(notifier(&config))
+        .with_email_visibility(config.submitter_email_visibility);
+    for worker_id in 0..config.jobs.workers {
+        tokio::spawn(job_service.clone().run_worker(worker_id));
+    }
+
+    // Watch submission volume for spikes
+    if config.alerts.is_enabled() {
+        let alert_service = AlertService::new(db.clone(), config.alerts.clone(), job_service);
+        tokio::spawn(alert_service.run_periodically());
+    }
+
+    // Keep the statistics rollups up to date when they are enabled
+    if let Some(refresh_secs) = config.stats.rollup_refresh_secs {
+        let stats_service = StatsService::new(db.clone()).with_rollups(true);
+        tokio::spawn(
+            stats_service
+                .run_rollup_refresh(Duration::from_secs(refresh_secs), stats_cache.clone()),
+        );
+    }
+
     let app_state = AppState {
         db,
         config: config.clone(),
+        redactor,
+        stats_cache,
     };
 
     // Build application
@@ -32,6 +168,152 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing::info!("📊 Dashboard available at http://{}/", bind_addr);
     tracing::info!("📋 API documentation at http://{}/api/", bind_addr);
 
-    axum::serve(listener, app).await?;
+    // The peer address is recorded with submissions, for banning
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
+    Ok(())
+}
+
+async fn run_submitter_action(
+    db: DatabaseConnection,
+    action: SubmitterAction,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let submitter_service = SubmitterService::new(db.clone());
+
+    match action {
+        SubmitterAction::Export { email } => {
+            let export = submitter_service.export(&email).await?;
+            println!("{}", serde_json::to_string_pretty(&export)?);
+        }
+        SubmitterAction::Erase { email } => {
+            let summary = submitter_service.erase(&db, &email).await?;
+            println!(
+                "Erased {}: {} reports anonymized, {} reports scrubbed, {} subscriptions \
+                 deleted, {} bans lifted, {} moderation actions and {} audit events scrubbed",
+                summary.email,
+                summary.reports_anonymized,
+                summary.reports_scrubbed,
+                summary.subscriptions_deleted,
+                summary.bans_lifted,
+                summary.moderation_actions_scrubbed,
+                summary.audit_events_scrubbed
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_workers(
+    db: DatabaseConnection,
+    config: Arc<Config>,
+    workers: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    tracing::info!("⚙️ Starting {} job workers", workers);
+
+    let job_service = JobService::new(db, config.jobs.clone())
+        .with_notifier(notifier(&config))
+        .with_email_visibility(config.submitter_email_visibility);
+    let mut tasks = tokio::task::JoinSet::new();
+    for worker_id in 0..workers {
+        tasks.spawn(job_service.clone().run_worker(worker_id));
+    }
+    while tasks.join_next().await.is_some() {}
+
+    Ok(())
+}
+
+async fn run_import(
+    db: DatabaseConnection,
+    config: Arc<Config>,
+    dumpdata: Option<PathBuf>,
+    database: Option<String>,
+    table_prefix: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let import_service = LegacyImportService::new(db, Redactor::new(&config.redaction)?);
+    let print_progress = |progress: ImportProgress| {
+        println!(
+            "Processed {} of {} failures",
+            progress.processed, progress.total
+        );
+    };
+
+    let summary = match (dumpdata, database) {
+        (Some(path), _) => {
+            import_service
+                .import(LegacySource::Dumpdata(&path), print_progress)
+                .await?
+        }
+        (None, Some(url)) => {
+            let legacy_db = Database::connect(&url).await?;
+            import_service
+                .import(
+                    LegacySource::Database {
+                        db: &legacy_db,
+                        table_prefix: &table_prefix,
+                    },
+                    print_progress,
+                )
+                .await?
+        }
+        (None, None) => return Err("either --dumpdata or --database is required".into()),
+    };
+
+    for skipped in &summary.skipped {
+        println!("Skipped failure {}: {}", skipped.failure_id, skipped.reason);
+    }
+    println!(
+        "Imported {} reports, {} already present, {} skipped",
+        summary.imported,
+        summary.already_present,
+        summary.skipped.len()
+    );
+
+    Ok(())
+}
+
+fn notifier(config: &Config) -> Notifier {
+    Notifier::new(
+        config.email.clone(),
+        config.alerts.recipients.clone(),
+        config.base_url.clone(),
+    )
+}
+
+async fn run_purge(
+    db: DatabaseConnection,
+    config: Arc<Config>,
+    dry_run: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !config.retention.is_enabled() {
+        println!("No retention rules configured; nothing to purge");
+        return Ok(());
+    }
+
+    let report = RetentionService::new(db, config.retention.clone())
+        .purge(dry_run)
+        .await?;
+
+    let verb = if dry_run { "Would" } else { "Did" };
+    if let Some(cutoff) = report.report_cutoff {
+        println!(
+            "{} delete {} reports created before {}",
+            verb,
+            report.reports_deleted,
+            cutoff.format("%Y-%m-%d %H:%M UTC")
+        );
+    }
+    if let Some(cutoff) = report.log_data_cutoff {
+        println!(
+            "{} clear log data of {} reports created before {}",
+            verb,
+            report.log_data_cleared,
+            cutoff.format("%Y-%m-%d %H:%M UTC")
+        );
+    }
+
     Ok(())
 }
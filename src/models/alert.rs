@@ -0,0 +1,28 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub const DIMENSION_FAILURE_GROUP: &str = "failure_group";
+pub const DIMENSION_RECIPE: &str = "recipe";
+pub const DIMENSION_MACHINE: &str = "machine";
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "alerts")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub dimension: String,
+    pub dimension_key: String,
+    pub label: String,
+    pub window_count: i64,
+    pub baseline_count: i64,
+    pub expected_count: f64,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    pub notified_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
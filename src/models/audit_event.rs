@@ -0,0 +1,250 @@
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    entity::prelude::*,
+    sea_query::{Expr, Func},
+    Condition,
+};
+use serde::{Deserialize, Serialize};
+
+use super::error_report::parse_date_bound;
+
+pub const ACTION_CREATE: &str = "create";
+pub const ACTION_UPDATE: &str = "update";
+pub const ACTION_DELETE: &str = "delete";
+pub const ACTION_HIDE: &str = "hide";
+pub const ACTION_UNHIDE: &str = "unhide";
+pub const ACTION_TAG: &str = "tag";
+pub const ACTION_UNTAG: &str = "untag";
+pub const ACTION_SUBSCRIBE: &str = "subscribe";
+pub const ACTION_UNSUBSCRIBE: &str = "unsubscribe";
+pub const ACTION_ERASE: &str = "erase";
+pub const ACTION_RETRY: &str = "retry";
+pub const ACTION_CHECK: &str = "check";
+pub const ACTION_ACKNOWLEDGE: &str = "acknowledge";
+pub const ACTION_MERGE: &str = "merge";
+pub const ACTION_SPLIT: &str = "split";
+
+pub const ACTIONS: [&str; 15] = [
+    ACTION_CREATE,
+    ACTION_UPDATE,
+    ACTION_DELETE,
+    ACTION_HIDE,
+    ACTION_UNHIDE,
+    ACTION_TAG,
+    ACTION_UNTAG,
+    ACTION_SUBSCRIBE,
+    ACTION_UNSUBSCRIBE,
+    ACTION_ERASE,
+    ACTION_RETRY,
+    ACTION_CHECK,
+    ACTION_ACKNOWLEDGE,
+    ACTION_MERGE,
+    ACTION_SPLIT,
+];
+
+pub const ENTITY_ALERT: &str = "alert";
+pub const ENTITY_COMMENT: &str = "comment";
+pub const ENTITY_ERROR_REPORT: &str = "error_report";
+pub const ENTITY_FAILURE_GROUP: &str = "failure_group";
+pub const ENTITY_FAILURE_GROUP_RULE: &str = "failure_group_rule";
+pub const ENTITY_JOB: &str = "job";
+pub const ENTITY_KNOWN_ISSUE: &str = "known_issue";
+pub const ENTITY_SAVED_SEARCH: &str = "saved_search";
+pub const ENTITY_SUBMISSION_BAN: &str = "submission_ban";
+pub const ENTITY_SUBMITTER: &str = "submitter";
+
+pub const ENTITY_TYPES: [&str; 10] = [
+    ENTITY_ALERT,
+    ENTITY_COMMENT,
+    ENTITY_ERROR_REPORT,
+    ENTITY_FAILURE_GROUP,
+    ENTITY_FAILURE_GROUP_RULE,
+    ENTITY_JOB,
+    ENTITY_KNOWN_ISSUE,
+    ENTITY_SAVED_SEARCH,
+    ENTITY_SUBMISSION_BAN,
+    ENTITY_SUBMITTER,
+];
+
+/// A change made through the web interface or API. Rows are never updated or
+/// deleted; the table rejects both.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "audit_events")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub created_at: DateTime<Utc>,
+    pub actor: Option<String>,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Option<String>,
+    pub before: Option<Json>,
+    pub after: Option<Json>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub method: String,
+    pub path: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// A field changed by an audited operation, for display
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl Model {
+    /// The recorded fields, with their values before and after the change
+    pub fn changes(&self) -> Vec<FieldChange> {
+        let empty = serde_json::Map::new();
+        let before = self
+            .before
+            .as_ref()
+            .and_then(Json::as_object)
+            .unwrap_or(&empty);
+        let after = self
+            .after
+            .as_ref()
+            .and_then(Json::as_object)
+            .unwrap_or(&empty);
+
+        let mut names: Vec<&String> = before.keys().chain(after.keys()).collect();
+        names.sort();
+        names.dedup();
+        names
+            .into_iter()
+            .map(|name| FieldChange {
+                field: name.clone(),
+                before: before.get(name).map(display_value),
+                after: after.get(name).map(display_value),
+            })
+            .collect()
+    }
+}
+
+fn display_value(value: &Json) -> String {
+    match value {
+        Json::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+// Query parameters for the audit log page and export
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditQuery {
+    pub page: Option<u64>,
+    pub actor: Option<String>,
+    pub action: Option<String>,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<String>,
+    pub date_from: Option<String>,
+    pub date_to: Option<String>,
+}
+
+impl AuditQuery {
+    /// Events matching every given filter; empty values and unparsable dates
+    /// are ignored. The actor is matched case-insensitively.
+    pub fn condition(&self) -> Condition {
+        let mut condition = Condition::all();
+        if let Some(actor) = non_empty(&self.actor) {
+            condition = condition
+                .add(Expr::expr(Func::lower(Expr::col(Column::Actor))).eq(actor.to_lowercase()));
+        }
+        if let Some(action) = non_empty(&self.action) {
+            condition = condition.add(Column::Action.eq(action));
+        }
+        if let Some(entity_type) = non_empty(&self.entity_type) {
+            condition = condition.add(Column::EntityType.eq(entity_type));
+        }
+        if let Some(entity_id) = non_empty(&self.entity_id) {
+            condition = condition.add(Column::EntityId.eq(entity_id));
+        }
+        if let Some(date_from) = non_empty(&self.date_from).and_then(|d| parse_date_bound(d, false))
+        {
+            condition = condition.add(Column::CreatedAt.gte(date_from));
+        }
+        if let Some(date_to) = non_empty(&self.date_to).and_then(|d| parse_date_bound(d, true)) {
+            condition = condition.add(Column::CreatedAt.lte(date_to));
+        }
+        condition
+    }
+
+    /// The filters as a URL query string, for pagination and export links
+    pub fn filter_query(&self) -> String {
+        let filters = [
+            ("actor", &self.actor),
+            ("action", &self.action),
+            ("entity_type", &self.entity_type),
+            ("entity_id", &self.entity_id),
+            ("date_from", &self.date_from),
+            ("date_to", &self.date_to),
+        ];
+        let pairs: Vec<(&str, &str)> = filters
+            .into_iter()
+            .filter_map(|(name, value)| non_empty(value).map(|value| (name, value)))
+            .collect();
+        serde_urlencoded::to_string(pairs).unwrap_or_default()
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_changes() {
+        let event = Model {
+            id: 1,
+            created_at: Utc::now(),
+            actor: None,
+            action: ACTION_UPDATE.to_string(),
+            entity_type: ENTITY_KNOWN_ISSUE.to_string(),
+            entity_id: Some("3".to_string()),
+            before: Some(json!({"title": "Old", "enabled": true})),
+            after: Some(json!({"title": "New", "link": "https://bugzilla.yoctoproject.org/1"})),
+            ip: None,
+            user_agent: None,
+            method: "POST".to_string(),
+            path: "/admin/known-issues/3".to_string(),
+        };
+        let change = |field: &str, before: Option<&str>, after: Option<&str>| FieldChange {
+            field: field.to_string(),
+            before: before.map(str::to_string),
+            after: after.map(str::to_string),
+        };
+        assert_eq!(
+            event.changes(),
+            vec![
+                change("enabled", Some("true"), None),
+                change("link", None, Some("https://bugzilla.yoctoproject.org/1")),
+                change("title", Some("Old"), Some("New")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_query() {
+        let query = AuditQuery {
+            page: Some(2),
+            actor: Some("Jane Doe".to_string()),
+            entity_type: Some(ENTITY_ERROR_REPORT.to_string()),
+            entity_id: Some(" ".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            query.filter_query(),
+            "actor=Jane+Doe&entity_type=error_report"
+        );
+    }
+}
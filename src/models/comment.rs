@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A markdown note on either an error report or a failure group
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "comments")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub error_report_id: Option<i32>,
+    pub failure_group_id: Option<i32>,
+    /// Comment this one replies to, on the same report or group
+    pub parent_id: Option<i32>,
+    pub author: Option<String>,
+    pub body: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::error_report::Entity",
+        from = "Column::ErrorReportId",
+        to = "super::error_report::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    ErrorReport,
+    #[sea_orm(
+        belongs_to = "super::failure_group::Entity",
+        from = "Column::FailureGroupId",
+        to = "super::failure_group::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    FailureGroup,
+}
+
+impl Related<super::error_report::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ErrorReport.def()
+    }
+}
+
+impl Related<super::failure_group::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::FailureGroup.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+// DTO for the comment forms and API
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CommentData {
+    pub body: String,
+    pub author: Option<String>,
+    pub parent_id: Option<i32>,
+}
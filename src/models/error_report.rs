@@ -1,7 +1,16 @@
-use chrono::{DateTime, Utc};
-use sea_orm::entity::prelude::*;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use sea_orm::{
+    entity::prelude::*,
+    sea_query::{Expr, Func, LikeExpr, SimpleExpr},
+    Condition, Order, QueryOrder, Select,
+};
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    config::EmailVisibility,
+    utils::privacy::{email_hash_key, EMAIL_HASH_LENGTH, EMAIL_HASH_PREFIX},
+};
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
 #[sea_orm(table_name = "error_reports")]
 pub struct Model {
@@ -23,12 +32,33 @@ pub struct Model {
     pub submitter_email: Option<String>,
     pub bugzilla_link: Option<String>,
     pub branch_commit: String,
+    pub failure_group_id: Option<i32>,
+    /// Set when a moderator hid the report from public pages
+    pub hidden_at: Option<DateTime<Utc>>,
+    pub hidden_reason: Option<String>,
+    /// Only shown to moderators, never in the JSON API
+    #[serde(skip_serializing, default)]
+    pub submitter_ip: Option<String>,
+    #[serde(skip_serializing, default)]
+    pub submitter_token: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::failure_group::Entity",
+        from = "Column::FailureGroupId",
+        to = "super::failure_group::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    FailureGroup,
     #[sea_orm(has_many = "super::build_configuration::Entity")]
     BuildConfiguration,
+    #[sea_orm(has_many = "super::error_report_known_issue::Entity")]
+    ErrorReportKnownIssue,
+    #[sea_orm(has_many = "super::report_redaction::Entity")]
+    ReportRedaction,
 }
 
 impl Related<super::build_configuration::Entity> for Entity {
@@ -37,6 +67,38 @@ impl Related<super::build_configuration::Entity> for Entity {
     }
 }
 
+impl Related<super::error_report_known_issue::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ErrorReportKnownIssue.def()
+    }
+}
+
+impl Related<super::failure_group::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::FailureGroup.def()
+    }
+}
+
+impl Related<super::report_redaction::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ReportRedaction.def()
+    }
+}
+
+impl Related<super::known_issue::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::error_report_known_issue::Relation::KnownIssue.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(
+            super::error_report_known_issue::Relation::ErrorReport
+                .def()
+                .rev(),
+        )
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
 
 // DTO for API requests
@@ -81,19 +143,96 @@ pub struct PaginationInfo {
     pub total_pages: u64,
 }
 
-// Query parameters for filtering
-#[derive(Debug, Deserialize)]
+/// Largest page of an error listing
+pub const MAX_PER_PAGE: u64 = 100;
+
+// Query parameters for filtering. Every text filter may be repeated to match
+// any of its values; a value is matched exactly, by prefix when it ends with `*`,
+// and excluded when it starts with `!`.
+#[derive(Debug, Clone, Deserialize)]
 pub struct ErrorQuery {
     pub page: Option<u64>,
     pub per_page: Option<u64>,
-    pub machine: Option<String>,
-    pub distro: Option<String>,
-    pub distro_version: Option<String>,
-    pub error_type: Option<String>,
-    pub failure_package: Option<String>,
+    #[serde(default)]
+    pub machine: Vec<String>,
+    #[serde(default)]
+    pub distro: Vec<String>,
+    #[serde(default)]
+    pub distro_version: Vec<String>,
+    #[serde(default)]
+    pub error_type: Vec<String>,
+    #[serde(default)]
+    pub failure_task: Vec<String>,
+    #[serde(default)]
+    pub failure_package: Vec<String>,
+    #[serde(default)]
+    pub nativelsbstring: Vec<String>,
+    #[serde(default)]
+    pub build_sys: Vec<String>,
+    #[serde(default)]
+    pub target_sys: Vec<String>,
+    #[serde(default)]
+    pub branch_commit: Vec<String>,
+    /// Name of a layer in the build configuration
+    #[serde(default)]
+    pub layer: Vec<String>,
+    /// Submitter name, or an exact (case-insensitive) email address where
+    /// `email_visibility` allows it
+    #[serde(default)]
+    pub submitter: Vec<String>,
+    /// Name of a tag attached by triagers
+    #[serde(default)]
+    pub tag: Vec<String>,
+    pub failure_group: Option<i32>,
+    /// Lowest report ID, inclusive
+    pub id_from: Option<i32>,
+    /// Highest report ID, inclusive
+    pub id_to: Option<i32>,
     pub date_from: Option<String>,
     pub date_to: Option<String>,
     pub search: Option<String>,
+    pub sort: Option<ErrorSort>,
+    /// Not a query parameter: only moderators list hidden reports
+    #[serde(skip)]
+    pub visibility: Visibility,
+    /// Not a query parameter: how submitter emails are shown to whoever sent the
+    /// query, which decides whether the `submitter` filter may match them. Without
+    /// it only names are matched, so hidden addresses can't be probed.
+    #[serde(skip)]
+    pub email_visibility: Option<EmailVisibility>,
+}
+
+/// Which reports a listing includes, depending on whether a moderator hid them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    #[default]
+    Visible,
+    Hidden,
+    All,
+}
+
+impl Visibility {
+    pub fn name(self) -> &'static str {
+        match self {
+            Visibility::Visible => "visible",
+            Visibility::Hidden => "hidden",
+            Visibility::All => "all",
+        }
+    }
+
+    fn condition(self) -> Option<SimpleExpr> {
+        match self {
+            Visibility::Visible => Some(visible()),
+            Visibility::Hidden => Some(Column::HiddenAt.is_not_null()),
+            Visibility::All => None,
+        }
+    }
+}
+
+/// Reports that have not been hidden by a moderator
+pub fn visible() -> SimpleExpr {
+    Column::HiddenAt.is_null()
 }
 
 impl Default for ErrorQuery {
@@ -101,14 +240,666 @@ impl Default for ErrorQuery {
         Self {
             page: Some(1),
             per_page: Some(50),
-            machine: None,
-            distro: None,
-            distro_version: None,
-            error_type: None,
-            failure_package: None,
+            machine: Vec::new(),
+            distro: Vec::new(),
+            distro_version: Vec::new(),
+            error_type: Vec::new(),
+            failure_task: Vec::new(),
+            failure_package: Vec::new(),
+            nativelsbstring: Vec::new(),
+            build_sys: Vec::new(),
+            target_sys: Vec::new(),
+            branch_commit: Vec::new(),
+            layer: Vec::new(),
+            submitter: Vec::new(),
+            tag: Vec::new(),
+            failure_group: None,
+            id_from: None,
+            id_to: None,
             date_from: None,
             date_to: None,
             search: None,
+            sort: None,
+            visibility: Visibility::default(),
+            email_visibility: None,
+        }
+    }
+}
+
+impl ErrorQuery {
+    /// Parse a URL query string as received by the error list, with or without
+    /// the leading `?`
+    pub fn from_query_string(query: &str) -> Result<Self, serde_html_form::de::Error> {
+        serde_html_form::from_str(query.trim().trim_start_matches('?'))
+    }
+
+    /// Condition for the reports listed: the filters, restricted to the
+    /// requested visibility
+    pub fn condition(&self) -> Condition {
+        let condition = self.filters();
+        match self.visibility.condition() {
+            Some(visibility) => condition.add(visibility),
+            None => condition,
+        }
+    }
+
+    /// Filter condition for the query parameters alone, shared by the error
+    /// listings and the statistics. Empty values and unparsable dates are ignored.
+    pub fn filters(&self) -> Condition {
+        let mut condition = Condition::all();
+
+        let column_filters = [
+            (Column::Machine, &self.machine),
+            (Column::Distro, &self.distro),
+            (Column::DistroVersion, &self.distro_version),
+            (Column::ErrorType, &self.error_type),
+            (Column::FailureTask, &self.failure_task),
+            (Column::FailurePackage, &self.failure_package),
+            (Column::Nativelsbstring, &self.nativelsbstring),
+            (Column::BuildSys, &self.build_sys),
+            (Column::TargetSys, &self.target_sys),
+            (Column::BranchCommit, &self.branch_commit),
+        ];
+        for (column, values) in column_filters {
+            if let Some(filter) =
+                values_condition(values, |value| value.matches(Expr::col((Entity, column))))
+            {
+                condition = condition.add(filter);
+            }
+        }
+
+        if let Some(filter) = values_condition(&self.layer, layer_match) {
+            condition = condition.add(filter);
+        }
+        if let Some(filter) = values_condition(&self.submitter, |value| {
+            submitter_match(value, self.email_visibility)
+        }) {
+            condition = condition.add(filter);
+        }
+        if let Some(filter) = values_condition(&self.tag, tag_match) {
+            condition = condition.add(filter);
+        }
+
+        if let Some(failure_group) = self.failure_group {
+            condition = condition.add(Column::FailureGroupId.eq(failure_group));
+        }
+        if let Some(id_from) = self.id_from {
+            condition = condition.add(Column::Id.gte(id_from));
+        }
+        if let Some(id_to) = self.id_to {
+            condition = condition.add(Column::Id.lte(id_to));
+        }
+
+        if let Some(search_term) = non_empty(&self.search) {
+            condition = condition.add(
+                Column::ErrorDetails
+                    .contains(search_term)
+                    .or(Column::FailurePackage.contains(search_term))
+                    .or(Column::FailureTask.contains(search_term)),
+            );
+        }
+
+        if let Some(date_from) = non_empty(&self.date_from).and_then(|d| parse_date_bound(d, false))
+        {
+            condition = condition.add(Column::CreatedAt.gte(date_from));
+        }
+        if let Some(date_to) = non_empty(&self.date_to).and_then(|d| parse_date_bound(d, true)) {
+            condition = condition.add(Column::CreatedAt.lte(date_to));
+        }
+
+        condition
+    }
+
+    /// Reports matching the filters in the requested order. Ties are broken by
+    /// ID so that pages don't overlap.
+    pub fn select(&self) -> Select<Entity> {
+        let sort = self.sort.unwrap_or_default();
+        let order = if sort.descending {
+            Order::Desc
+        } else {
+            Order::Asc
+        };
+        let mut select = Entity::find()
+            .filter(self.condition())
+            .order_by(sort.field.column(), order.clone());
+        if sort.field != SortField::Id {
+            select = select.order_by(Column::Id, order);
+        }
+        select
+    }
+
+    /// Requested page, counting from 1
+    pub fn page(&self) -> u64 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    /// Requested page size, `default` when not given
+    pub fn per_page(&self, default: u64) -> u64 {
+        self.per_page.unwrap_or(default).clamp(1, MAX_PER_PAGE)
+    }
+
+    /// Non-empty filters as `(parameter, value)` pairs, one pair per value of
+    /// multi-valued filters
+    pub fn filter_pairs(&self) -> Vec<(&'static str, String)> {
+        let multi_filters = [
+            ("machine", &self.machine),
+            ("distro", &self.distro),
+            ("distro_version", &self.distro_version),
+            ("error_type", &self.error_type),
+            ("failure_task", &self.failure_task),
+            ("failure_package", &self.failure_package),
+            ("nativelsbstring", &self.nativelsbstring),
+            ("build_sys", &self.build_sys),
+            ("target_sys", &self.target_sys),
+            ("branch_commit", &self.branch_commit),
+            ("layer", &self.layer),
+            ("submitter", &self.submitter),
+            ("tag", &self.tag),
+        ];
+        let mut pairs: Vec<(&'static str, String)> = multi_filters
+            .into_iter()
+            .flat_map(|(name, values)| {
+                values
+                    .iter()
+                    .map(|value| value.trim())
+                    .filter(|value| !value.is_empty())
+                    .map(move |value| (name, value.to_string()))
+            })
+            .collect();
+
+        let ids = [
+            ("failure_group", self.failure_group),
+            ("id_from", self.id_from),
+            ("id_to", self.id_to),
+        ];
+        pairs.extend(
+            ids.into_iter()
+                .filter_map(|(name, id)| id.map(|id| (name, id.to_string()))),
+        );
+
+        let filters = [
+            ("date_from", &self.date_from),
+            ("date_to", &self.date_to),
+            ("search", &self.search),
+        ];
+        pairs.extend(
+            filters.into_iter().filter_map(|(name, value)| {
+                non_empty(value).map(|value| (name, value.to_string()))
+            }),
+        );
+        pairs
+    }
+
+    /// Replace the values of a multi-valued filter with `values`, keeping the
+    /// excluded ones, so that a link drills down into `values` without dropping
+    /// the other filters. Unknown filter names are ignored.
+    pub fn narrow(&mut self, name: &str, values: Vec<String>) {
+        let filter = match name {
+            "machine" => &mut self.machine,
+            "distro" => &mut self.distro,
+            "distro_version" => &mut self.distro_version,
+            "error_type" => &mut self.error_type,
+            "failure_task" => &mut self.failure_task,
+            "failure_package" => &mut self.failure_package,
+            "nativelsbstring" => &mut self.nativelsbstring,
+            "build_sys" => &mut self.build_sys,
+            "target_sys" => &mut self.target_sys,
+            "branch_commit" => &mut self.branch_commit,
+            "layer" => &mut self.layer,
+            "submitter" => &mut self.submitter,
+            "tag" => &mut self.tag,
+            _ => return,
+        };
+        filter.retain(|value| value.trim().starts_with('!'));
+        filter.extend(values);
+    }
+
+    /// The filters as a URL query string, for links that keep the current filters
+    pub fn filter_query(&self) -> String {
+        serde_urlencoded::to_string(self.filter_pairs()).unwrap_or_default()
+    }
+
+    /// The filters, sort order and page size as a URL query string, for
+    /// pagination links
+    pub fn listing_query(&self) -> String {
+        let mut pairs = self.filter_pairs();
+        if let Some(sort) = self.sort {
+            pairs.push(("sort", sort.to_string()));
+        }
+        if let Some(per_page) = self.per_page {
+            pairs.push(("per_page", per_page.to_string()));
+        }
+        serde_urlencoded::to_string(pairs).unwrap_or_default()
+    }
+}
+
+/// Fields an error listing can be sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    Id,
+    CreatedAt,
+    Machine,
+    Distro,
+    DistroVersion,
+    ErrorType,
+    FailureTask,
+    FailurePackage,
+}
+
+impl SortField {
+    pub const ALL: [SortField; 8] = [
+        SortField::Id,
+        SortField::CreatedAt,
+        SortField::Machine,
+        SortField::Distro,
+        SortField::DistroVersion,
+        SortField::ErrorType,
+        SortField::FailureTask,
+        SortField::FailurePackage,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SortField::Id => "id",
+            SortField::CreatedAt => "created_at",
+            SortField::Machine => "machine",
+            SortField::Distro => "distro",
+            SortField::DistroVersion => "distro_version",
+            SortField::ErrorType => "error_type",
+            SortField::FailureTask => "failure_task",
+            SortField::FailurePackage => "failure_package",
+        }
+    }
+
+    fn column(self) -> Column {
+        match self {
+            SortField::Id => Column::Id,
+            SortField::CreatedAt => Column::CreatedAt,
+            SortField::Machine => Column::Machine,
+            SortField::Distro => Column::Distro,
+            SortField::DistroVersion => Column::DistroVersion,
+            SortField::ErrorType => Column::ErrorType,
+            SortField::FailureTask => Column::FailureTask,
+            SortField::FailurePackage => Column::FailurePackage,
+        }
+    }
+}
+
+/// Order of an error listing: a field name, descending when prefixed with `-`.
+/// Newest reports come first unless asked otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ErrorSort {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+impl Default for ErrorSort {
+    fn default() -> Self {
+        Self {
+            field: SortField::CreatedAt,
+            descending: true,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown sort field: {0}")]
+pub struct UnknownSortField(String);
+
+impl TryFrom<String> for ErrorSort {
+    type Error = UnknownSortField;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let trimmed = value.trim();
+        let (descending, name) = match trimmed.strip_prefix('-') {
+            Some(name) => (true, name),
+            None => (false, trimmed),
+        };
+        SortField::ALL
+            .into_iter()
+            .find(|field| field.name() == name)
+            .map(|field| Self { field, descending })
+            .ok_or(UnknownSortField(value))
+    }
+}
+
+impl std::fmt::Display for ErrorSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.descending {
+            f.write_str("-")?;
+        }
+        f.write_str(self.field.name())
+    }
+}
+
+/// One value of a text filter
+#[derive(Debug, PartialEq, Eq)]
+struct FilterValue<'a> {
+    text: &'a str,
+    prefix: bool,
+    negated: bool,
+}
+
+impl<'a> FilterValue<'a> {
+    /// `value`, `prefix*`, or either of them negated with a leading `!`.
+    /// Values without any text are ignored.
+    fn parse(value: &'a str) -> Option<Self> {
+        let value = value.trim();
+        let (negated, value) = match value.strip_prefix('!') {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, value),
+        };
+        let (prefix, text) = match value.strip_suffix('*') {
+            Some(rest) => (true, rest),
+            None => (false, value),
+        };
+        (!text.is_empty()).then_some(Self {
+            text,
+            prefix,
+            negated,
+        })
+    }
+
+    /// Whether `target` equals or starts with the value, ignoring negation
+    fn matches(&self, target: Expr) -> SimpleExpr {
+        if self.prefix {
+            target.like(LikeExpr::new(format!("{}%", escape_like(self.text))).escape('\\'))
+        } else {
+            target.eq(self.text)
+        }
+    }
+}
+
+/// Reports matching any of the plain values and none of the negated ones
+fn values_condition(
+    values: &[String],
+    matches: impl Fn(&FilterValue) -> SimpleExpr,
+) -> Option<Condition> {
+    let mut included = Condition::any();
+    let mut condition = Condition::all();
+    for value in values.iter().filter_map(|value| FilterValue::parse(value)) {
+        if value.negated {
+            condition = condition.add(Condition::all().add(matches(&value)).not());
+        } else {
+            included = included.add(matches(&value));
+        }
+    }
+    if !included.is_empty() {
+        condition = condition.add(included);
+    }
+    (!condition.is_empty()).then_some(condition)
+}
+
+/// Escape the wildcards of a `LIKE` pattern, using the default `\` escape character
+pub(crate) fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Reports whose build configuration lists the layer. `meta_layers` is stored
+/// as a JSON array of `LayerInfo`.
+fn layer_match(value: &FilterValue) -> SimpleExpr {
+    let layers = "SELECT error_report_id FROM build_configurations \
+                  CROSS JOIN jsonb_array_elements(meta_layers::jsonb) AS layer";
+    if value.prefix {
+        Expr::cust_with_values(
+            format!(
+                "error_reports.id IN ({} WHERE layer->>'name' LIKE $1)",
+                layers
+            ),
+            [format!("{}%", escape_like(value.text))],
+        )
+    } else {
+        Expr::cust_with_values(
+            format!("error_reports.id IN ({} WHERE layer->>'name' = $1)", layers),
+            [value.text],
+        )
+    }
+}
+
+/// Reports carrying the tag. Tag names are stored in lower case.
+fn tag_match(value: &FilterValue) -> SimpleExpr {
+    let tagged = "SELECT error_report_tags.error_report_id FROM error_report_tags \
+                  JOIN tags ON tags.id = error_report_tags.tag_id";
+    let text = value.text.to_lowercase();
+    if value.prefix {
+        Expr::cust_with_values(
+            format!("error_reports.id IN ({} WHERE tags.name LIKE $1)", tagged),
+            [format!("{}%", escape_like(&text))],
+        )
+    } else {
+        Expr::cust_with_values(
+            format!("error_reports.id IN ({} WHERE tags.name = $1)", tagged),
+            [text],
+        )
+    }
+}
+
+/// Reports by the named submitter, or by the email address when the value is one
+/// and the addresses are shown; with hashed addresses, by the hash instead.
+/// Anonymous reports have an empty name and email here, so negations keep them.
+fn submitter_match(value: &FilterValue, email_visibility: Option<EmailVisibility>) -> SimpleExpr {
+    match email_visibility {
+        Some(EmailVisibility::Show) if value.text.contains('@') => {
+            Expr::expr(Func::lower(Func::coalesce([
+                Expr::col(Column::SubmitterEmail).into(),
+                Expr::val("").into(),
+            ])))
+            .eq(value.text.to_lowercase())
+        }
+        // Same HMAC as `utils::privacy::hash_email`, built from the padded keys
+        Some(EmailVisibility::Hash) if value.text.starts_with(EMAIL_HASH_PREFIX) => {
+            let key = email_hash_key();
+            Expr::cust_with_values(
+                format!(
+                    "$1 || left(encode(sha256($2 || sha256($3 || convert_to(\
+                     lower(btrim(COALESCE(error_reports.submitter_email, ''))), 'UTF8'))), \
+                     'hex'), {}) = $4",
+                    EMAIL_HASH_LENGTH
+                ),
+                [
+                    Value::from(EMAIL_HASH_PREFIX),
+                    Value::from(key.outer_pad().to_vec()),
+                    Value::from(key.inner_pad().to_vec()),
+                    Value::from(value.text.to_lowercase()),
+                ],
+            )
+        }
+        _ => value.matches(Expr::expr(Func::coalesce([
+            Expr::col(Column::SubmitterName).into(),
+            Expr::val("").into(),
+        ]))),
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// Accept RFC 3339 timestamps or plain `YYYY-MM-DD` dates (as sent by HTML date
+/// inputs). A plain end date covers the whole day.
+pub(crate) fn parse_date_bound(value: &str, end_of_day: bool) -> Option<DateTime<Utc>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+    let time = if end_of_day {
+        NaiveTime::from_hms_opt(23, 59, 59)?
+    } else {
+        NaiveTime::MIN
+    };
+    Some(date.and_time(time).and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date_bound() {
+        assert_eq!(
+            parse_date_bound("2024-05-01T12:00:00+02:00", false)
+                .unwrap()
+                .to_rfc3339(),
+            "2024-05-01T10:00:00+00:00"
+        );
+        assert_eq!(
+            parse_date_bound("2024-05-01", true).unwrap().to_rfc3339(),
+            "2024-05-01T23:59:59+00:00"
+        );
+        assert!(parse_date_bound("yesterday", false).is_none());
+    }
+
+    #[test]
+    fn test_empty_filters_are_ignored() {
+        let query = ErrorQuery {
+            machine: vec![String::new()],
+            search: Some("  ".to_string()),
+            ..Default::default()
+        };
+        assert!(query.filters().is_empty());
+        assert_eq!(query.filter_query(), "");
+    }
+
+    #[test]
+    fn test_filter_query() {
+        let query = ErrorQuery {
+            machine: vec!["qemux86-64".to_string(), "qemuarm".to_string()],
+            search: Some("undefined reference".to_string()),
+            failure_group: Some(3),
+            page: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            query.filter_query(),
+            "machine=qemux86-64&machine=qemuarm&failure_group=3&search=undefined+reference"
+        );
+    }
+
+    #[test]
+    fn test_from_query_string() {
+        let query =
+            ErrorQuery::from_query_string("?machine=qemuarm&machine=qemux86&sort=machine&page=3")
+                .unwrap();
+        assert_eq!(query.machine, vec!["qemuarm", "qemux86"]);
+        assert_eq!(query.page, Some(3));
+        assert_eq!(
+            query.listing_query(),
+            "machine=qemuarm&machine=qemux86&sort=machine"
+        );
+        assert!(ErrorQuery::from_query_string("sort=colour").is_err());
+    }
+
+    #[test]
+    fn test_filter_value_syntax() {
+        let parsed = |value| {
+            FilterValue::parse(value).map(|value| (value.text, value.prefix, value.negated))
+        };
+        assert_eq!(parsed("qemuarm"), Some(("qemuarm", false, false)));
+        assert_eq!(parsed("qemu*"), Some(("qemu", true, false)));
+        assert_eq!(parsed(" !qemu*"), Some(("qemu", true, true)));
+        assert_eq!(parsed("!"), None);
+        assert_eq!(parsed("*"), None);
+    }
+
+    #[test]
+    fn test_condition_sql() {
+        use sea_orm::{DbBackend, QueryTrait};
+
+        let query = ErrorQuery {
+            machine: vec![
+                "qemux86-64".to_string(),
+                "qemuarm*".to_string(),
+                "!qemuarm64".to_string(),
+            ],
+            layer: vec!["meta-oe".to_string(), " ".to_string()],
+            submitter: vec!["!Jo_e*".to_string()],
+            tag: vec!["!Flaky".to_string()],
+            id_from: Some(100),
+            ..Default::default()
+        };
+        let sql = query.select().build(DbBackend::Postgres).to_string();
+        assert!(sql.contains(r#"(NOT "error_reports"."machine" = 'qemuarm64')"#));
+        assert!(sql.contains(r#"("error_reports"."machine" = 'qemux86-64' OR "error_reports"."machine" LIKE 'qemuarm%'"#));
+        assert!(sql.contains("WHERE layer->>'name' = 'meta-oe')"));
+        assert!(sql.contains(r#"NOT COALESCE("submitter_name", '') LIKE E'Jo\\_e%'"#));
+        assert!(sql.contains("(NOT (error_reports.id IN (SELECT error_report_tags.error_report_id"));
+        assert!(sql.contains("WHERE tags.name = 'flaky')"));
+        assert!(sql.contains(r#""error_reports"."id" >= 100"#));
+        assert!(sql.contains(r#""error_reports"."hidden_at" IS NULL"#));
+        assert!(sql
+            .ends_with(r#"ORDER BY "error_reports"."created_at" DESC, "error_reports"."id" DESC"#));
+    }
+
+    #[test]
+    fn test_submitter_email_follows_email_visibility() {
+        use sea_orm::{DbBackend, QueryTrait};
+
+        let sql = |email_visibility, submitter: &str| {
+            ErrorQuery {
+                submitter: vec![submitter.to_string()],
+                email_visibility,
+                ..Default::default()
+            }
+            .select()
+            .build(DbBackend::Postgres)
+            .to_string()
+            .split_once(" WHERE ")
+            .unwrap()
+            .1
+            .to_string()
+        };
+
+        let shown = sql(Some(EmailVisibility::Show), "Jane@Example.com");
+        assert!(shown.contains(r#"LOWER(COALESCE("submitter_email", '')) = 'jane@example.com'"#));
+
+        // Hidden or unknown visibility: the address is only compared with names
+        for email_visibility in [
+            Some(EmailVisibility::Hide),
+            Some(EmailVisibility::Hash),
+            None,
+        ] {
+            let hidden = sql(email_visibility, "jane@example.com");
+            assert!(!hidden.contains("submitter_email"));
+            assert!(hidden.contains(r#"COALESCE("submitter_name", '') = 'jane@example.com'"#));
         }
+
+        let hash = "hmac:0123456789abcdef0123456789abcdef";
+        let hashed = sql(Some(EmailVisibility::Hash), hash);
+        assert!(hashed.contains("encode(sha256("));
+        assert!(hashed.contains("'hex'), 32) = 'hmac:0123456789abcdef0123456789abcdef'"));
+        assert!(!sql(Some(EmailVisibility::Show), hash).contains("submitter_email"));
+    }
+
+    #[test]
+    fn test_visibility() {
+        use sea_orm::{DbBackend, QueryTrait};
+
+        let sql = |visibility| {
+            ErrorQuery {
+                visibility,
+                ..Default::default()
+            }
+            .select()
+            .build(DbBackend::Postgres)
+            .to_string()
+        };
+        assert!(sql(Visibility::Hidden).contains(r#""error_reports"."hidden_at" IS NOT NULL"#));
+        assert!(!sql(Visibility::All).contains(r#""hidden_at" IS"#));
+        // Moderators can't ask for hidden reports through the query string
+        let query = ErrorQuery::from_query_string("visibility=all").unwrap();
+        assert_eq!(query.visibility, Visibility::Visible);
+    }
+
+    #[test]
+    fn test_sort() {
+        let sort = ErrorSort::try_from("-failure_package".to_string()).unwrap();
+        assert_eq!(sort.field, SortField::FailurePackage);
+        assert!(sort.descending);
+        assert_eq!(sort.to_string(), "-failure_package");
+        assert!(ErrorSort::try_from("log_data".to_string()).is_err());
     }
 }
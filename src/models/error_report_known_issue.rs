@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "error_report_known_issues")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub error_report_id: i32,
+    pub known_issue_id: i32,
+    pub matched_at: DateTime<Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::error_report::Entity",
+        from = "Column::ErrorReportId",
+        to = "super::error_report::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    ErrorReport,
+    #[sea_orm(
+        belongs_to = "super::known_issue::Entity",
+        from = "Column::KnownIssueId",
+        to = "super::known_issue::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    KnownIssue,
+}
+
+impl Related<super::error_report::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ErrorReport.def()
+    }
+}
+
+impl Related<super::known_issue::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::KnownIssue.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
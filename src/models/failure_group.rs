@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "failure_groups")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    #[sea_orm(unique)]
+    pub fingerprint: String,
+    pub recipe: String,
+    pub failure_task: String,
+    pub error_type: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::error_report::Entity")]
+    ErrorReport,
+}
+
+impl Related<super::error_report::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ErrorReport.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+// DTO for merging a group into another from the admin UI
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MergeGroupData {
+    pub target_id: i32,
+    pub actor: Option<String>,
+    pub note: Option<String>,
+}
+
+// DTO for moving reports out into a new group from the admin UI
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SplitGroupData {
+    #[serde(default)]
+    pub report_id: Vec<i32>,
+    pub actor: Option<String>,
+    pub note: Option<String>,
+}
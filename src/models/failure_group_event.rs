@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub const ACTION_MERGE: &str = "merge";
+pub const ACTION_SPLIT: &str = "split";
+pub const ACTION_ADD_RULE: &str = "add_rule";
+pub const ACTION_DELETE_RULE: &str = "delete_rule";
+
+/// A change made to failure groups by a triager. Group IDs are kept as plain
+/// values so that the history survives merged-away groups.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "failure_group_events")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub action: String,
+    pub failure_group_id: i32,
+    pub other_group_id: Option<i32>,
+    pub report_count: i32,
+    pub actor: Option<String>,
+    pub details: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
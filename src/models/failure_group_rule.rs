@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Sends future reports with `fingerprint` to `failure_group_id`
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "failure_group_rules")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    #[sea_orm(unique)]
+    pub fingerprint: String,
+    pub failure_group_id: i32,
+    pub recipe: String,
+    pub failure_task: String,
+    pub error_type: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::failure_group::Entity",
+        from = "Column::FailureGroupId",
+        to = "super::failure_group::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    FailureGroup,
+}
+
+impl Related<super::failure_group::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::FailureGroup.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+// DTO for the admin rule form
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FailureGroupRuleData {
+    pub recipe: String,
+    pub failure_task: String,
+    pub error_type: String,
+    pub actor: Option<String>,
+}
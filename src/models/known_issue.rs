@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "known_issues")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub title: String,
+    pub explanation: String,
+    pub link: Option<String>,
+    pub error_details_pattern: Option<String>,
+    pub log_data_pattern: Option<String>,
+    pub failure_task_pattern: Option<String>,
+    pub enabled: bool,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::error_report_known_issue::Entity")]
+    ErrorReportKnownIssue,
+}
+
+impl Related<super::error_report_known_issue::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ErrorReportKnownIssue.def()
+    }
+}
+
+impl Related<super::error_report::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::error_report_known_issue::Relation::ErrorReport.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(
+            super::error_report_known_issue::Relation::KnownIssue
+                .def()
+                .rev(),
+        )
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+// DTO for the admin known issue form
+#[derive(Debug, Deserialize, Serialize)]
+pub struct KnownIssueData {
+    pub title: String,
+    pub explanation: String,
+    pub link: Option<String>,
+    pub error_details_pattern: Option<String>,
+    pub log_data_pattern: Option<String>,
+    pub failure_task_pattern: Option<String>,
+    pub enabled: Option<String>,
+    /// Name of whoever made the change, for the audit log
+    pub actor: Option<String>,
+}
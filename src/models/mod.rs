@@ -1,5 +1,39 @@
+pub mod alert;
+pub mod audit_event;
 pub mod build_configuration;
+pub mod comment;
 pub mod error_report;
+pub mod error_report_known_issue;
+pub mod error_report_tag;
+pub mod failure_group;
+pub mod failure_group_event;
+pub mod failure_group_rule;
+pub mod job;
+pub mod known_issue;
+pub mod moderation_action;
+pub mod report_redaction;
+pub mod saved_search;
+pub mod saved_search_subscription;
+pub mod stats_rollup;
+pub mod submission_ban;
+pub mod tag;
 
+pub use alert::Entity as Alert;
+pub use audit_event::Entity as AuditEvent;
 pub use build_configuration::Entity as BuildConfiguration;
+pub use comment::Entity as Comment;
 pub use error_report::Entity as ErrorReport;
+pub use error_report_known_issue::Entity as ErrorReportKnownIssue;
+pub use error_report_tag::Entity as ErrorReportTag;
+pub use failure_group::Entity as FailureGroup;
+pub use failure_group_event::Entity as FailureGroupEvent;
+pub use failure_group_rule::Entity as FailureGroupRule;
+pub use job::Entity as Job;
+pub use known_issue::Entity as KnownIssue;
+pub use moderation_action::Entity as ModerationAction;
+pub use report_redaction::Entity as ReportRedaction;
+pub use saved_search::Entity as SavedSearch;
+pub use saved_search_subscription::Entity as SavedSearchSubscription;
+pub use stats_rollup::Entity as StatsRollup;
+pub use submission_ban::Entity as SubmissionBan;
+pub use tag::Entity as Tag;
@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::error_report::Visibility;
+
+pub const ACTION_HIDE: &str = "hide";
+pub const ACTION_UNHIDE: &str = "unhide";
+pub const ACTION_DELETE: &str = "delete";
+pub const ACTION_BAN: &str = "ban";
+pub const ACTION_UNBAN: &str = "unban";
+
+/// A moderation action. Report and ban IDs are kept as plain values so that
+/// the log survives deleted reports and lifted bans.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "moderation_actions")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub action: String,
+    pub error_report_id: Option<i32>,
+    pub submission_ban_id: Option<i32>,
+    pub actor: Option<String>,
+    pub details: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+// DTO for the bulk moderation form
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ModerationData {
+    pub action: String,
+    #[serde(default)]
+    pub report_id: Vec<i32>,
+    /// Act on every report matching `query` and `status` instead of `report_id`
+    pub all_matching: Option<String>,
+    #[serde(default)]
+    pub query: String,
+    pub status: Option<Visibility>,
+    pub reason: Option<String>,
+    pub actor: Option<String>,
+}
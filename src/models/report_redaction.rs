@@ -0,0 +1,33 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "report_redactions")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub error_report_id: i32,
+    pub rule_name: String,
+    pub field: String,
+    pub occurrences: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::error_report::Entity",
+        from = "Column::ErrorReportId",
+        to = "super::error_report::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    ErrorReport,
+}
+
+impl Related<super::error_report::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ErrorReport.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "saved_searches")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub slug: String,
+    pub name: String,
+    /// Query string of the error list, as produced by `ErrorQuery::listing_query`
+    pub query: String,
+    pub owner: Option<String>,
+    pub shared: bool,
+    pub pinned: bool,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::saved_search_subscription::Entity")]
+    SavedSearchSubscription,
+}
+
+impl Related<super::saved_search_subscription::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SavedSearchSubscription.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// Whether `name` is the owner the search was saved with. Searches without
+    /// an owner belong to no one.
+    pub fn is_owned_by(&self, name: Option<&str>) -> bool {
+        match (&self.owner, name.map(str::trim)) {
+            (Some(owner), Some(name)) => owner == name,
+            _ => false,
+        }
+    }
+}
+
+// DTO for the save search form
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SavedSearchData {
+    pub name: String,
+    pub query: String,
+    pub owner: Option<String>,
+    pub shared: Option<String>,
+    pub pinned: Option<String>,
+}
@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "saved_search_subscriptions")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub saved_search_id: i32,
+    pub email: String,
+    /// Secret in the confirmation and unsubscribe links
+    pub token: String,
+    pub created_at: DateTime<Utc>,
+    pub confirmation_sent_at: DateTime<Utc>,
+    /// Only confirmed subscriptions are notified
+    pub confirmed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::saved_search::Entity",
+        from = "Column::SavedSearchId",
+        to = "super::saved_search::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    SavedSearch,
+}
+
+impl Related<super::saved_search::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SavedSearch.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+// DTO for the subscribe form
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SubscriptionData {
+    pub email: String,
+}
@@ -0,0 +1,25 @@
+use chrono::NaiveDate;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Row of the `stats_daily_rollups` materialized view (read-only)
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "stats_daily_rollups")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub day: NaiveDate,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub machine: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub distro: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub error_type: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub failure_package: String,
+    pub count: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub const KIND_IP: &str = "ip";
+pub const KIND_EMAIL: &str = "email";
+pub const KIND_TOKEN: &str = "token";
+
+/// Every kind of ban, in the order offered on the admin page
+pub const KINDS: [&str; 3] = [KIND_IP, KIND_EMAIL, KIND_TOKEN];
+
+/// A submitter whose new reports are rejected
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "submission_bans")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub kind: String,
+    pub value: String,
+    pub reason: String,
+    pub actor: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Normalize a banned value so that it compares equal to what submissions send
+pub fn normalize_value(kind: &str, value: &str) -> String {
+    let value = value.trim();
+    if kind == KIND_EMAIL {
+        value.to_lowercase()
+    } else {
+        value.to_string()
+    }
+}
+
+// DTO for the admin ban form
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BanData {
+    pub kind: String,
+    pub value: String,
+    pub reason: Option<String>,
+    pub actor: Option<String>,
+}
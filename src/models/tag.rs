@@ -0,0 +1,43 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "tags")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::error_report_tag::Entity")]
+    ErrorReportTag,
+}
+
+impl Related<super::error_report_tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ErrorReportTag.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+// DTO for the add tag form and API
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TagData {
+    pub name: String,
+    pub actor: Option<String>,
+}
+
+/// Tags are matched case-insensitively and stored in lower case, with runs of
+/// whitespace turned into dashes
+pub fn normalize_name(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+}
@@ -0,0 +1,285 @@
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr,
+    EntityTrait, FromQueryResult, QueryFilter, QueryOrder, QuerySelect, Set, Statement,
+};
+use std::collections::{HashMap, HashSet};
+use tracing::{error, info};
+
+use crate::{
+    config::AlertsConfig,
+    models::{alert, failure_group, Alert, FailureGroup},
+    services::jobs::{JobPayload, JobService},
+};
+
+/// Number of alerts listed on the admin page
+const RECENT_ALERTS: u64 = 100;
+
+/// Volume per key in the alert window (`created_at >= $2`) and in the baseline
+/// period before it (`$1 <= created_at < $2`). `{key}` is replaced by a fixed expression.
+const VOLUME_SQL: &str = r#"
+SELECT
+    {key} AS dimension_key,
+    COUNT(*) FILTER (WHERE er.created_at >= $2) AS window_count,
+    COUNT(*) FILTER (WHERE er.created_at < $2) AS baseline_count
+FROM error_reports er
+LEFT JOIN failure_groups fg ON fg.id = er.failure_group_id
+WHERE er.created_at >= $1 AND er.hidden_at IS NULL AND {key} IS NOT NULL
+GROUP BY 1
+HAVING COUNT(*) FILTER (WHERE er.created_at >= $2) >= $3
+"#;
+
+/// What submission volume is tracked per
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertDimension {
+    FailureGroup,
+    Recipe,
+    Machine,
+}
+
+impl AlertDimension {
+    pub const ALL: [AlertDimension; 3] = [
+        AlertDimension::FailureGroup,
+        AlertDimension::Recipe,
+        AlertDimension::Machine,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AlertDimension::FailureGroup => alert::DIMENSION_FAILURE_GROUP,
+            AlertDimension::Recipe => alert::DIMENSION_RECIPE,
+            AlertDimension::Machine => alert::DIMENSION_MACHINE,
+        }
+    }
+
+    fn key_expr(self) -> &'static str {
+        match self {
+            AlertDimension::FailureGroup => "er.failure_group_id::text",
+            AlertDimension::Recipe => "fg.recipe",
+            AlertDimension::Machine => "er.machine",
+        }
+    }
+}
+
+#[derive(Debug, FromQueryResult)]
+struct VolumeRow {
+    dimension_key: String,
+    window_count: i64,
+    baseline_count: i64,
+}
+
+pub struct AlertService {
+    db: DatabaseConnection,
+    config: AlertsConfig,
+    jobs: JobService,
+}
+
+impl AlertService {
+    pub fn new(db: DatabaseConnection, config: AlertsConfig, jobs: JobService) -> Self {
+        Self { db, config, jobs }
+    }
+
+    /// Compare the recent volume of every dimension against its baseline and
+    /// record an alert for each spike not already alerted on in this window
+    pub async fn check<C: ConnectionTrait>(&self, db: &C) -> Result<Vec<alert::Model>, DbErr> {
+        let now = Utc::now();
+        let window_start = now - Duration::hours(self.config.window_hours.into());
+        let baseline_start = window_start - Duration::days(self.config.baseline_days.into());
+
+        let already_alerted: HashSet<(String, String)> = Alert::find()
+            .filter(alert::Column::CreatedAt.gte(window_start))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|alert| (alert.dimension, alert.dimension_key))
+            .collect();
+
+        let mut raised = Vec::new();
+        for dimension in AlertDimension::ALL {
+            let spikes: Vec<VolumeRow> = self
+                .volumes(dimension, baseline_start, window_start)
+                .await?
+                .into_iter()
+                .filter(|row| {
+                    let expected = self.expected_count(row.baseline_count);
+                    is_spike(
+                        row.window_count,
+                        expected,
+                        self.config.threshold,
+                        self.config.min_count,
+                    )
+                })
+                .filter(|row| {
+                    !already_alerted
+                        .contains(&(dimension.as_str().to_string(), row.dimension_key.clone()))
+                })
+                .collect();
+
+            let labels = self.labels(dimension, &spikes).await?;
+            for row in spikes {
+                let alert = alert::ActiveModel {
+                    created_at: Set(now),
+                    dimension: Set(dimension.as_str().to_string()),
+                    label: Set(labels
+                        .get(&row.dimension_key)
+                        .cloned()
+                        .unwrap_or_else(|| row.dimension_key.clone())),
+                    dimension_key: Set(row.dimension_key),
+                    window_count: Set(row.window_count),
+                    baseline_count: Set(row.baseline_count),
+                    expected_count: Set(self.expected_count(row.baseline_count)),
+                    acknowledged_at: Set(None),
+                    notified_at: Set(None),
+                    ..Default::default()
+                }
+                .insert(db)
+                .await?;
+
+                // Queued with the alert, so a raised alert is never left unsent
+                if !self.config.recipients.is_empty() {
+                    let notify_job = JobPayload::NotifyAlert { alert_id: alert.id };
+                    self.jobs.enqueue(db, &notify_job).await?;
+                }
+                raised.push(alert);
+            }
+        }
+
+        Ok(raised)
+    }
+
+    /// Run the check on the configured interval until the process exits
+    pub async fn run_periodically(self) {
+        let Some(minutes) = self.config.check_interval_minutes else {
+            return;
+        };
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(minutes * 60));
+
+        loop {
+            interval.tick().await;
+
+            match self.check(&self.db).await {
+                Ok(raised) if !raised.is_empty() => info!("Raised {} alerts", raised.len()),
+                Ok(_) => {}
+                Err(e) => error!("Alert check failed: {:?}", e),
+            }
+        }
+    }
+
+    pub async fn recent(&self) -> Result<Vec<alert::Model>, DbErr> {
+        Alert::find()
+            .order_by_desc(alert::Column::CreatedAt)
+            .limit(RECENT_ALERTS)
+            .all(&self.db)
+            .await
+    }
+
+    pub async fn acknowledge<C: ConnectionTrait>(&self, db: &C, id: i32) -> Result<bool, DbErr> {
+        let Some(alert) = Alert::find_by_id(id).one(db).await? else {
+            return Ok(false);
+        };
+        if alert.acknowledged_at.is_none() {
+            let mut active: alert::ActiveModel = alert.into();
+            active.acknowledged_at = Set(Some(Utc::now()));
+            active.update(db).await?;
+        }
+        Ok(true)
+    }
+
+    async fn volumes(
+        &self,
+        dimension: AlertDimension,
+        baseline_start: DateTime<Utc>,
+        window_start: DateTime<Utc>,
+    ) -> Result<Vec<VolumeRow>, DbErr> {
+        let sql = VOLUME_SQL.replace("{key}", dimension.key_expr());
+        VolumeRow::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            sql,
+            [
+                baseline_start.into(),
+                window_start.into(),
+                self.config.min_count.into(),
+            ],
+        ))
+        .all(&self.db)
+        .await
+    }
+
+    /// Human readable names for the keys; failure groups are described by their fields
+    async fn labels(
+        &self,
+        dimension: AlertDimension,
+        rows: &[VolumeRow],
+    ) -> Result<HashMap<String, String>, DbErr> {
+        if dimension != AlertDimension::FailureGroup {
+            return Ok(rows
+                .iter()
+                .map(|row| {
+                    let label = format!("{} {}", dimension.as_str(), row.dimension_key);
+                    (row.dimension_key.clone(), label)
+                })
+                .collect());
+        }
+
+        let group_ids: Vec<i32> = rows
+            .iter()
+            .filter_map(|row| row.dimension_key.parse().ok())
+            .collect();
+        Ok(FailureGroup::find()
+            .filter(failure_group::Column::Id.is_in(group_ids))
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|group| {
+                let label = format!(
+                    "{} {} ({})",
+                    group.recipe, group.failure_task, group.error_type
+                );
+                (group.id.to_string(), label)
+            })
+            .collect())
+    }
+
+    /// Baseline volume scaled to the length of the alert window
+    fn expected_count(&self, baseline_count: i64) -> f64 {
+        let baseline_hours = f64::from(self.config.baseline_days) * 24.0;
+        baseline_count as f64 * f64::from(self.config.window_hours) / baseline_hours
+    }
+}
+
+/// A window is a spike when it has at least `min_count` reports and exceeds the
+/// expected volume `threshold` times. Keys without a baseline count as one
+/// expected report, so a new failure needs more than `threshold` reports.
+pub fn is_spike(window_count: i64, expected: f64, threshold: f64, min_count: i64) -> bool {
+    window_count >= min_count && window_count as f64 > threshold * expected.max(1.0)
+}
+
+/// Subject and body of the notification sent for an alert
+pub fn alert_message(alert: &alert::Model, base_url: &str) -> (String, String) {
+    let subject = format!("Error report spike: {}", alert.label);
+    let body = format!(
+        "Submissions for {} spiked: {} reports in the alert window, \
+         about {:.1} expected from the {} reports in the baseline period.\n\n\
+         {}/admin/alerts\n",
+        alert.label, alert.window_count, alert.expected_count, alert.baseline_count, base_url
+    );
+    (subject, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_spike() {
+        // 30 reports against ~5 expected
+        assert!(is_spike(30, 5.0, 3.0, 10));
+        // Below the minimum volume
+        assert!(!is_spike(9, 1.0, 3.0, 10));
+        // Within the normal range
+        assert!(!is_spike(12, 5.0, 3.0, 10));
+        // No baseline at all
+        assert!(is_spike(10, 0.0, 3.0, 10));
+        assert!(!is_spike(3, 0.0, 3.0, 1));
+    }
+}
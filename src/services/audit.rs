@@ -0,0 +1,282 @@
+use chrono::Utc;
+use futures::Stream;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, Set,
+};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    models::{
+        audit_event::{self, AuditQuery},
+        AuditEvent,
+    },
+    services::comments::{CommentTarget, CommentView},
+    utils::client::RequestMeta,
+};
+
+/// Events fetched from the database per chunk of an export
+const EXPORT_PAGE_SIZE: u64 = 500;
+
+/// A change to record: what was done to which entity, and by whom
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub action: &'static str,
+    pub entity_type: &'static str,
+    pub entity_id: Option<String>,
+    pub actor: Option<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl AuditEntry {
+    pub fn new(action: &'static str, entity_type: &'static str) -> Self {
+        Self {
+            action,
+            entity_type,
+            entity_id: None,
+            actor: None,
+            before: None,
+            after: None,
+        }
+    }
+
+    pub fn entity(mut self, id: impl ToString) -> Self {
+        self.entity_id = Some(id.to_string());
+        self
+    }
+
+    /// The name given with the change; blank names are dropped
+    pub fn actor(mut self, actor: Option<&str>) -> Self {
+        self.actor = actor
+            .map(str::trim)
+            .filter(|actor| !actor.is_empty())
+            .map(str::to_string);
+        self
+    }
+
+    /// State of the entity before the change
+    pub fn before(mut self, value: &impl Serialize) -> Self {
+        self.before = serde_json::to_value(value).ok();
+        self
+    }
+
+    /// State of the entity after the change
+    pub fn after(mut self, value: &impl Serialize) -> Self {
+        self.after = serde_json::to_value(value).ok();
+        self
+    }
+
+    /// A new comment, with what it was posted on
+    pub fn comment(target: CommentTarget, comment: &CommentView) -> Self {
+        let (error_report_id, failure_group_id) = target.ids();
+        Self::new(audit_event::ACTION_CREATE, audit_event::ENTITY_COMMENT)
+            .entity(comment.id)
+            .actor(comment.author.as_deref())
+            .after(&serde_json::json!({
+                "error_report_id": error_report_id,
+                "failure_group_id": failure_group_id,
+                "parent_id": comment.parent_id,
+                "body": comment.body,
+            }))
+    }
+}
+
+/// One page of the audit log
+#[derive(Debug)]
+pub struct AuditPage {
+    pub events: Vec<audit_event::Model>,
+    pub page: u64,
+    pub total_pages: u64,
+    pub total: u64,
+}
+
+pub struct AuditService {
+    db: DatabaseConnection,
+}
+
+impl AuditService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Record a change on the connection that made it. Handlers pass the
+    /// transaction of the change, so the change and its event commit together.
+    pub async fn record<C: ConnectionTrait>(
+        db: &C,
+        meta: &RequestMeta,
+        entry: AuditEntry,
+    ) -> Result<(), DbErr> {
+        Self::record_all(db, meta, vec![entry]).await
+    }
+
+    /// Record several changes made by one request, such as a bulk action
+    pub async fn record_all<C: ConnectionTrait>(
+        db: &C,
+        meta: &RequestMeta,
+        entries: Vec<AuditEntry>,
+    ) -> Result<(), DbErr> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let now = Utc::now();
+        let events = entries.into_iter().map(|entry| {
+            let (before, after) = diff(entry.before, entry.after);
+            audit_event::ActiveModel {
+                created_at: Set(now),
+                actor: Set(entry.actor),
+                action: Set(entry.action.to_string()),
+                entity_type: Set(entry.entity_type.to_string()),
+                entity_id: Set(entry.entity_id),
+                before: Set(before),
+                after: Set(after),
+                ip: Set(meta.ip.clone()),
+                user_agent: Set(meta.user_agent.clone()),
+                method: Set(meta.method.clone()),
+                path: Set(meta.path.clone()),
+                ..Default::default()
+            }
+        });
+
+        AuditEvent::insert_many(events)
+            .exec_without_returning(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Newest events first
+    pub async fn list(&self, query: &AuditQuery, per_page: u64) -> Result<AuditPage, DbErr> {
+        let page = query.page.unwrap_or(1).max(1);
+        let paginator = AuditEvent::find()
+            .filter(query.condition())
+            .order_by_desc(audit_event::Column::Id)
+            .paginate(&self.db, per_page);
+        let events = paginator.fetch_page(page - 1).await?;
+        let total = paginator.num_items().await?;
+
+        Ok(AuditPage {
+            events,
+            page,
+            total_pages: total.div_ceil(per_page),
+            total,
+        })
+    }
+
+    /// Every matching event as a JSON array, oldest first, fetched in chunks
+    pub fn export(self, query: AuditQuery) -> impl Stream<Item = Result<String, DbErr>> {
+        let state = ExportState {
+            service: self,
+            query,
+            last_id: None,
+            done: false,
+        };
+
+        futures::stream::unfold(state, |mut state| async move {
+            if state.done {
+                return None;
+            }
+
+            let mut chunk = if state.last_id.is_none() {
+                String::from("[")
+            } else {
+                String::new()
+            };
+            match state.service.next_page(&state.query, state.last_id).await {
+                Ok(page) => {
+                    state.done = (page.len() as u64) < EXPORT_PAGE_SIZE;
+                    for event in &page {
+                        if state.last_id.is_some() {
+                            chunk.push(',');
+                        }
+                        chunk.push('\n');
+                        chunk.push_str(&serde_json::to_string(event).unwrap_or_default());
+                        state.last_id = Some(event.id);
+                    }
+                    if state.done {
+                        chunk.push_str("\n]\n");
+                    }
+                    Some((Ok(chunk), state))
+                }
+                Err(e) => {
+                    state.done = true;
+                    Some((Err(e), state))
+                }
+            }
+        })
+    }
+
+    /// Next page in ID order, which stays stable while events are being added
+    async fn next_page(
+        &self,
+        query: &AuditQuery,
+        after_id: Option<i64>,
+    ) -> Result<Vec<audit_event::Model>, DbErr> {
+        let mut select = AuditEvent::find().filter(query.condition());
+        if let Some(after_id) = after_id {
+            select = select.filter(audit_event::Column::Id.gt(after_id));
+        }
+
+        select
+            .order_by_asc(audit_event::Column::Id)
+            .limit(EXPORT_PAGE_SIZE)
+            .all(&self.db)
+            .await
+    }
+}
+
+struct ExportState {
+    service: AuditService,
+    query: AuditQuery,
+    last_id: Option<i64>,
+    done: bool,
+}
+
+/// Keep only the fields that differ when both states are objects, so an update
+/// records what it changed. Creations and deletions keep the whole entity.
+fn diff(before: Option<Value>, after: Option<Value>) -> (Option<Value>, Option<Value>) {
+    match (before, after) {
+        (Some(Value::Object(mut before)), Some(Value::Object(mut after))) => {
+            let unchanged: Vec<String> = before
+                .iter()
+                .filter(|(name, value)| after.get(*name) == Some(*value))
+                .map(|(name, _)| name.clone())
+                .collect();
+            for name in &unchanged {
+                before.remove(name);
+                after.remove(name);
+            }
+            (Some(Value::Object(before)), Some(Value::Object(after)))
+        }
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff() {
+        let (before, after) = diff(
+            Some(json!({"id": 3, "title": "Old", "enabled": true})),
+            Some(json!({"id": 3, "title": "New", "enabled": true, "link": "x"})),
+        );
+        assert_eq!(before, Some(json!({"title": "Old"})));
+        assert_eq!(after, Some(json!({"title": "New", "link": "x"})));
+
+        let created = Some(json!({"id": 4, "name": "flaky"}));
+        assert_eq!(diff(None, created.clone()), (None, created));
+    }
+
+    #[test]
+    fn test_entry_actor() {
+        let entry = AuditEntry::new(audit_event::ACTION_DELETE, audit_event::ENTITY_JOB)
+            .entity(7)
+            .actor(Some("  "));
+        assert_eq!(entry.entity_id.as_deref(), Some("7"));
+        assert_eq!(entry.actor, None);
+    }
+}
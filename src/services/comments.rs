@@ -0,0 +1,222 @@
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, Set,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::{
+    models::{
+        comment::{self, CommentData},
+        Comment,
+    },
+    utils::markdown::render_markdown,
+};
+
+/// Replies are indented by their depth up to this level
+const MAX_INDENT: usize = 4;
+
+/// What a comment is attached to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentTarget {
+    Report(i32),
+    Group(i32),
+}
+
+impl CommentTarget {
+    /// The report and failure group columns of a comment on this target
+    pub fn ids(self) -> (Option<i32>, Option<i32>) {
+        match self {
+            CommentTarget::Report(id) => (Some(id), None),
+            CommentTarget::Group(id) => (None, Some(id)),
+        }
+    }
+
+    fn column(self) -> (comment::Column, i32) {
+        match self {
+            CommentTarget::Report(id) => (comment::Column::ErrorReportId, id),
+            CommentTarget::Group(id) => (comment::Column::FailureGroupId, id),
+        }
+    }
+
+    fn contains(self, comment: &comment::Model) -> bool {
+        match self {
+            CommentTarget::Report(id) => comment.error_report_id == Some(id),
+            CommentTarget::Group(id) => comment.failure_group_id == Some(id),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommentError {
+    #[error("The comment replied to does not belong to this thread")]
+    InvalidParent,
+    #[error("Database error: {0}")]
+    Database(#[from] DbErr),
+}
+
+/// A comment in thread order, with its markdown rendered
+#[derive(Debug, Clone, Serialize)]
+pub struct CommentView {
+    pub id: i32,
+    pub parent_id: Option<i32>,
+    pub author: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub body: String,
+    /// Sanitized HTML of `body`
+    pub html: String,
+    /// Number of comments above this one in its thread
+    pub depth: usize,
+    #[serde(skip)]
+    pub indent: usize,
+}
+
+pub struct CommentService {
+    db: DatabaseConnection,
+}
+
+impl CommentService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// All comments on the target, each followed by its replies
+    pub async fn thread(&self, target: CommentTarget) -> Result<Vec<CommentView>, DbErr> {
+        let (column, id) = target.column();
+        let comments = Comment::find()
+            .filter(column.eq(id))
+            .order_by_asc(comment::Column::CreatedAt)
+            .order_by_asc(comment::Column::Id)
+            .all(&self.db)
+            .await?;
+        Ok(thread_comments(comments))
+    }
+
+    /// Add a comment; the input is expected to have passed validation
+    pub async fn create<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        target: CommentTarget,
+        data: CommentData,
+    ) -> Result<CommentView, CommentError> {
+        let mut depth = 0;
+        if let Some(parent_id) = data.parent_id {
+            let parent = Comment::find_by_id(parent_id)
+                .one(db)
+                .await?
+                .filter(|parent| target.contains(parent))
+                .ok_or(CommentError::InvalidParent)?;
+            depth = self.depth_of(parent).await? + 1;
+        }
+
+        let (error_report_id, failure_group_id) = target.ids();
+        let comment = comment::ActiveModel {
+            created_at: Set(Utc::now()),
+            error_report_id: Set(error_report_id),
+            failure_group_id: Set(failure_group_id),
+            parent_id: Set(data.parent_id),
+            author: Set(data
+                .author
+                .map(|author| author.trim().to_string())
+                .filter(|author| !author.is_empty())),
+            body: Set(data.body.trim().to_string()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+
+        Ok(comment_view(comment, depth))
+    }
+
+    async fn depth_of(&self, mut comment: comment::Model) -> Result<usize, DbErr> {
+        let mut depth = 0;
+        while let Some(parent_id) = comment.parent_id {
+            let Some(parent) = Comment::find_by_id(parent_id).one(&self.db).await? else {
+                break;
+            };
+            comment = parent;
+            depth += 1;
+        }
+        Ok(depth)
+    }
+}
+
+/// Order comments depth first: every comment is followed by its replies,
+/// oldest first. Comments whose parent is missing start a thread of their own.
+fn thread_comments(comments: Vec<comment::Model>) -> Vec<CommentView> {
+    let ids: Vec<i32> = comments.iter().map(|comment| comment.id).collect();
+    let mut roots = Vec::new();
+    let mut replies: HashMap<i32, Vec<comment::Model>> = HashMap::new();
+    for comment in comments {
+        match comment
+            .parent_id
+            .filter(|parent_id| ids.contains(parent_id))
+        {
+            Some(parent_id) => replies.entry(parent_id).or_default().push(comment),
+            None => roots.push(comment),
+        }
+    }
+
+    let mut views = Vec::new();
+    let mut stack: Vec<(comment::Model, usize)> = roots
+        .into_iter()
+        .rev()
+        .map(|comment| (comment, 0))
+        .collect();
+    while let Some((comment, depth)) = stack.pop() {
+        if let Some(children) = replies.remove(&comment.id) {
+            stack.extend(children.into_iter().rev().map(|child| (child, depth + 1)));
+        }
+        views.push(comment_view(comment, depth));
+    }
+    views
+}
+
+fn comment_view(comment: comment::Model, depth: usize) -> CommentView {
+    CommentView {
+        id: comment.id,
+        parent_id: comment.parent_id,
+        author: comment.author,
+        created_at: comment.created_at,
+        html: render_markdown(&comment.body),
+        body: comment.body,
+        depth,
+        indent: depth.min(MAX_INDENT),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(id: i32, parent_id: Option<i32>) -> comment::Model {
+        comment::Model {
+            id,
+            created_at: Utc::now(),
+            error_report_id: Some(1),
+            failure_group_id: None,
+            parent_id,
+            author: None,
+            body: format!("comment {}", id),
+        }
+    }
+
+    #[test]
+    fn test_thread_comments() {
+        let comments = vec![
+            comment(1, None),
+            comment(2, None),
+            comment(3, Some(1)),
+            comment(4, Some(3)),
+            comment(5, Some(1)),
+            // Reply to a comment on another thread
+            comment(6, Some(99)),
+        ];
+        let order: Vec<(i32, usize)> = thread_comments(comments)
+            .into_iter()
+            .map(|view| (view.id, view.depth))
+            .collect();
+        assert_eq!(order, vec![(1, 0), (3, 1), (4, 2), (5, 1), (2, 0), (6, 0)]);
+    }
+}
@@ -0,0 +1,395 @@
+use regex::Regex;
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter};
+use serde::Serialize;
+use similar::{ChangeTag, TextDiff};
+use std::{collections::BTreeMap, sync::OnceLock, time::Duration};
+
+use crate::models::{
+    build_configuration::{self, LayerInfo},
+    error_report, BuildConfiguration, ErrorReport,
+};
+
+/// Unchanged lines shown around each change
+const DIFF_CONTEXT_LINES: usize = 3;
+/// Time after which the line diff settles for a coarser result
+const DIFF_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Values that differ between otherwise identical builds, replaced before diffing
+const NORMALIZATION_RULES: &[(&str, &str)] = &[
+    // Build directories, e.g. /home/user/poky/build/tmp-glibc/work/
+    (r"\S*/tmp[\w-]*/work/", "<TMPDIR>/work/"),
+    // Task scripts and logs carry the PID
+    (r"\b(run|log)\.(do_\w+)\.\d+\b", "$1.$2.<PID>"),
+    (
+        r"\b\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:?\d{2})?\b",
+        "<TIMESTAMP>",
+    ),
+    (r"\b\d{2}:\d{2}:\d{2}(\.\d+)?\b", "<TIME>"),
+    (r"\b0x[0-9a-fA-F]+\b", "<ADDR>"),
+];
+
+/// A field of both reports
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldDiff {
+    pub field: &'static str,
+    pub a: Option<String>,
+    pub b: Option<String>,
+    pub differs: bool,
+}
+
+impl FieldDiff {
+    fn new(field: &'static str, a: Option<String>, b: Option<String>) -> Self {
+        let differs = a != b;
+        Self {
+            field,
+            a,
+            b,
+            differs,
+        }
+    }
+}
+
+/// A layer as checked out in each build
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LayerDiff {
+    pub name: String,
+    pub a_branch: Option<String>,
+    pub a_commit: Option<String>,
+    pub b_branch: Option<String>,
+    pub b_commit: Option<String>,
+    pub differs: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffLineKind {
+    Equal,
+    Delete,
+    Insert,
+}
+
+impl DiffLineKind {
+    pub fn css_class(&self) -> &'static str {
+        match self {
+            DiffLineKind::Equal => "",
+            DiffLineKind::Delete => "diff-delete",
+            DiffLineKind::Insert => "diff-insert",
+        }
+    }
+
+    pub fn marker(&self) -> &'static str {
+        match self {
+            DiffLineKind::Equal => " ",
+            DiffLineKind::Delete => "-",
+            DiffLineKind::Insert => "+",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    /// Line number in report A, for equal and deleted lines
+    pub a_line: Option<usize>,
+    /// Line number in report B, for equal and inserted lines
+    pub b_line: Option<usize>,
+    /// Normalized text
+    pub text: String,
+}
+
+/// Changed lines of a text field with some context
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LineDiff {
+    pub identical: bool,
+    pub hunks: Vec<Vec<DiffLine>>,
+}
+
+/// Everything that differs between two reports
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportComparison {
+    pub a_id: i32,
+    pub b_id: i32,
+    pub fields: Vec<FieldDiff>,
+    pub build_configuration: Vec<FieldDiff>,
+    pub layers: Vec<LayerDiff>,
+    pub error_details: LineDiff,
+    pub log_data: LineDiff,
+}
+
+impl ReportComparison {
+    /// The text diffs with their headings, in display order
+    pub fn text_diffs(&self) -> Vec<(&'static str, &LineDiff)> {
+        vec![
+            ("Error Details", &self.error_details),
+            ("Build Log", &self.log_data),
+        ]
+    }
+}
+
+pub struct CompareService {
+    db: DatabaseConnection,
+}
+
+impl CompareService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Compare two reports; `None` when either does not exist or is hidden
+    pub async fn compare(&self, a_id: i32, b_id: i32) -> Result<Option<ReportComparison>, DbErr> {
+        let Some(a) = self.visible_report(a_id).await? else {
+            return Ok(None);
+        };
+        let Some(b) = self.visible_report(b_id).await? else {
+            return Ok(None);
+        };
+        let a_config = self.build_configuration(a_id).await?;
+        let b_config = self.build_configuration(b_id).await?;
+
+        Ok(Some(compare_reports(
+            &a,
+            a_config.as_ref(),
+            &b,
+            b_config.as_ref(),
+        )))
+    }
+
+    async fn visible_report(&self, id: i32) -> Result<Option<error_report::Model>, DbErr> {
+        ErrorReport::find_by_id(id)
+            .filter(error_report::visible())
+            .one(&self.db)
+            .await
+    }
+
+    async fn build_configuration(
+        &self,
+        report_id: i32,
+    ) -> Result<Option<build_configuration::Model>, DbErr> {
+        BuildConfiguration::find()
+            .filter(build_configuration::Column::ErrorReportId.eq(report_id))
+            .one(&self.db)
+            .await
+    }
+}
+
+pub fn compare_reports(
+    a: &error_report::Model,
+    a_config: Option<&build_configuration::Model>,
+    b: &error_report::Model,
+    b_config: Option<&build_configuration::Model>,
+) -> ReportComparison {
+    let metadata = |report: &error_report::Model| -> Vec<(&'static str, Option<String>)> {
+        vec![
+            ("created_at", Some(report.created_at.to_rfc3339())),
+            ("machine", Some(report.machine.clone())),
+            ("distro", Some(report.distro.clone())),
+            ("distro_version", Some(report.distro_version.clone())),
+            ("build_sys", Some(report.build_sys.clone())),
+            ("nativelsbstring", Some(report.nativelsbstring.clone())),
+            ("target_sys", Some(report.target_sys.clone())),
+            ("failure_task", Some(report.failure_task.clone())),
+            ("failure_package", Some(report.failure_package.clone())),
+            ("error_type", Some(report.error_type.clone())),
+            ("branch_commit", Some(report.branch_commit.clone())),
+            (
+                "failure_group_id",
+                report.failure_group_id.map(|id| id.to_string()),
+            ),
+        ]
+    };
+    let config_fields =
+        |config: Option<&build_configuration::Model>| -> Vec<(&'static str, Option<String>)> {
+            vec![
+                ("bb_version", config.map(|c| c.bb_version.clone())),
+                (
+                    "tune_features",
+                    config.and_then(|c| c.tune_features.clone()),
+                ),
+                ("target_fpu", config.and_then(|c| c.target_fpu.clone())),
+            ]
+        };
+    let pair_up = |a: Vec<(&'static str, Option<String>)>,
+                   b: Vec<(&'static str, Option<String>)>| {
+        a.into_iter()
+            .zip(b)
+            .map(|((field, a), (_, b))| FieldDiff::new(field, a, b))
+            .collect()
+    };
+
+    ReportComparison {
+        a_id: a.id,
+        b_id: b.id,
+        fields: pair_up(metadata(a), metadata(b)),
+        build_configuration: pair_up(config_fields(a_config), config_fields(b_config)),
+        layers: layer_diff(&layers(a_config), &layers(b_config)),
+        error_details: text_diff(&a.error_details, &b.error_details),
+        log_data: text_diff(&a.log_data, &b.log_data),
+    }
+}
+
+fn layers(config: Option<&build_configuration::Model>) -> Vec<LayerInfo> {
+    config
+        .and_then(|config| serde_json::from_str(&config.meta_layers).ok())
+        .unwrap_or_default()
+}
+
+/// Layers of either build by name, with their branch and commit in each
+pub fn layer_diff(a: &[LayerInfo], b: &[LayerInfo]) -> Vec<LayerDiff> {
+    let mut layers: BTreeMap<&str, LayerDiff> = BTreeMap::new();
+    for layer in a {
+        let diff = layers
+            .entry(&layer.name)
+            .or_insert_with(|| empty_layer(&layer.name));
+        diff.a_branch = layer.branch.clone();
+        diff.a_commit = layer.commit.clone();
+    }
+    for layer in b {
+        let diff = layers
+            .entry(&layer.name)
+            .or_insert_with(|| empty_layer(&layer.name));
+        diff.b_branch = layer.branch.clone();
+        diff.b_commit = layer.commit.clone();
+    }
+
+    let a_names: Vec<&str> = a.iter().map(|layer| layer.name.as_str()).collect();
+    let b_names: Vec<&str> = b.iter().map(|layer| layer.name.as_str()).collect();
+    layers
+        .into_values()
+        .map(|mut diff| {
+            let in_both =
+                a_names.contains(&diff.name.as_str()) && b_names.contains(&diff.name.as_str());
+            diff.differs =
+                !in_both || diff.a_branch != diff.b_branch || diff.a_commit != diff.b_commit;
+            diff
+        })
+        .collect()
+}
+
+fn empty_layer(name: &str) -> LayerDiff {
+    LayerDiff {
+        name: name.to_string(),
+        a_branch: None,
+        a_commit: None,
+        b_branch: None,
+        b_commit: None,
+        differs: false,
+    }
+}
+
+/// Replace build directories, PIDs, timestamps and addresses so that only
+/// meaningful differences remain
+pub fn normalize(text: &str) -> String {
+    static RULES: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    let rules = RULES.get_or_init(|| {
+        NORMALIZATION_RULES
+            .iter()
+            .map(|(pattern, replacement)| (Regex::new(pattern).unwrap(), *replacement))
+            .collect()
+    });
+
+    rules
+        .iter()
+        .fold(text.to_string(), |text, (pattern, replacement)| {
+            pattern.replace_all(&text, *replacement).into_owned()
+        })
+}
+
+/// Line diff of the normalized texts, grouped into hunks with context
+pub fn text_diff(a: &str, b: &str) -> LineDiff {
+    let a = normalize(a);
+    let b = normalize(b);
+    let diff = TextDiff::configure()
+        .timeout(DIFF_TIMEOUT)
+        .diff_lines(&a, &b);
+
+    let hunks: Vec<Vec<DiffLine>> = diff
+        .grouped_ops(DIFF_CONTEXT_LINES)
+        .iter()
+        .map(|group| {
+            group
+                .iter()
+                .flat_map(|op| diff.iter_changes(op))
+                .map(|change| DiffLine {
+                    kind: match change.tag() {
+                        ChangeTag::Equal => DiffLineKind::Equal,
+                        ChangeTag::Delete => DiffLineKind::Delete,
+                        ChangeTag::Insert => DiffLineKind::Insert,
+                    },
+                    a_line: change.old_index().map(|index| index + 1),
+                    b_line: change.new_index().map(|index| index + 1),
+                    text: change.value().trim_end_matches(['\r', '\n']).to_string(),
+                })
+                .collect()
+        })
+        .collect();
+
+    LineDiff {
+        identical: hunks.is_empty(),
+        hunks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(name: &str, commit: &str) -> LayerInfo {
+        LayerInfo {
+            name: name.to_string(),
+            path: format!("/path/to/{}", name),
+            commit: Some(commit.to_string()),
+            branch: Some("master".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize("/home/alice/poky/build/tmp-glibc/work/core2-64-poky-linux/busybox/temp/run.do_compile.12345"),
+            "<TMPDIR>/work/core2-64-poky-linux/busybox/temp/run.do_compile.<PID>"
+        );
+        assert_eq!(
+            normalize("2024-05-01 10:22:11 segfault at 0x7ffd3a"),
+            "<TIMESTAMP> segfault at <ADDR>"
+        );
+    }
+
+    #[test]
+    fn test_text_diff() {
+        let a = "NOTE: started 10:00:01\nline a\nsame\nERROR: failed in /b1/tmp/work/x\n";
+        let b = "NOTE: started 11:30:00\nline b\nsame\nERROR: failed in /b2/tmp/work/x\n";
+
+        let diff = text_diff(a, b);
+        assert!(!diff.identical);
+        let changed: Vec<(DiffLineKind, &str)> = diff.hunks[0]
+            .iter()
+            .filter(|line| line.kind != DiffLineKind::Equal)
+            .map(|line| (line.kind, line.text.as_str()))
+            .collect();
+        assert_eq!(
+            changed,
+            vec![
+                (DiffLineKind::Delete, "line a"),
+                (DiffLineKind::Insert, "line b")
+            ]
+        );
+
+        assert!(text_diff("same\n", "same\n").identical);
+    }
+
+    #[test]
+    fn test_layer_diff() {
+        let a = vec![layer("meta", "aaa"), layer("meta-poky", "bbb")];
+        let b = vec![layer("meta", "aaa"), layer("meta-oe", "ccc")];
+
+        let diff = layer_diff(&a, &b);
+        let summary: Vec<(&str, bool)> = diff
+            .iter()
+            .map(|layer| (layer.name.as_str(), layer.differs))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("meta", false), ("meta-oe", true), ("meta-poky", true)]
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use sea_orm::{DatabaseConnection, DbErr, PaginatorTrait};
+
+use crate::models::error_report::{ErrorListResponse, ErrorQuery, PaginationInfo};
+
+/// Paginated error listings shared by the API and the web interface
+pub struct ErrorListService {
+    db: DatabaseConnection,
+}
+
+impl ErrorListService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// One page of the reports matching `query`, in the requested order
+    pub async fn list(
+        &self,
+        query: &ErrorQuery,
+        default_per_page: u64,
+    ) -> Result<ErrorListResponse, DbErr> {
+        let page = query.page();
+        let per_page = query.per_page(default_per_page);
+
+        let paginator = query.select().paginate(&self.db, per_page);
+        let errors = paginator.fetch_page(page - 1).await?;
+        let total = paginator.num_items().await?;
+
+        Ok(ErrorListResponse {
+            errors,
+            pagination: PaginationInfo {
+                page,
+                per_page,
+                total,
+                total_pages: total.div_ceil(per_page),
+            },
+        })
+    }
+}
@@ -0,0 +1,406 @@
+use futures::Stream;
+use sea_orm::{
+    ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect,
+};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::{collections::HashMap, str::FromStr};
+
+use crate::{
+    config::EmailVisibility,
+    models::{build_configuration, error_report, BuildConfiguration, ErrorReport},
+    utils::privacy::mask_email,
+};
+
+/// Report fields that can be exported, in their default order
+pub const EXPORT_COLUMNS: &[&str] = &[
+    "id",
+    "created_at",
+    "machine",
+    "distro",
+    "distro_version",
+    "build_sys",
+    "nativelsbstring",
+    "target_sys",
+    "failure_task",
+    "failure_package",
+    "error_type",
+    "error_details",
+    "log_data",
+    "submitter_name",
+    "submitter_email",
+    "bugzilla_link",
+    "branch_commit",
+    "failure_group_id",
+];
+
+/// Build configuration fields appended when `include_build_config` is set
+const BUILD_CONFIG_COLUMNS: &[&str] = &["bb_version", "tune_features", "target_fpu", "meta_layers"];
+
+/// Reports fetched from the database per chunk of output
+const EXPORT_PAGE_SIZE: u64 = 500;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+}
+
+/// Export shape; filters are taken from `ErrorQuery`
+#[derive(Debug, Default, Deserialize)]
+pub struct ExportParams {
+    pub format: Option<ExportFormat>,
+    /// Comma-separated report fields; all but `log_data` when unset
+    pub columns: Option<String>,
+    pub include_build_config: Option<bool>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ExportParamsError {
+    #[error("Unknown export column: {0}")]
+    UnknownColumn(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    pub columns: Vec<&'static str>,
+    pub include_build_config: bool,
+    pub filter: Condition,
+}
+
+impl ExportOptions {
+    pub fn new(
+        params: &ExportParams,
+        query: &error_report::ErrorQuery,
+    ) -> Result<Self, ExportParamsError> {
+        let columns = match params.columns.as_deref().map(str::trim) {
+            Some(columns) if !columns.is_empty() => columns
+                .split(',')
+                .map(str::trim)
+                .filter(|column| !column.is_empty())
+                .map(|column| {
+                    EXPORT_COLUMNS
+                        .iter()
+                        .copied()
+                        .find(|known| *known == column)
+                        .ok_or_else(|| ExportParamsError::UnknownColumn(column.to_string()))
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            // Logs can be huge; they have to be asked for explicitly
+            _ => EXPORT_COLUMNS
+                .iter()
+                .copied()
+                .filter(|column| *column != "log_data")
+                .collect(),
+        };
+
+        Ok(Self {
+            format: params.format.unwrap_or_default(),
+            columns,
+            include_build_config: params.include_build_config.unwrap_or(false),
+            filter: query.condition(),
+        })
+    }
+}
+
+/// Requested fields of a report, as returned by the database
+struct ExportRow {
+    id: i32,
+    fields: Map<String, Value>,
+}
+
+struct ExportState {
+    service: ExportService,
+    options: ExportOptions,
+    last_id: Option<i32>,
+    started: bool,
+    done: bool,
+}
+
+pub struct ExportService {
+    db: DatabaseConnection,
+    email_visibility: EmailVisibility,
+}
+
+impl ExportService {
+    pub fn new(db: DatabaseConnection, email_visibility: EmailVisibility) -> Self {
+        Self {
+            db,
+            email_visibility,
+        }
+    }
+
+    /// Stream every matching report as CSV or NDJSON, one page of reports per
+    /// chunk, so the full result is never held in memory
+    pub fn stream(self, options: ExportOptions) -> impl Stream<Item = Result<String, DbErr>> {
+        let state = ExportState {
+            service: self,
+            options,
+            last_id: None,
+            started: false,
+            done: false,
+        };
+
+        futures::stream::unfold(state, |mut state| async move {
+            if state.done {
+                return None;
+            }
+
+            let mut chunk = String::new();
+            if !state.started {
+                state.started = true;
+                if state.options.format == ExportFormat::Csv {
+                    chunk.push_str(&csv_header(&state.options));
+                }
+            }
+
+            match state.service.next_page(&state.options, state.last_id).await {
+                Ok(page) => {
+                    state.done = (page.len() as u64) < EXPORT_PAGE_SIZE;
+                    state.last_id = page.last().map(|(row, _)| row.id);
+                    for (row, build_config) in &page {
+                        chunk.push_str(&format_row(
+                            &state.options,
+                            &row.fields,
+                            build_config.as_ref(),
+                        ));
+                    }
+                    Some((Ok(chunk), state))
+                }
+                Err(e) => {
+                    state.done = true;
+                    Some((Err(e), state))
+                }
+            }
+        })
+    }
+
+    /// Next page in ID order, which stays stable while reports are being added.
+    /// Only the requested columns are read, so large logs aren't loaded unless asked for.
+    async fn next_page(
+        &self,
+        options: &ExportOptions,
+        after_id: Option<i32>,
+    ) -> Result<Vec<(ExportRow, Option<build_configuration::Model>)>, DbErr> {
+        // The ID is needed for paging and build configurations even when not exported
+        let columns = std::iter::once("id")
+            .chain(options.columns.iter().copied())
+            .map(|column| {
+                error_report::Column::from_str(column)
+                    .map_err(|_| DbErr::Custom(format!("Unknown export column: {}", column)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut query = ErrorReport::find()
+            .select_only()
+            .columns(columns)
+            .filter(options.filter.clone());
+        if let Some(after_id) = after_id {
+            query = query.filter(error_report::Column::Id.gt(after_id));
+        }
+
+        let reports: Vec<ExportRow> = query
+            .order_by_asc(error_report::Column::Id)
+            .limit(EXPORT_PAGE_SIZE)
+            .into_json()
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .filter_map(|row| match row {
+                Value::Object(fields) => Some(self.export_row(fields)),
+                _ => None,
+            })
+            .collect();
+
+        let mut build_configs: HashMap<i32, build_configuration::Model> =
+            if options.include_build_config && !reports.is_empty() {
+                BuildConfiguration::find()
+                    .filter(
+                        build_configuration::Column::ErrorReportId
+                            .is_in(reports.iter().map(|report| report.id)),
+                    )
+                    .all(&self.db)
+                    .await?
+                    .into_iter()
+                    .map(|config| (config.error_report_id, config))
+                    .collect()
+            } else {
+                HashMap::new()
+            };
+
+        Ok(reports
+            .into_iter()
+            .map(|report| {
+                let build_config = build_configs.remove(&report.id);
+                (report, build_config)
+            })
+            .collect())
+    }
+
+    /// Mask the submitter email of a row before it is exported
+    fn export_row(&self, mut fields: Map<String, Value>) -> ExportRow {
+        let id = fields.get("id").and_then(Value::as_i64).unwrap_or_default() as i32;
+        if let Some(email) = fields.get_mut("submitter_email") {
+            *email = match email.as_str() {
+                Some(address) => {
+                    mask_email(self.email_visibility, address).map_or(Value::Null, Value::String)
+                }
+                None => Value::Null,
+            };
+        }
+        ExportRow { id, fields }
+    }
+}
+
+fn csv_header(options: &ExportOptions) -> String {
+    let mut columns = options.columns.clone();
+    if options.include_build_config {
+        columns.extend_from_slice(BUILD_CONFIG_COLUMNS);
+    }
+    csv_line(columns.into_iter().map(str::to_string))
+}
+
+/// One CSV line or NDJSON object for a report
+fn format_row(
+    options: &ExportOptions,
+    fields: &Map<String, Value>,
+    build_config: Option<&build_configuration::Model>,
+) -> String {
+    let selected = options
+        .columns
+        .iter()
+        .map(|column| (*column, fields.get(*column).cloned().unwrap_or(Value::Null)));
+
+    match options.format {
+        ExportFormat::Csv => {
+            let mut values: Vec<String> = selected.map(|(_, value)| csv_value(&value)).collect();
+            if options.include_build_config {
+                match build_config {
+                    Some(config) => values.extend([
+                        config.bb_version.clone(),
+                        config.tune_features.clone().unwrap_or_default(),
+                        config.target_fpu.clone().unwrap_or_default(),
+                        config.meta_layers.clone(),
+                    ]),
+                    None => values.extend(BUILD_CONFIG_COLUMNS.iter().map(|_| String::new())),
+                }
+            }
+            csv_line(values.into_iter())
+        }
+        ExportFormat::Ndjson => {
+            let mut object: Map<String, Value> = selected
+                .map(|(column, value)| (column.to_string(), value))
+                .collect();
+            if options.include_build_config {
+                let build_configuration = build_config.map_or(Value::Null, |config| {
+                    serde_json::json!({
+                        "bb_version": config.bb_version,
+                        "tune_features": config.tune_features,
+                        "target_fpu": config.target_fpu,
+                        "meta_layers": serde_json::from_str::<Value>(&config.meta_layers)
+                            .unwrap_or(Value::Null),
+                    })
+                });
+                object.insert("build_configuration".to_string(), build_configuration);
+            }
+            format!("{}\n", Value::Object(object))
+        }
+    }
+}
+
+fn csv_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(value) => value.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Join fields into an RFC 4180 line, quoting fields that need it
+fn csv_line(fields: impl Iterator<Item = String>) -> String {
+    let mut line = fields
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push_str("\r\n");
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::error_report::ErrorQuery;
+
+    #[test]
+    fn test_csv_line_quoting() {
+        let line = csv_line(
+            [
+                "plain".to_string(),
+                "a,b".to_string(),
+                "say \"hi\"".to_string(),
+                "two\nlines".to_string(),
+            ]
+            .into_iter(),
+        );
+        assert_eq!(line, "plain,\"a,b\",\"say \"\"hi\"\"\",\"two\nlines\"\r\n");
+    }
+
+    #[test]
+    fn test_export_columns() {
+        let options = ExportOptions::new(&ExportParams::default(), &ErrorQuery::default()).unwrap();
+        assert!(!options.columns.contains(&"log_data"));
+        assert_eq!(options.columns.len(), EXPORT_COLUMNS.len() - 1);
+
+        let params = ExportParams {
+            columns: Some("id, machine,log_data".to_string()),
+            ..Default::default()
+        };
+        let options = ExportOptions::new(&params, &ErrorQuery::default()).unwrap();
+        assert_eq!(options.columns, vec!["id", "machine", "log_data"]);
+
+        let params = ExportParams {
+            columns: Some("id,password".to_string()),
+            ..Default::default()
+        };
+        assert!(ExportOptions::new(&params, &ErrorQuery::default()).is_err());
+    }
+
+    #[test]
+    fn test_export_row_masks_email() {
+        let service = ExportService::new(DatabaseConnection::Disconnected, EmailVisibility::Hide);
+        let fields = serde_json::json!({ "id": 7, "submitter_email": "test@example.com" });
+        let Value::Object(fields) = fields else {
+            unreachable!()
+        };
+
+        let row = service.export_row(fields);
+        assert_eq!(row.id, 7);
+        assert_eq!(row.fields["submitter_email"], Value::Null);
+    }
+}
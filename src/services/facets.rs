@@ -0,0 +1,334 @@
+use futures::future::try_join_all;
+use sea_orm::{
+    sea_query::{Alias, Expr},
+    ColumnTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait, FromQueryResult, QueryFilter,
+    QueryOrder, QuerySelect, QueryTrait, Statement,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{
+    error_report::{self, ErrorQuery},
+    ErrorReport,
+};
+
+/// Values listed per facet unless `facet_size` asks for another amount
+pub const DEFAULT_FACET_SIZE: u64 = 10;
+const MAX_FACET_SIZE: u64 = 100;
+
+/// Fields of a report the error list can be narrowed down by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FacetField {
+    Machine,
+    Distro,
+    DistroVersion,
+    ErrorType,
+    FailureTask,
+    HostDistro,
+    Layer,
+    Tag,
+}
+
+impl FacetField {
+    pub const ALL: [FacetField; 8] = [
+        FacetField::Machine,
+        FacetField::Distro,
+        FacetField::DistroVersion,
+        FacetField::ErrorType,
+        FacetField::FailureTask,
+        FacetField::HostDistro,
+        FacetField::Layer,
+        FacetField::Tag,
+    ];
+
+    /// Name of the `ErrorQuery` parameter selecting values of this facet
+    pub fn param(self) -> &'static str {
+        match self {
+            FacetField::Machine => "machine",
+            FacetField::Distro => "distro",
+            FacetField::DistroVersion => "distro_version",
+            FacetField::ErrorType => "error_type",
+            FacetField::FailureTask => "failure_task",
+            FacetField::HostDistro => "nativelsbstring",
+            FacetField::Layer => "layer",
+            FacetField::Tag => "tag",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FacetField::Machine => "Machine",
+            FacetField::Distro => "Distro",
+            FacetField::DistroVersion => "Distro Version",
+            FacetField::ErrorType => "Error Type",
+            FacetField::FailureTask => "Failure Task",
+            FacetField::HostDistro => "Host Distro",
+            FacetField::Layer => "Layer",
+            FacetField::Tag => "Tag",
+        }
+    }
+
+    /// Report column holding the value; layers and tags are stored separately
+    fn column(self) -> Option<error_report::Column> {
+        match self {
+            FacetField::Machine => Some(error_report::Column::Machine),
+            FacetField::Distro => Some(error_report::Column::Distro),
+            FacetField::DistroVersion => Some(error_report::Column::DistroVersion),
+            FacetField::ErrorType => Some(error_report::Column::ErrorType),
+            FacetField::FailureTask => Some(error_report::Column::FailureTask),
+            FacetField::HostDistro => Some(error_report::Column::Nativelsbstring),
+            FacetField::Layer | FacetField::Tag => None,
+        }
+    }
+
+    /// Value expression and row source for values not stored on the report.
+    /// Every row of the source has an `error_report_id`.
+    fn related_values(self) -> (&'static str, &'static str) {
+        match self {
+            FacetField::Tag => (
+                "tags.name",
+                "error_report_tags JOIN tags ON tags.id = error_report_tags.tag_id",
+            ),
+            _ => (
+                "layer->>'name'",
+                "build_configurations CROSS JOIN jsonb_array_elements(meta_layers::jsonb) AS layer",
+            ),
+        }
+    }
+
+    fn selected(self, query: &mut ErrorQuery) -> &mut Vec<String> {
+        match self {
+            FacetField::Machine => &mut query.machine,
+            FacetField::Distro => &mut query.distro,
+            FacetField::DistroVersion => &mut query.distro_version,
+            FacetField::ErrorType => &mut query.error_type,
+            FacetField::FailureTask => &mut query.failure_task,
+            FacetField::HostDistro => &mut query.nativelsbstring,
+            FacetField::Layer => &mut query.layer,
+            FacetField::Tag => &mut query.tag,
+        }
+    }
+}
+
+/// Number of values listed per facet
+#[derive(Debug, Default, Deserialize)]
+pub struct FacetParams {
+    pub facet_size: Option<u64>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FacetParamsError {
+    #[error("facet_size must be between 1 and {MAX_FACET_SIZE}")]
+    InvalidSize,
+}
+
+impl FacetParams {
+    pub fn size(&self) -> Result<u64, FacetParamsError> {
+        let size = self.facet_size.unwrap_or(DEFAULT_FACET_SIZE);
+        if !(1..=MAX_FACET_SIZE).contains(&size) {
+            return Err(FacetParamsError::InvalidSize);
+        }
+        Ok(size)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Facet {
+    pub field: FacetField,
+    pub param: &'static str,
+    pub label: &'static str,
+    pub values: Vec<FacetValue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FacetValue {
+    pub value: String,
+    pub count: u64,
+    pub selected: bool,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct FacetRow {
+    value: String,
+    count: i64,
+}
+
+pub struct FacetService {
+    db: DatabaseConnection,
+}
+
+impl FacetService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Most frequent values of every facet among the reports matching `query`.
+    /// Each facet is counted without its own filter so that further values can
+    /// be added to the selection; selected values are always listed.
+    pub async fn get_facets(&self, query: &ErrorQuery, size: u64) -> Result<Vec<Facet>, DbErr> {
+        try_join_all(
+            FacetField::ALL
+                .into_iter()
+                .map(|field| self.get_facet(field, query, size)),
+        )
+        .await
+    }
+
+    async fn get_facet(
+        &self,
+        field: FacetField,
+        query: &ErrorQuery,
+        size: u64,
+    ) -> Result<Facet, DbErr> {
+        let mut others = query.clone();
+        let selected = std::mem::take(field.selected(&mut others));
+
+        let mut rows = self.count_values(field, &others, size, &[]).await?;
+        let missing: Vec<String> = selected
+            .iter()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty() && !rows.iter().any(|row| &row.value == value))
+            .collect();
+        if !missing.is_empty() {
+            rows.extend(self.count_values(field, &others, size, &missing).await?);
+        }
+
+        Ok(Facet {
+            field,
+            param: field.param(),
+            label: field.label(),
+            values: facet_values(rows, &selected),
+        })
+    }
+
+    /// Report counts per value, most frequent first; only `values` when not empty
+    async fn count_values(
+        &self,
+        field: FacetField,
+        query: &ErrorQuery,
+        size: u64,
+        values: &[String],
+    ) -> Result<Vec<FacetRow>, DbErr> {
+        match field.column() {
+            Some(column) => {
+                let mut select = ErrorReport::find()
+                    .select_only()
+                    .column_as(column, "value")
+                    .column_as(error_report::Column::Id.count(), "count")
+                    .filter(query.condition());
+                if !values.is_empty() {
+                    select = select.filter(column.is_in(values));
+                }
+                select
+                    .group_by(column)
+                    .order_by_desc(Expr::col(Alias::new("count")))
+                    .order_by_asc(column)
+                    .limit(size)
+                    .into_model::<FacetRow>()
+                    .all(&self.db)
+                    .await
+            }
+            None => {
+                // Values of the matching reports from their layer list or tags
+                let (value_expr, source) = field.related_values();
+                let reports = ErrorReport::find()
+                    .select_only()
+                    .column(error_report::Column::Id)
+                    .filter(query.condition())
+                    .build(DbBackend::Postgres);
+                let mut sql_values = reports.values.map(|v| v.0).unwrap_or_default();
+                let mut value_filter = String::new();
+                if !values.is_empty() {
+                    let placeholders: Vec<String> = values
+                        .iter()
+                        .enumerate()
+                        .map(|(i, _)| format!("${}", sql_values.len() + i + 1))
+                        .collect();
+                    value_filter = format!("AND {} IN ({})", value_expr, placeholders.join(", "));
+                    sql_values.extend(values.iter().map(|value| value.clone().into()));
+                }
+                sql_values.push((size as i64).into());
+
+                FacetRow::find_by_statement(Statement::from_sql_and_values(
+                    DbBackend::Postgres,
+                    format!(
+                        r#"SELECT {value} AS value, COUNT(DISTINCT error_report_id) AS count
+                        FROM {source}
+                        WHERE error_report_id IN ({reports}) AND {value} IS NOT NULL {filter}
+                        GROUP BY 1
+                        ORDER BY count DESC, value
+                        LIMIT ${limit}"#,
+                        value = value_expr,
+                        source = source,
+                        reports = reports.sql,
+                        filter = value_filter,
+                        limit = sql_values.len()
+                    ),
+                    sql_values,
+                ))
+                .all(&self.db)
+                .await
+            }
+        }
+    }
+}
+
+/// Rows as facet values, without blank values and with the selected ones marked
+fn facet_values(rows: Vec<FacetRow>, selected: &[String]) -> Vec<FacetValue> {
+    rows.into_iter()
+        .filter(|row| !row.value.trim().is_empty())
+        .map(|row| FacetValue {
+            selected: selected.iter().any(|value| value.trim() == row.value),
+            value: row.value,
+            count: row.count as u64,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_facet_values() {
+        let rows = vec![
+            FacetRow {
+                value: "qemux86-64".to_string(),
+                count: 12,
+            },
+            FacetRow {
+                value: String::new(),
+                count: 3,
+            },
+            FacetRow {
+                value: "qemuarm".to_string(),
+                count: 2,
+            },
+        ];
+        let values = facet_values(rows, &["qemuarm ".to_string()]);
+        assert_eq!(
+            values,
+            vec![
+                FacetValue {
+                    value: "qemux86-64".to_string(),
+                    count: 12,
+                    selected: false,
+                },
+                FacetValue {
+                    value: "qemuarm".to_string(),
+                    count: 2,
+                    selected: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_facet_size() {
+        assert_eq!(FacetParams::default().size().unwrap(), DEFAULT_FACET_SIZE);
+        let too_many = FacetParams {
+            facet_size: Some(MAX_FACET_SIZE + 1),
+        };
+        assert!(too_many.size().is_err());
+    }
+}
@@ -0,0 +1,751 @@
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use sea_orm::{
+    sea_query::{Alias, Expr, OnConflict},
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, sync::OnceLock};
+
+use crate::models::{
+    alert, comment, error_report, failure_group, failure_group_event,
+    failure_group_rule::{self, FailureGroupRuleData},
+    saved_search, Alert, Comment, ErrorReport, FailureGroup, FailureGroupEvent, FailureGroupRule,
+    SavedSearch,
+};
+
+/// Version and revision suffix of a package name, e.g. "-1.36.1-r0".
+/// Must stay in sync with the backfill in migrations/006_failure_groups.sql.
+/// Also used as a PostgreSQL regular expression by the failure matrix.
+pub(crate) const VERSION_SUFFIX_PATTERN: &str = r"-[0-9][^-]*-r[0-9]+$";
+
+/// Recipe name of a failing package, so that version bumps don't start a new group
+pub fn recipe_name(failure_package: &str) -> &str {
+    static VERSION_SUFFIX: OnceLock<Regex> = OnceLock::new();
+    let pattern = VERSION_SUFFIX.get_or_init(|| Regex::new(VERSION_SUFFIX_PATTERN).unwrap());
+
+    match pattern.find(failure_package) {
+        Some(suffix) => &failure_package[..suffix.start()],
+        None => failure_package,
+    }
+}
+
+/// Stable identifier of a failure group
+pub fn fingerprint(recipe: &str, failure_task: &str, error_type: &str) -> String {
+    let digest = Sha256::digest(format!("{}\n{}\n{}", recipe, failure_task, error_type));
+    hex::encode(digest)
+}
+
+/// Fingerprint of a group created by a split. It is never computed for a
+/// report, so only reports moved by hand end up in the group.
+fn split_fingerprint() -> String {
+    format!("split-{}", uuid::Uuid::new_v4().simple())
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CurationError {
+    #[error("Failure group {0} does not exist")]
+    GroupNotFound(i32),
+    #[error("A group cannot be merged into itself")]
+    SameGroup,
+    #[error("None of the selected reports belong to this group")]
+    NoReports,
+    #[error("Database error: {0}")]
+    Database(#[from] DbErr),
+}
+
+/// A failure group with its number of reports, for the admin list
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupSummary {
+    pub group: failure_group::Model,
+    pub report_count: i64,
+}
+
+/// Report counts and activity period of a failure group
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupActivity {
+    pub report_count: i64,
+    pub first_seen: Option<DateTime<Utc>>,
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+/// How often a failure group occurs on a build host distro compared with all
+/// reports submitted since the group first appeared
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HostCorrelation {
+    pub host: String,
+    pub count: u64,
+    /// Percentage of the group's reports from this host distro
+    pub share: f64,
+    pub baseline_count: u64,
+    /// Percentage of all reports from this host distro
+    pub baseline_share: f64,
+    /// `share / baseline_share`; above 1 the failure is over-represented on this host
+    pub lift: Option<f64>,
+}
+
+pub struct FailureGroupService {
+    db: DatabaseConnection,
+}
+
+impl FailureGroupService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Group a report fails in, creating the group on first sight
+    pub async fn resolve(
+        &self,
+        failure_package: &str,
+        failure_task: &str,
+        error_type: &str,
+    ) -> Result<failure_group::Model, DbErr> {
+        let recipe = recipe_name(failure_package);
+        let fingerprint = fingerprint(recipe, failure_task, error_type);
+
+        // Fingerprints of merged groups, and those added by hand, are redirected
+        if let Some(group) = FailureGroupRule::find()
+            .filter(failure_group_rule::Column::Fingerprint.eq(&fingerprint))
+            .find_also_related(FailureGroup)
+            .one(&self.db)
+            .await?
+            .and_then(|(_, group)| group)
+        {
+            return Ok(group);
+        }
+
+        // Concurrent submissions of a new failure race on the unique fingerprint
+        FailureGroup::insert(failure_group::ActiveModel {
+            created_at: Set(Utc::now()),
+            fingerprint: Set(fingerprint.clone()),
+            recipe: Set(recipe.to_string()),
+            failure_task: Set(failure_task.to_string()),
+            error_type: Set(error_type.to_string()),
+            ..Default::default()
+        })
+        .on_conflict(
+            OnConflict::column(failure_group::Column::Fingerprint)
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(&self.db)
+        .await?;
+
+        FailureGroup::find()
+            .filter(failure_group::Column::Fingerprint.eq(&fingerprint))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound(format!("failure group {}", fingerprint)))
+    }
+
+    pub async fn activity(&self, group_id: i32) -> Result<GroupActivity, DbErr> {
+        let (report_count, first_seen, last_seen) = ErrorReport::find()
+            .select_only()
+            .column_as(error_report::Column::Id.count(), "report_count")
+            .column_as(error_report::Column::CreatedAt.min(), "first_seen")
+            .column_as(error_report::Column::CreatedAt.max(), "last_seen")
+            .filter(error_report::Column::FailureGroupId.eq(group_id))
+            .filter(error_report::visible())
+            .into_tuple::<(i64, Option<DateTime<Utc>>, Option<DateTime<Utc>>)>()
+            .one(&self.db)
+            .await?
+            .unwrap_or((0, None, None));
+
+        Ok(GroupActivity {
+            report_count,
+            first_seen,
+            last_seen,
+        })
+    }
+
+    pub async fn get(&self, group_id: i32) -> Result<Option<failure_group::Model>, DbErr> {
+        FailureGroup::find_by_id(group_id).one(&self.db).await
+    }
+
+    /// Groups with the most reports first, optionally only those of recipes
+    /// starting with `recipe`
+    pub async fn list(&self, recipe: Option<&str>, limit: u64) -> Result<Vec<GroupSummary>, DbErr> {
+        let mut query = FailureGroup::find();
+        if let Some(recipe) = recipe.map(str::trim).filter(|recipe| !recipe.is_empty()) {
+            query = query.filter(failure_group::Column::Recipe.starts_with(recipe));
+        }
+
+        let rows = query
+            .select_only()
+            .column(failure_group::Column::Id)
+            .column_as(error_report::Column::Id.count(), "report_count")
+            .left_join(ErrorReport)
+            .group_by(failure_group::Column::Id)
+            .order_by_desc(Expr::col(Alias::new("report_count")))
+            .order_by_asc(failure_group::Column::Id)
+            .limit(limit)
+            .into_tuple::<(i32, i64)>()
+            .all(&self.db)
+            .await?;
+
+        let groups = FailureGroup::find()
+            .filter(failure_group::Column::Id.is_in(rows.iter().map(|(id, _)| *id)))
+            .all(&self.db)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(id, report_count)| {
+                groups
+                    .iter()
+                    .find(|group| group.id == id)
+                    .map(|group| GroupSummary {
+                        group: group.clone(),
+                        report_count,
+                    })
+            })
+            .collect())
+    }
+
+    /// Most recent reports of a group
+    pub async fn reports(
+        &self,
+        group_id: i32,
+        limit: u64,
+    ) -> Result<Vec<error_report::Model>, DbErr> {
+        ErrorReport::find()
+            .filter(error_report::Column::FailureGroupId.eq(group_id))
+            .order_by_desc(error_report::Column::Id)
+            .limit(limit)
+            .all(&self.db)
+            .await
+    }
+
+    /// Fingerprints redirected to a group
+    pub async fn rules(&self, group_id: i32) -> Result<Vec<failure_group_rule::Model>, DbErr> {
+        FailureGroupRule::find()
+            .filter(failure_group_rule::Column::FailureGroupId.eq(group_id))
+            .order_by_asc(failure_group_rule::Column::Recipe)
+            .order_by_asc(failure_group_rule::Column::Id)
+            .all(&self.db)
+            .await
+    }
+
+    /// Changes made to a group, including those it was merged from or split off
+    pub async fn events(&self, group_id: i32) -> Result<Vec<failure_group_event::Model>, DbErr> {
+        FailureGroupEvent::find()
+            .filter(
+                Condition::any()
+                    .add(failure_group_event::Column::FailureGroupId.eq(group_id))
+                    .add(failure_group_event::Column::OtherGroupId.eq(group_id)),
+            )
+            .order_by_desc(failure_group_event::Column::CreatedAt)
+            .order_by_desc(failure_group_event::Column::Id)
+            .all(&self.db)
+            .await
+    }
+
+    pub async fn recent_events(
+        &self,
+        limit: u64,
+    ) -> Result<Vec<failure_group_event::Model>, DbErr> {
+        FailureGroupEvent::find()
+            .order_by_desc(failure_group_event::Column::CreatedAt)
+            .order_by_desc(failure_group_event::Column::Id)
+            .limit(limit)
+            .all(&self.db)
+            .await
+    }
+
+    /// Move all reports, comments and rules of `source_id` to `target_id` and
+    /// delete the source group. A rule keeps sending reports with the source
+    /// fingerprint to the target. Bug links are stored on the reports, so
+    /// they move along with them.
+    pub async fn merge<C: ConnectionTrait + TransactionTrait>(
+        &self,
+        db: &C,
+        source_id: i32,
+        target_id: i32,
+        actor: Option<&str>,
+        note: Option<&str>,
+    ) -> Result<failure_group_event::Model, CurationError> {
+        if source_id == target_id {
+            return Err(CurationError::SameGroup);
+        }
+
+        let txn = db.begin().await?;
+        let source = FailureGroup::find_by_id(source_id)
+            .one(&txn)
+            .await?
+            .ok_or(CurationError::GroupNotFound(source_id))?;
+        FailureGroup::find_by_id(target_id)
+            .one(&txn)
+            .await?
+            .ok_or(CurationError::GroupNotFound(target_id))?;
+
+        let moved = ErrorReport::update_many()
+            .col_expr(error_report::Column::FailureGroupId, Expr::value(target_id))
+            .filter(error_report::Column::FailureGroupId.eq(source_id))
+            .exec(&txn)
+            .await?;
+        Comment::update_many()
+            .col_expr(comment::Column::FailureGroupId, Expr::value(target_id))
+            .filter(comment::Column::FailureGroupId.eq(source_id))
+            .exec(&txn)
+            .await?;
+        FailureGroupRule::update_many()
+            .col_expr(
+                failure_group_rule::Column::FailureGroupId,
+                Expr::value(target_id),
+            )
+            .filter(failure_group_rule::Column::FailureGroupId.eq(source_id))
+            .exec(&txn)
+            .await?;
+        // Alerts and saved searches about the merged group follow it, so their
+        // links don't lead to a deleted group
+        Alert::update_many()
+            .col_expr(
+                alert::Column::DimensionKey,
+                Expr::value(target_id.to_string()),
+            )
+            .filter(alert::Column::Dimension.eq(alert::DIMENSION_FAILURE_GROUP))
+            .filter(alert::Column::DimensionKey.eq(source_id.to_string()))
+            .exec(&txn)
+            .await?;
+        repoint_saved_searches(&txn, source_id, target_id).await?;
+
+        // Groups created by a split have no fingerprint of their own to redirect
+        if fingerprint(&source.recipe, &source.failure_task, &source.error_type)
+            == source.fingerprint
+        {
+            upsert_rule(
+                &txn,
+                &source.fingerprint,
+                target_id,
+                &source.recipe,
+                &source.failure_task,
+                &source.error_type,
+            )
+            .await?;
+        }
+
+        FailureGroup::delete_by_id(source_id).exec(&txn).await?;
+
+        let event = record_event(
+            &txn,
+            failure_group_event::ACTION_MERGE,
+            target_id,
+            Some(source_id),
+            moved.rows_affected as i32,
+            actor,
+            &describe(
+                &format!(
+                    "Merged {} {} ({})",
+                    source.recipe, source.failure_task, source.error_type
+                ),
+                note,
+            ),
+        )
+        .await?;
+        txn.commit().await?;
+
+        Ok(event)
+    }
+
+    /// Move the given reports of a group into a new group with the same
+    /// recipe, task and error type. Report IDs from other groups are ignored.
+    pub async fn split<C: ConnectionTrait + TransactionTrait>(
+        &self,
+        db: &C,
+        group_id: i32,
+        report_ids: &[i32],
+        actor: Option<&str>,
+        note: Option<&str>,
+    ) -> Result<failure_group::Model, CurationError> {
+        let txn = db.begin().await?;
+        let source = FailureGroup::find_by_id(group_id)
+            .one(&txn)
+            .await?
+            .ok_or(CurationError::GroupNotFound(group_id))?;
+
+        let group = failure_group::ActiveModel {
+            created_at: Set(Utc::now()),
+            fingerprint: Set(split_fingerprint()),
+            recipe: Set(source.recipe.clone()),
+            failure_task: Set(source.failure_task.clone()),
+            error_type: Set(source.error_type.clone()),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        let moved = ErrorReport::update_many()
+            .col_expr(error_report::Column::FailureGroupId, Expr::value(group.id))
+            .filter(error_report::Column::FailureGroupId.eq(group_id))
+            .filter(error_report::Column::Id.is_in(report_ids.iter().copied()))
+            .exec(&txn)
+            .await?;
+        if moved.rows_affected == 0 {
+            // Dropping the transaction rolls back the new group
+            return Err(CurationError::NoReports);
+        }
+
+        record_event(
+            &txn,
+            failure_group_event::ACTION_SPLIT,
+            group_id,
+            Some(group.id),
+            moved.rows_affected as i32,
+            actor,
+            &describe(&format!("Split into group {}", group.id), note),
+        )
+        .await?;
+        txn.commit().await?;
+
+        Ok(group)
+    }
+
+    /// Send future reports with the fingerprint of the given recipe, task and
+    /// error type to a group. An existing rule for the fingerprint is replaced.
+    pub async fn add_rule<C: ConnectionTrait + TransactionTrait>(
+        &self,
+        db: &C,
+        group_id: i32,
+        data: FailureGroupRuleData,
+    ) -> Result<failure_group_rule::Model, CurationError> {
+        let recipe = recipe_name(data.recipe.trim());
+        let failure_task = data.failure_task.trim();
+        let error_type = data.error_type.trim();
+
+        let txn = db.begin().await?;
+        FailureGroup::find_by_id(group_id)
+            .one(&txn)
+            .await?
+            .ok_or(CurationError::GroupNotFound(group_id))?;
+
+        let fingerprint = fingerprint(recipe, failure_task, error_type);
+        let rule = upsert_rule(
+            &txn,
+            &fingerprint,
+            group_id,
+            recipe,
+            failure_task,
+            error_type,
+        )
+        .await?;
+        record_event(
+            &txn,
+            failure_group_event::ACTION_ADD_RULE,
+            group_id,
+            None,
+            0,
+            data.actor.as_deref(),
+            &format!("Rule for {} {} ({})", recipe, failure_task, error_type),
+        )
+        .await?;
+        txn.commit().await?;
+
+        Ok(rule)
+    }
+
+    /// Remove a rule; reports with its fingerprint get their own group again.
+    /// Returns the group the rule pointed to.
+    pub async fn delete_rule<C: ConnectionTrait + TransactionTrait>(
+        &self,
+        db: &C,
+        rule_id: i32,
+        actor: Option<&str>,
+    ) -> Result<Option<i32>, DbErr> {
+        let txn = db.begin().await?;
+        let Some(rule) = FailureGroupRule::find_by_id(rule_id).one(&txn).await? else {
+            return Ok(None);
+        };
+
+        FailureGroupRule::delete_by_id(rule_id).exec(&txn).await?;
+        record_event(
+            &txn,
+            failure_group_event::ACTION_DELETE_RULE,
+            rule.failure_group_id,
+            None,
+            0,
+            actor,
+            &format!(
+                "Rule for {} {} ({})",
+                rule.recipe, rule.failure_task, rule.error_type
+            ),
+        )
+        .await?;
+        txn.commit().await?;
+
+        Ok(Some(rule.failure_group_id))
+    }
+
+    /// Host distros the group occurs on, against all reports since `since`
+    pub async fn host_correlation(
+        &self,
+        group_id: i32,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<HostCorrelation>, DbErr> {
+        let group_counts = self.host_counts(Some(group_id), since).await?;
+        let baseline_counts = self.host_counts(None, since).await?;
+
+        Ok(host_correlation(&group_counts, &baseline_counts))
+    }
+
+    /// Reports per host distro since `since`, for one group or for all reports
+    async fn host_counts(
+        &self,
+        group_id: Option<i32>,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<(String, i64)>, DbErr> {
+        let mut query = ErrorReport::find().filter(error_report::visible());
+        if let Some(group_id) = group_id {
+            query = query.filter(error_report::Column::FailureGroupId.eq(group_id));
+        }
+
+        query
+            .select_only()
+            .column(error_report::Column::Nativelsbstring)
+            .column_as(error_report::Column::Id.count(), "count")
+            .filter(error_report::Column::CreatedAt.gte(since))
+            .group_by(error_report::Column::Nativelsbstring)
+            .order_by_desc(Expr::col(Alias::new("count")))
+            .into_tuple::<(String, i64)>()
+            .all(&self.db)
+            .await
+    }
+}
+
+async fn upsert_rule<C: ConnectionTrait>(
+    db: &C,
+    fingerprint: &str,
+    group_id: i32,
+    recipe: &str,
+    failure_task: &str,
+    error_type: &str,
+) -> Result<failure_group_rule::Model, DbErr> {
+    FailureGroupRule::insert(failure_group_rule::ActiveModel {
+        created_at: Set(Utc::now()),
+        fingerprint: Set(fingerprint.to_string()),
+        failure_group_id: Set(group_id),
+        recipe: Set(recipe.to_string()),
+        failure_task: Set(failure_task.to_string()),
+        error_type: Set(error_type.to_string()),
+        ..Default::default()
+    })
+    .on_conflict(
+        OnConflict::column(failure_group_rule::Column::Fingerprint)
+            .update_columns([
+                failure_group_rule::Column::FailureGroupId,
+                failure_group_rule::Column::CreatedAt,
+            ])
+            .to_owned(),
+    )
+    .exec_with_returning(db)
+    .await
+}
+
+async fn record_event<C: ConnectionTrait>(
+    db: &C,
+    action: &str,
+    group_id: i32,
+    other_group_id: Option<i32>,
+    report_count: i32,
+    actor: Option<&str>,
+    details: &str,
+) -> Result<failure_group_event::Model, DbErr> {
+    failure_group_event::ActiveModel {
+        created_at: Set(Utc::now()),
+        action: Set(action.to_string()),
+        failure_group_id: Set(group_id),
+        other_group_id: Set(other_group_id),
+        report_count: Set(report_count),
+        actor: Set(actor
+            .map(str::trim)
+            .filter(|actor| !actor.is_empty())
+            .map(str::to_string)),
+        details: Set(details.to_string()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+}
+
+/// Event details followed by the note given by the triager, if any
+fn describe(summary: &str, note: Option<&str>) -> String {
+    match note.map(str::trim).filter(|note| !note.is_empty()) {
+        Some(note) => format!("{}: {}", summary, note),
+        None => summary.to_string(),
+    }
+}
+
+/// Compare the host distribution of a group with the baseline. Every baseline
+/// host is listed, so hosts the failure never occurs on show up with a zero count.
+pub fn host_correlation(
+    group_counts: &[(String, i64)],
+    baseline_counts: &[(String, i64)],
+) -> Vec<HostCorrelation> {
+    let group_total: i64 = group_counts.iter().map(|(_, count)| count).sum();
+    let baseline_total: i64 = baseline_counts.iter().map(|(_, count)| count).sum();
+    let percentage = |count: i64, total: i64| {
+        if total > 0 {
+            count as f64 * 100.0 / total as f64
+        } else {
+            0.0
+        }
+    };
+
+    let group: HashMap<&str, i64> = group_counts
+        .iter()
+        .map(|(host, count)| (host.as_str(), *count))
+        .collect();
+    let mut baseline: HashMap<&str, i64> = baseline_counts
+        .iter()
+        .map(|(host, count)| (host.as_str(), *count))
+        .collect();
+    for (host, count) in &group {
+        baseline.entry(host).or_insert(*count);
+    }
+
+    let mut correlations: Vec<HostCorrelation> = baseline
+        .into_iter()
+        .map(|(host, baseline_count)| {
+            let count = group.get(host).copied().unwrap_or(0);
+            let share = percentage(count, group_total);
+            let baseline_share = percentage(baseline_count, baseline_total);
+            HostCorrelation {
+                host: host.to_string(),
+                count: count as u64,
+                share,
+                baseline_count: baseline_count as u64,
+                baseline_share,
+                lift: (baseline_share > 0.0).then(|| share / baseline_share),
+            }
+        })
+        .collect();
+
+    correlations.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then(b.baseline_count.cmp(&a.baseline_count))
+            .then(a.host.cmp(&b.host))
+    });
+    correlations
+}
+
+/// Point the `failure_group` filter of saved searches at the group it was merged into
+async fn repoint_saved_searches<C: ConnectionTrait>(
+    db: &C,
+    source_id: i32,
+    target_id: i32,
+) -> Result<(), DbErr> {
+    let searches = SavedSearch::find()
+        .filter(saved_search::Column::Query.contains(format!("failure_group={}", source_id)))
+        .all(db)
+        .await?;
+
+    for search in searches {
+        let Some(query) = replace_group_filter(&search.query, source_id, target_id) else {
+            continue;
+        };
+        let mut search: saved_search::ActiveModel = search.into();
+        search.query = Set(query);
+        search.update(db).await?;
+    }
+    Ok(())
+}
+
+/// `query` with its `failure_group` filter changed from `source_id` to
+/// `target_id`, or `None` when it doesn't filter on `source_id`
+fn replace_group_filter(query: &str, source_id: i32, target_id: i32) -> Option<String> {
+    let mut pairs: Vec<(String, String)> = serde_urlencoded::from_str(query).ok()?;
+    let source_id = source_id.to_string();
+    let mut changed = false;
+    for (name, value) in &mut pairs {
+        if name == "failure_group" && *value == source_id {
+            *value = target_id.to_string();
+            changed = true;
+        }
+    }
+    if !changed {
+        return None;
+    }
+    serde_urlencoded::to_string(pairs).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_group_filter() {
+        assert_eq!(
+            replace_group_filter("machine=qemuarm&failure_group=12&sort=-id", 12, 3).as_deref(),
+            Some("machine=qemuarm&failure_group=3&sort=-id")
+        );
+        // A different group whose ID starts with the same digits
+        assert_eq!(replace_group_filter("failure_group=123", 12, 3), None);
+        assert_eq!(replace_group_filter("machine=qemuarm", 12, 3), None);
+    }
+
+    #[test]
+    fn test_recipe_name() {
+        assert_eq!(recipe_name("busybox-1.36.1-r0"), "busybox");
+        assert_eq!(
+            recipe_name("gcc-cross-x86_64-13.2.0-r5"),
+            "gcc-cross-x86_64"
+        );
+        assert_eq!(recipe_name("python3-3.12.3-r0"), "python3");
+        assert_eq!(recipe_name("example-test-package"), "example-test-package");
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_version() {
+        let a = fingerprint(recipe_name("busybox-1.36.1-r0"), "do_compile", "Error");
+        let b = fingerprint(recipe_name("busybox-1.37.0-r1"), "do_compile", "Error");
+        assert_eq!(a, b);
+        assert_eq!(a.len(), 64);
+        assert_ne!(a, fingerprint("busybox", "do_install", "Error"));
+    }
+
+    #[test]
+    fn test_split_fingerprint() {
+        let fingerprint = split_fingerprint();
+        assert!(fingerprint.starts_with("split-"));
+        assert!(fingerprint.len() <= 64);
+        assert_ne!(fingerprint, split_fingerprint());
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(describe("Split into group 4", None), "Split into group 4");
+        assert_eq!(
+            describe("Split into group 4", Some("  ")),
+            "Split into group 4"
+        );
+        assert_eq!(
+            describe("Split into group 4", Some("different root cause")),
+            "Split into group 4: different root cause"
+        );
+    }
+
+    #[test]
+    fn test_host_correlation() {
+        let group = vec![
+            ("fedora-41".to_string(), 9),
+            ("ubuntu-24.04".to_string(), 1),
+        ];
+        let baseline = vec![
+            ("ubuntu-24.04".to_string(), 70),
+            ("fedora-41".to_string(), 20),
+            ("debian-12".to_string(), 10),
+        ];
+
+        let correlations = host_correlation(&group, &baseline);
+        let hosts: Vec<&str> = correlations.iter().map(|c| c.host.as_str()).collect();
+        assert_eq!(hosts, vec!["fedora-41", "ubuntu-24.04", "debian-12"]);
+
+        assert_eq!(correlations[0].share, 90.0);
+        assert_eq!(correlations[0].baseline_share, 20.0);
+        assert_eq!(correlations[0].lift, Some(4.5));
+        assert_eq!(correlations[2].count, 0);
+        assert_eq!(correlations[2].lift, Some(0.0));
+    }
+}
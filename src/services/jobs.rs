@@ -0,0 +1,472 @@
+use chrono::{Duration, Utc};
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend,
+    DbErr, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set, Statement, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::{
+    config::{EmailVisibility, JobsConfig},
+    models::{alert, job, Alert, ErrorReport, Job},
+    services::{
+        alerts::alert_message,
+        known_issues::KnownIssueService,
+        notifications::{NotificationError, Notifier},
+        saved_searches::{confirmation_message, saved_search_message, SavedSearchService},
+    },
+};
+
+/// Atomically claim the next due job. Jobs left `running` by a worker that died
+/// are picked up again once their lock is stale.
+const CLAIM_NEXT_JOB_SQL: &str = r#"
+UPDATE jobs
+SET status = 'running', locked_at = NOW(), attempts = attempts + 1
+WHERE id = (
+    SELECT id FROM jobs
+    WHERE (status = 'pending' AND run_at <= NOW())
+       OR (status = 'running' AND locked_at < NOW() - INTERVAL '15 minutes')
+    ORDER BY run_at, id
+    LIMIT 1
+    FOR UPDATE SKIP LOCKED
+)
+RETURNING *
+"#;
+
+/// Work queued for the background workers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JobPayload {
+    /// Match a newly submitted report against the known issues
+    AnnotateKnownIssues { error_report_id: i32 },
+    /// Email a volume spike alert to the alert recipients
+    NotifyAlert { alert_id: i32 },
+    /// Queue notifications for the subscribed saved searches a new report matches
+    MatchSavedSearches { error_report_id: i32 },
+    /// Split into one `NotifySubscription` job per subscriber. Only queued by
+    /// earlier versions, which emailed every subscriber from one job.
+    NotifySavedSearch {
+        saved_search_id: i32,
+        error_report_id: i32,
+    },
+    /// Email one subscriber of a saved search about a matching report
+    NotifySubscription {
+        subscription_id: i32,
+        error_report_id: i32,
+    },
+    /// Ask the owner of an address to confirm a saved search subscription
+    ConfirmSubscription { subscription_id: i32 },
+}
+
+impl JobPayload {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            JobPayload::AnnotateKnownIssues { .. } => "annotate_known_issues",
+            JobPayload::NotifyAlert { .. } => "notify_alert",
+            JobPayload::MatchSavedSearches { .. } => "match_saved_searches",
+            JobPayload::NotifySavedSearch { .. } => "notify_saved_search",
+            JobPayload::NotifySubscription { .. } => "notify_subscription",
+            JobPayload::ConfirmSubscription { .. } => "confirm_subscription",
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum JobError {
+    #[error("Invalid job payload: {0}")]
+    InvalidPayload(#[from] serde_json::Error),
+    #[error("Database error: {0}")]
+    Database(#[from] DbErr),
+    #[error("Notification failed: {0}")]
+    Notification(#[from] NotificationError),
+    #[error("No notifier configured for this worker")]
+    NotifierUnavailable,
+}
+
+/// Number of jobs in each state, shown on the admin jobs page
+#[derive(Debug, Default, Serialize)]
+pub struct JobCounts {
+    pub pending: u64,
+    pub running: u64,
+    pub dead: u64,
+}
+
+#[derive(Clone)]
+pub struct JobService {
+    db: DatabaseConnection,
+    config: JobsConfig,
+    notifier: Option<Notifier>,
+    email_visibility: Option<EmailVisibility>,
+}
+
+impl JobService {
+    pub fn new(db: DatabaseConnection, config: JobsConfig) -> Self {
+        Self {
+            db,
+            config,
+            notifier: None,
+            email_visibility: None,
+        }
+    }
+
+    /// Notifier used by workers to deliver alert notifications
+    pub fn with_notifier(mut self, notifier: Notifier) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// How submitter emails are shown publicly, which decides whether saved
+    /// searches may match new reports by email address
+    pub fn with_email_visibility(mut self, email_visibility: EmailVisibility) -> Self {
+        self.email_visibility = Some(email_visibility);
+        self
+    }
+
+    /// Queue a job on the given connection, so a job for a change commits with it
+    pub async fn enqueue<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        payload: &JobPayload,
+    ) -> Result<job::Model, DbErr> {
+        let now = Utc::now();
+        let serialized =
+            serde_json::to_string(payload).map_err(|e| DbErr::Custom(e.to_string()))?;
+
+        job::ActiveModel {
+            created_at: Set(now),
+            kind: Set(payload.kind().to_string()),
+            payload: Set(serialized),
+            status: Set(job::STATUS_PENDING.to_string()),
+            attempts: Set(0),
+            max_attempts: Set(self.config.max_attempts),
+            run_at: Set(now),
+            locked_at: Set(None),
+            last_error: Set(None),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+    }
+
+    pub async fn counts(&self) -> Result<JobCounts, DbErr> {
+        let rows = Job::find()
+            .select_only()
+            .column(job::Column::Status)
+            .column_as(job::Column::Id.count(), "count")
+            .group_by(job::Column::Status)
+            .into_tuple::<(String, i64)>()
+            .all(&self.db)
+            .await?;
+
+        let mut counts = JobCounts::default();
+        for (status, count) in rows {
+            match status.as_str() {
+                job::STATUS_PENDING => counts.pending = count as u64,
+                job::STATUS_RUNNING => counts.running = count as u64,
+                job::STATUS_DEAD => counts.dead = count as u64,
+                _ => {}
+            }
+        }
+        Ok(counts)
+    }
+
+    /// Jobs that exhausted their retries, most recent first
+    pub async fn dead_jobs(&self) -> Result<Vec<job::Model>, DbErr> {
+        Job::find()
+            .filter(job::Column::Status.eq(job::STATUS_DEAD))
+            .order_by_desc(job::Column::RunAt)
+            .all(&self.db)
+            .await
+    }
+
+    /// Put a dead job back in the queue with a fresh set of attempts
+    pub async fn retry<C: ConnectionTrait>(&self, db: &C, id: i32) -> Result<bool, DbErr> {
+        let result = Job::update_many()
+            .col_expr(job::Column::Status, Expr::value(job::STATUS_PENDING))
+            .col_expr(job::Column::Attempts, Expr::value(0))
+            .col_expr(job::Column::RunAt, Expr::value(Utc::now()))
+            .col_expr(
+                job::Column::LockedAt,
+                Expr::value(Option::<chrono::DateTime<Utc>>::None),
+            )
+            .filter(job::Column::Id.eq(id))
+            .filter(job::Column::Status.eq(job::STATUS_DEAD))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    pub async fn delete<C: ConnectionTrait>(&self, db: &C, id: i32) -> Result<bool, DbErr> {
+        let result = Job::delete_many()
+            .filter(job::Column::Id.eq(id))
+            .filter(job::Column::Status.eq(job::STATUS_DEAD))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Process jobs until the process exits, sleeping while the queue is empty
+    pub async fn run_worker(self, worker_id: usize) {
+        let poll_interval = std::time::Duration::from_secs(self.config.poll_interval_secs);
+        info!("Job worker {} started", worker_id);
+
+        loop {
+            match self.claim_next().await {
+                Ok(Some(job)) => self.process(job).await,
+                Ok(None) => tokio::time::sleep(poll_interval).await,
+                Err(e) => {
+                    error!("Job worker {} failed to claim a job: {:?}", worker_id, e);
+                    tokio::time::sleep(poll_interval).await;
+                }
+            }
+        }
+    }
+
+    async fn claim_next(&self) -> Result<Option<job::Model>, DbErr> {
+        Job::find()
+            .from_raw_sql(Statement::from_string(
+                DbBackend::Postgres,
+                CLAIM_NEXT_JOB_SQL,
+            ))
+            .one(&self.db)
+            .await
+    }
+
+    async fn process(&self, job: job::Model) {
+        let result = match serde_json::from_str::<JobPayload>(&job.payload) {
+            Ok(payload) => self.execute(payload).await,
+            Err(e) => Err(e.into()),
+        };
+
+        let outcome = match result {
+            // Finished jobs are removed; only failures are kept for inspection
+            Ok(()) => Job::delete_by_id(job.id).exec(&self.db).await.map(|_| ()),
+            Err(e) => self.record_failure(&job, &e).await,
+        };
+
+        if let Err(e) = outcome {
+            error!("Failed to update job {}: {:?}", job.id, e);
+        }
+    }
+
+    async fn execute(&self, payload: JobPayload) -> Result<(), JobError> {
+        match payload {
+            JobPayload::AnnotateKnownIssues { error_report_id } => {
+                // The report may have been deleted since the job was queued
+                let Some(report) = ErrorReport::find_by_id(error_report_id)
+                    .one(&self.db)
+                    .await?
+                else {
+                    return Ok(());
+                };
+
+                KnownIssueService::new(self.db.clone())
+                    .annotate_report(&report)
+                    .await?;
+                Ok(())
+            }
+            JobPayload::NotifyAlert { alert_id } => {
+                let Some(alert) = Alert::find_by_id(alert_id).one(&self.db).await? else {
+                    return Ok(());
+                };
+                // Already delivered by an earlier attempt
+                if alert.notified_at.is_some() {
+                    return Ok(());
+                }
+
+                let notifier = self
+                    .notifier
+                    .as_ref()
+                    .ok_or(JobError::NotifierUnavailable)?;
+                let (subject, body) = alert_message(&alert, notifier.base_url());
+                notifier.send(&subject, &body).await?;
+
+                let mut active: alert::ActiveModel = alert.into();
+                active.notified_at = Set(Some(Utc::now()));
+                active.update(&self.db).await?;
+                Ok(())
+            }
+            JobPayload::MatchSavedSearches { error_report_id } => {
+                let Some(report) = ErrorReport::find_by_id(error_report_id)
+                    .one(&self.db)
+                    .await?
+                else {
+                    return Ok(());
+                };
+
+                let mut saved_searches = SavedSearchService::new(self.db.clone());
+                if let Some(email_visibility) = self.email_visibility {
+                    saved_searches = saved_searches.with_email_visibility(email_visibility);
+                }
+                let searches = saved_searches.subscribed_matches(&report).await?;
+                let search_ids: Vec<i32> = searches.iter().map(|search| search.id).collect();
+                self.enqueue_notifications(&saved_searches, &search_ids, error_report_id)
+                    .await?;
+                Ok(())
+            }
+            JobPayload::NotifySavedSearch {
+                saved_search_id,
+                error_report_id,
+            } => {
+                let saved_searches = SavedSearchService::new(self.db.clone());
+                self.enqueue_notifications(&saved_searches, &[saved_search_id], error_report_id)
+                    .await?;
+                Ok(())
+            }
+            JobPayload::NotifySubscription {
+                subscription_id,
+                error_report_id,
+            } => {
+                let saved_searches = SavedSearchService::new(self.db.clone());
+                // Unsubscribed since the job was queued
+                let Some(subscription) = saved_searches.get_subscription(subscription_id).await?
+                else {
+                    return Ok(());
+                };
+                let Some(search) = saved_searches.get(subscription.saved_search_id).await? else {
+                    return Ok(());
+                };
+                let Some(report) = ErrorReport::find_by_id(error_report_id)
+                    .one(&self.db)
+                    .await?
+                else {
+                    return Ok(());
+                };
+
+                let notifier = self
+                    .notifier
+                    .as_ref()
+                    .ok_or(JobError::NotifierUnavailable)?;
+                let (subject, body) =
+                    saved_search_message(&search, &report, &subscription, notifier.base_url());
+                notifier
+                    .send_to(std::slice::from_ref(&subscription.email), &subject, &body)
+                    .await?;
+                Ok(())
+            }
+            JobPayload::ConfirmSubscription { subscription_id } => {
+                let saved_searches = SavedSearchService::new(self.db.clone());
+                let Some(subscription) = saved_searches.get_subscription(subscription_id).await?
+                else {
+                    return Ok(());
+                };
+                if subscription.confirmed_at.is_some() {
+                    return Ok(());
+                }
+                let Some(search) = saved_searches.get(subscription.saved_search_id).await? else {
+                    return Ok(());
+                };
+
+                let notifier = self
+                    .notifier
+                    .as_ref()
+                    .ok_or(JobError::NotifierUnavailable)?;
+                let (subject, body) =
+                    confirmation_message(&search, &subscription, notifier.base_url());
+                notifier
+                    .send_to(std::slice::from_ref(&subscription.email), &subject, &body)
+                    .await?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Queue one notification per confirmed subscriber of the given searches.
+    /// Each delivery is retried on its own, so a failure never sends a message
+    /// twice to the subscribers that already got it. The jobs are queued
+    /// together, so a retry of this step doesn't queue any of them twice.
+    async fn enqueue_notifications(
+        &self,
+        saved_searches: &SavedSearchService,
+        search_ids: &[i32],
+        error_report_id: i32,
+    ) -> Result<(), DbErr> {
+        let txn = self.db.begin().await?;
+        for search_id in search_ids {
+            for subscription in saved_searches.subscribers(*search_id).await? {
+                self.enqueue(
+                    &txn,
+                    &JobPayload::NotifySubscription {
+                        subscription_id: subscription.id,
+                        error_report_id,
+                    },
+                )
+                .await?;
+            }
+        }
+        txn.commit().await
+    }
+
+    async fn record_failure(&self, job: &job::Model, e: &JobError) -> Result<(), DbErr> {
+        let dead = job.attempts >= job.max_attempts;
+        if dead {
+            error!(
+                "Job {} ({}) failed after {} attempts: {}",
+                job.id, job.kind, job.attempts, e
+            );
+        } else {
+            warn!(
+                "Job {} ({}) failed on attempt {}: {}",
+                job.id, job.kind, job.attempts, e
+            );
+        }
+
+        let mut active: job::ActiveModel = job.clone().into();
+        active.status = Set(if dead {
+            job::STATUS_DEAD
+        } else {
+            job::STATUS_PENDING
+        }
+        .to_string());
+        active.run_at = Set(Utc::now() + retry_delay(job.attempts));
+        active.locked_at = Set(None);
+        active.last_error = Set(Some(e.to_string()));
+        active.update(&self.db).await.map(|_| ())
+    }
+}
+
+/// Exponential backoff between attempts: 30s, 1m, 2m, ... capped at one hour
+pub fn retry_delay(attempts: i32) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 7) as u32;
+    Duration::seconds(30 * 2_i64.pow(exponent)).min(Duration::hours(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_roundtrip() {
+        let payload = JobPayload::AnnotateKnownIssues {
+            error_report_id: 42,
+        };
+        let json = serde_json::to_string(&payload).unwrap();
+        assert_eq!(
+            json,
+            r#"{"kind":"annotate_known_issues","error_report_id":42}"#
+        );
+        assert_eq!(serde_json::from_str::<JobPayload>(&json).unwrap(), payload);
+    }
+
+    #[test]
+    fn test_notify_subscription_payload() {
+        let payload = JobPayload::NotifySubscription {
+            subscription_id: 7,
+            error_report_id: 42,
+        };
+        let json = serde_json::to_string(&payload).unwrap();
+        assert_eq!(
+            json,
+            r#"{"kind":"notify_subscription","subscription_id":7,"error_report_id":42}"#
+        );
+        assert_eq!(payload.kind(), "notify_subscription");
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(1), Duration::seconds(30));
+        assert_eq!(retry_delay(2), Duration::seconds(60));
+        assert_eq!(retry_delay(4), Duration::seconds(240));
+        assert_eq!(retry_delay(20), Duration::hours(1));
+    }
+}
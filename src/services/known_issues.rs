@@ -0,0 +1,286 @@
+use chrono::Utc;
+use regex::Regex;
+use sea_orm::{
+    sea_query::OnConflict, ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection,
+    DbErr, EntityTrait, ModelTrait, QueryFilter, QueryOrder, Set,
+};
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
+use tracing::warn;
+
+use crate::models::{
+    error_report, error_report_known_issue,
+    known_issue::{self, KnownIssueData},
+    ErrorReportKnownIssue, KnownIssue,
+};
+
+/// Compiled patterns kept before the cache is emptied; edited patterns are
+/// cached under their new text and the old entries are not removed otherwise
+const PATTERN_CACHE_LIMIT: usize = 1000;
+
+/// Known issue patterns compiled by their text, shared by all annotation runs
+static PATTERN_CACHE: OnceLock<Mutex<HashMap<String, Result<Regex, regex::Error>>>> =
+    OnceLock::new();
+
+pub struct KnownIssueService {
+    db: DatabaseConnection,
+}
+
+impl KnownIssueService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    pub async fn list(&self) -> Result<Vec<known_issue::Model>, DbErr> {
+        KnownIssue::find()
+            .order_by_asc(known_issue::Column::Title)
+            .all(&self.db)
+            .await
+    }
+
+    pub async fn get(&self, id: i32) -> Result<Option<known_issue::Model>, DbErr> {
+        KnownIssue::find_by_id(id).one(&self.db).await
+    }
+
+    pub async fn create<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        data: KnownIssueData,
+    ) -> Result<known_issue::Model, DbErr> {
+        let mut issue = known_issue::ActiveModel {
+            created_at: Set(Utc::now()),
+            ..Default::default()
+        };
+        apply_form(&mut issue, data);
+        issue.insert(db).await
+    }
+
+    pub async fn update<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        id: i32,
+        data: KnownIssueData,
+    ) -> Result<Option<known_issue::Model>, DbErr> {
+        let Some(existing) = KnownIssue::find_by_id(id).one(db).await? else {
+            return Ok(None);
+        };
+
+        let mut issue: known_issue::ActiveModel = existing.into();
+        apply_form(&mut issue, data);
+        issue.update(db).await.map(Some)
+    }
+
+    pub async fn delete<C: ConnectionTrait>(&self, db: &C, id: i32) -> Result<bool, DbErr> {
+        let result = KnownIssue::delete_by_id(id).exec(db).await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Match a newly submitted report against all enabled known issues and
+    /// record every hit so the detail page can show the workaround.
+    pub async fn annotate_report(
+        &self,
+        report: &error_report::Model,
+    ) -> Result<Vec<known_issue::Model>, DbErr> {
+        let issues = KnownIssue::find()
+            .filter(known_issue::Column::Enabled.eq(true))
+            .all(&self.db)
+            .await?;
+
+        let matched: Vec<known_issue::Model> = issues
+            .into_iter()
+            .filter(|issue| issue_matches(issue, report))
+            .collect();
+
+        for issue in &matched {
+            // Annotation may be retried, so existing matches are left untouched
+            ErrorReportKnownIssue::insert(error_report_known_issue::ActiveModel {
+                error_report_id: Set(report.id),
+                known_issue_id: Set(issue.id),
+                matched_at: Set(Utc::now()),
+                ..Default::default()
+            })
+            .on_conflict(
+                OnConflict::columns([
+                    error_report_known_issue::Column::ErrorReportId,
+                    error_report_known_issue::Column::KnownIssueId,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(&self.db)
+            .await?;
+        }
+
+        Ok(matched)
+    }
+
+    /// Known issues previously matched against the given report
+    pub async fn issues_for_report(
+        &self,
+        report: &error_report::Model,
+    ) -> Result<Vec<known_issue::Model>, DbErr> {
+        report
+            .find_related(KnownIssue)
+            .order_by_asc(known_issue::Column::Title)
+            .all(&self.db)
+            .await
+    }
+}
+
+fn apply_form(issue: &mut known_issue::ActiveModel, data: KnownIssueData) {
+    issue.title = Set(data.title.trim().to_string());
+    issue.explanation = Set(data.explanation.trim().to_string());
+    issue.link = Set(non_empty(data.link));
+    issue.error_details_pattern = Set(non_empty(data.error_details_pattern));
+    issue.log_data_pattern = Set(non_empty(data.log_data_pattern));
+    issue.failure_task_pattern = Set(non_empty(data.failure_task_pattern));
+    // HTML checkboxes are only submitted when ticked
+    issue.enabled = Set(data.enabled.is_some());
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// A known issue matches when every pattern it defines matches the
+/// corresponding report field. Issues without any pattern never match.
+pub fn issue_matches(issue: &known_issue::Model, report: &error_report::Model) -> bool {
+    let checks = [
+        (&issue.error_details_pattern, &report.error_details),
+        (&issue.log_data_pattern, &report.log_data),
+        (&issue.failure_task_pattern, &report.failure_task),
+    ];
+
+    let mut any_pattern = false;
+    for (pattern, haystack) in checks {
+        let Some(pattern) = pattern else {
+            continue;
+        };
+        any_pattern = true;
+
+        match compile_pattern(pattern) {
+            Ok(re) if re.is_match(haystack) => {}
+            Ok(_) => return false,
+            Err(e) => {
+                warn!(
+                    "Skipping known issue {} with invalid pattern: {}",
+                    issue.id, e
+                );
+                return false;
+            }
+        }
+    }
+
+    any_pattern
+}
+
+/// Compile a pattern, or reuse it when an earlier report was matched against it
+fn compile_pattern(pattern: &str) -> Result<Regex, regex::Error> {
+    let cache = PATTERN_CACHE.get_or_init(Default::default);
+    let mut cache = cache
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(compiled) = cache.get(pattern) {
+        return compiled.clone();
+    }
+
+    if cache.len() >= PATTERN_CACHE_LIMIT {
+        cache.clear();
+    }
+    let compiled = Regex::new(pattern);
+    cache.insert(pattern.to_string(), compiled.clone());
+    compiled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_report() -> error_report::Model {
+        error_report::Model {
+            id: 1,
+            created_at: Utc::now(),
+            machine: "qemux86-64".to_string(),
+            distro: "poky".to_string(),
+            distro_version: "4.0".to_string(),
+            build_sys: "x86_64-linux".to_string(),
+            nativelsbstring: "ubuntu-24.04".to_string(),
+            target_sys: "x86_64-poky-linux".to_string(),
+            failure_task: "do_compile".to_string(),
+            failure_package: "binutils-native".to_string(),
+            error_type: "CompilationError".to_string(),
+            error_details: "error: 'uint64_t' does not name a type".to_string(),
+            log_data: "ERROR: No space left on device".to_string(),
+            submitter_name: None,
+            submitter_email: None,
+            bugzilla_link: None,
+            branch_commit: "master:abc123".to_string(),
+            failure_group_id: None,
+            hidden_at: None,
+            hidden_reason: None,
+            submitter_ip: None,
+            submitter_token: None,
+        }
+    }
+
+    fn create_issue() -> known_issue::Model {
+        known_issue::Model {
+            id: 1,
+            created_at: Utc::now(),
+            title: "Disk full".to_string(),
+            explanation: "Free up space in TMPDIR".to_string(),
+            link: None,
+            error_details_pattern: None,
+            log_data_pattern: Some(r"No space left on device".to_string()),
+            failure_task_pattern: None,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_single_pattern_match() {
+        assert!(issue_matches(&create_issue(), &create_report()));
+    }
+
+    #[test]
+    fn test_all_patterns_must_match() {
+        let mut issue = create_issue();
+        issue.failure_task_pattern = Some("^do_fetch$".to_string());
+        assert!(!issue_matches(&issue, &create_report()));
+
+        issue.failure_task_pattern = Some("^do_(compile|configure)$".to_string());
+        assert!(issue_matches(&issue, &create_report()));
+    }
+
+    #[test]
+    fn test_issue_without_patterns_never_matches() {
+        let mut issue = create_issue();
+        issue.log_data_pattern = None;
+        assert!(!issue_matches(&issue, &create_report()));
+    }
+
+    #[test]
+    fn test_invalid_pattern_does_not_match() {
+        let mut issue = create_issue();
+        issue.log_data_pattern = Some("(unclosed".to_string());
+        assert!(!issue_matches(&issue, &create_report()));
+    }
+
+    #[test]
+    fn test_compile_pattern_is_cached() {
+        let pattern = r"^do_compile_ptest_base$";
+        let first = compile_pattern(pattern).unwrap();
+        assert!(PATTERN_CACHE
+            .get()
+            .unwrap()
+            .lock()
+            .unwrap()
+            .contains_key(pattern));
+        assert_eq!(compile_pattern(pattern).unwrap().as_str(), first.as_str());
+        assert!(compile_pattern("(unclosed").is_err());
+    }
+}
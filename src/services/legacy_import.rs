@@ -0,0 +1,682 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait,
+    FromQueryResult, QueryFilter, QuerySelect, Set, Statement, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::HashMap, path::Path};
+
+use crate::{
+    models::{
+        build_configuration::{self, LayerInfo},
+        error_report, report_redaction, BuildConfiguration, ErrorReport, ReportRedaction,
+    },
+    services::failure_groups::{fingerprint, recipe_name, FailureGroupService},
+    utils::redaction::Redactor,
+};
+
+/// Legacy failures imported per transaction
+const IMPORT_BATCH_SIZE: usize = 500;
+
+/// Failures of a loaded legacy database joined with their build, as JSON objects
+/// keyed like the `dumpdata` fields. `{build}` and `{failure}` are replaced by
+/// validated table names.
+const LEGACY_ROWS_SQL: &str = r#"
+SELECT f.id AS failure_id, row_to_json(f)::text AS failure, row_to_json(b)::text AS build
+FROM {failure} f
+LEFT JOIN {build} b ON b.id = f."BUILD_id"
+WHERE f.id > $1
+ORDER BY f.id
+LIMIT $2
+"#;
+
+/// Width of the `error_reports` columns legacy values are checked against
+const COLUMN_LIMITS: &[(&str, usize)] = &[
+    ("machine", 100),
+    ("distro", 100),
+    ("distro_version", 50),
+    ("build_sys", 100),
+    ("nativelsbstring", 100),
+    ("target_sys", 100),
+    ("failure_task", 200),
+    ("failure_package", 200),
+    ("error_type", 100),
+    ("submitter_name", 100),
+    ("submitter_email", 200),
+    ("branch_commit", 100),
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("Failed to read dump: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid dumpdata JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Database error: {0}")]
+    Database(#[from] DbErr),
+    #[error("Invalid legacy table prefix: {0}")]
+    InvalidTablePrefix(String),
+}
+
+/// Where the legacy Django data is read from
+pub enum LegacySource<'a> {
+    /// JSON written by `manage.py dumpdata`
+    Dumpdata(&'a Path),
+    /// A PostgreSQL database the legacy SQL dump was loaded into, and the
+    /// prefix of its tables (the Django app label)
+    Database {
+        db: &'a DatabaseConnection,
+        table_prefix: &'a str,
+    },
+}
+
+/// `Build` model of the Django application
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+struct LegacyBuild {
+    date: String,
+    machine: String,
+    branch: String,
+    commit: String,
+    distro: String,
+    distro_version: Option<String>,
+    nativelsbstring: String,
+    build_sys: String,
+    target_sys: String,
+    name: Option<String>,
+    email: Option<String>,
+    error_type: Option<String>,
+    bitbake_version: Option<String>,
+    layer_version: Option<String>,
+}
+
+/// `BuildFailure` model of the Django application
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+struct LegacyFailure {
+    task: String,
+    recipe: String,
+    recipe_version: Option<String>,
+    error_details: String,
+}
+
+/// One legacy failure with the fields of its build
+#[derive(Debug)]
+pub struct LegacyRow {
+    pub failure_id: i32,
+    pub failure: Value,
+    pub build: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DumpObject {
+    model: String,
+    pk: Value,
+    fields: Value,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct LegacyDbRow {
+    failure_id: i32,
+    failure: String,
+    build: Option<String>,
+}
+
+/// A legacy failure that was not imported
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedRow {
+    pub failure_id: i32,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ImportProgress {
+    pub processed: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ImportSummary {
+    pub imported: usize,
+    /// Failures imported by an earlier run
+    pub already_present: usize,
+    /// Failures not imported, including those whose ID a native report already has
+    pub skipped: Vec<SkippedRow>,
+}
+
+/// The fields of a stored report that tell an imported failure from a native one
+#[derive(Debug, FromQueryResult)]
+struct ExistingReport {
+    id: i32,
+    created_at: DateTime<Utc>,
+    failure_task: String,
+    failure_package: String,
+    branch_commit: String,
+}
+
+impl ExistingReport {
+    /// Whether this report is the given legacy failure, imported by an earlier run
+    fn is_import_of(&self, report: &error_report::Model) -> bool {
+        self.created_at == report.created_at
+            && self.failure_task == report.failure_task
+            && self.failure_package == report.failure_package
+            && self.branch_commit == report.branch_commit
+    }
+}
+
+/// A legacy failure mapped onto the new schema
+#[derive(Debug, PartialEq)]
+struct MappedReport {
+    report: error_report::Model,
+    build_configuration: Option<build_configuration::Model>,
+}
+
+pub struct LegacyImportService {
+    db: DatabaseConnection,
+    redactor: Redactor,
+}
+
+impl LegacyImportService {
+    /// Imported logs go through the same redaction rules as new submissions
+    pub fn new(db: DatabaseConnection, redactor: Redactor) -> Self {
+        Self { db, redactor }
+    }
+
+    /// Import every legacy failure as an error report with the same ID, so
+    /// links to the old `/Errors/Details/<id>/` pages keep working
+    pub async fn import(
+        &self,
+        source: LegacySource<'_>,
+        mut on_progress: impl FnMut(ImportProgress),
+    ) -> Result<ImportSummary, ImportError> {
+        let mut summary = ImportSummary::default();
+        let mut group_ids = HashMap::new();
+
+        match source {
+            LegacySource::Dumpdata(path) => {
+                let rows = read_dumpdata(&std::fs::read_to_string(path)?)?;
+                let total = rows.len();
+                let mut processed = 0;
+                for batch in rows.chunks(IMPORT_BATCH_SIZE) {
+                    self.import_batch(batch, &mut group_ids, &mut summary)
+                        .await?;
+                    processed += batch.len();
+                    on_progress(ImportProgress { processed, total });
+                }
+            }
+            LegacySource::Database { db, table_prefix } => {
+                let (build_table, failure_table) = legacy_tables(table_prefix)?;
+                let total = db
+                    .query_one(Statement::from_string(
+                        DbBackend::Postgres,
+                        format!("SELECT COUNT(*) AS count FROM {}", failure_table),
+                    ))
+                    .await?
+                    .map(|row| row.try_get::<i64>("", "count"))
+                    .transpose()?
+                    .unwrap_or(0) as usize;
+
+                let sql = LEGACY_ROWS_SQL
+                    .replace("{build}", &build_table)
+                    .replace("{failure}", &failure_table);
+                let mut last_id = 0;
+                let mut processed = 0;
+                loop {
+                    let rows = LegacyDbRow::find_by_statement(Statement::from_sql_and_values(
+                        DbBackend::Postgres,
+                        sql.clone(),
+                        [last_id.into(), (IMPORT_BATCH_SIZE as i64).into()],
+                    ))
+                    .all(db)
+                    .await?;
+                    let Some(last) = rows.last() else {
+                        break;
+                    };
+                    last_id = last.failure_id;
+
+                    let batch: Vec<LegacyRow> = rows
+                        .into_iter()
+                        .map(|row| LegacyRow {
+                            failure_id: row.failure_id,
+                            failure: serde_json::from_str(&row.failure).unwrap_or(Value::Null),
+                            build: row
+                                .build
+                                .and_then(|build| serde_json::from_str(&build).ok()),
+                        })
+                        .collect();
+                    self.import_batch(&batch, &mut group_ids, &mut summary)
+                        .await?;
+                    processed += batch.len();
+                    on_progress(ImportProgress { processed, total });
+                }
+            }
+        }
+
+        // Explicit IDs bypass the sequences; new submissions continue after them
+        for table in ["error_reports", "build_configurations"] {
+            self.db
+                .execute(Statement::from_string(
+                    DbBackend::Postgres,
+                    format!(
+                        "SELECT setval(pg_get_serial_sequence('{table}', 'id'), \
+                         GREATEST((SELECT MAX(id) FROM {table}), 1))"
+                    ),
+                ))
+                .await?;
+        }
+
+        Ok(summary)
+    }
+
+    async fn import_batch(
+        &self,
+        rows: &[LegacyRow],
+        group_ids: &mut HashMap<String, i32>,
+        summary: &mut ImportSummary,
+    ) -> Result<(), DbErr> {
+        let existing: HashMap<i32, ExistingReport> = ErrorReport::find()
+            .select_only()
+            .columns([
+                error_report::Column::Id,
+                error_report::Column::CreatedAt,
+                error_report::Column::FailureTask,
+                error_report::Column::FailurePackage,
+                error_report::Column::BranchCommit,
+            ])
+            .filter(error_report::Column::Id.is_in(rows.iter().map(|row| row.failure_id)))
+            .into_model::<ExistingReport>()
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|report| (report.id, report))
+            .collect();
+
+        let mut reports = Vec::new();
+        let mut build_configurations = Vec::new();
+        let mut redactions = Vec::new();
+        for row in rows {
+            let mapped = match map_row(row) {
+                Ok(mapped) => mapped,
+                Err(reason) => {
+                    summary.skipped.push(SkippedRow {
+                        failure_id: row.failure_id,
+                        reason,
+                    });
+                    continue;
+                }
+            };
+
+            let mut report = mapped.report;
+            if let Some(existing) = existing.get(&report.id) {
+                if existing.is_import_of(&report) {
+                    summary.already_present += 1;
+                } else {
+                    summary.skipped.push(SkippedRow {
+                        failure_id: row.failure_id,
+                        reason: format!(
+                            "ID already used by a report submitted at {}",
+                            existing.created_at.to_rfc3339()
+                        ),
+                    });
+                }
+                continue;
+            }
+
+            // Strip secrets and personal data like a new submission would
+            let (error_details, mut hits) =
+                self.redactor.redact("error_details", &report.error_details);
+            let (log_data, log_hits) = self.redactor.redact("log_data", &report.log_data);
+            report.error_details = error_details;
+            report.log_data = log_data;
+            hits.extend(log_hits);
+            redactions.extend(hits.into_iter().map(|hit| report_redaction::ActiveModel {
+                error_report_id: Set(report.id),
+                rule_name: Set(hit.rule),
+                field: Set(hit.field),
+                occurrences: Set(hit.occurrences as i32),
+                ..Default::default()
+            }));
+
+            let group_key = fingerprint(
+                recipe_name(&report.failure_package),
+                &report.failure_task,
+                &report.error_type,
+            );
+            let failure_group_id = match group_ids.get(&group_key) {
+                Some(id) => *id,
+                None => {
+                    let group = FailureGroupService::new(self.db.clone())
+                        .resolve(
+                            &report.failure_package,
+                            &report.failure_task,
+                            &report.error_type,
+                        )
+                        .await?;
+                    group_ids.insert(group_key, group.id);
+                    group.id
+                }
+            };
+
+            reports.push(error_report::ActiveModel {
+                id: Set(report.id),
+                created_at: Set(report.created_at),
+                machine: Set(report.machine),
+                distro: Set(report.distro),
+                distro_version: Set(report.distro_version),
+                build_sys: Set(report.build_sys),
+                nativelsbstring: Set(report.nativelsbstring),
+                target_sys: Set(report.target_sys),
+                failure_task: Set(report.failure_task),
+                failure_package: Set(report.failure_package),
+                error_type: Set(report.error_type),
+                error_details: Set(report.error_details),
+                log_data: Set(report.log_data),
+                submitter_name: Set(report.submitter_name),
+                submitter_email: Set(report.submitter_email),
+                bugzilla_link: Set(report.bugzilla_link),
+                branch_commit: Set(report.branch_commit),
+                failure_group_id: Set(Some(failure_group_id)),
+                ..Default::default()
+            });
+            if let Some(config) = mapped.build_configuration {
+                build_configurations.push(build_configuration::ActiveModel {
+                    error_report_id: Set(config.error_report_id),
+                    bb_version: Set(config.bb_version),
+                    tune_features: Set(config.tune_features),
+                    target_fpu: Set(config.target_fpu),
+                    meta_layers: Set(config.meta_layers),
+                    ..Default::default()
+                });
+            }
+        }
+
+        if reports.is_empty() {
+            return Ok(());
+        }
+        let imported = reports.len();
+
+        let txn = self.db.begin().await?;
+        ErrorReport::insert_many(reports)
+            .exec_without_returning(&txn)
+            .await?;
+        if !build_configurations.is_empty() {
+            BuildConfiguration::insert_many(build_configurations)
+                .exec_without_returning(&txn)
+                .await?;
+        }
+        if !redactions.is_empty() {
+            ReportRedaction::insert_many(redactions)
+                .exec_without_returning(&txn)
+                .await?;
+        }
+        txn.commit().await?;
+
+        summary.imported += imported;
+        Ok(())
+    }
+}
+
+/// Pair the `BuildFailure` objects of a `dumpdata` export with their `Build`.
+/// Objects of other models are ignored.
+pub fn read_dumpdata(json: &str) -> Result<Vec<LegacyRow>, serde_json::Error> {
+    let objects: Vec<DumpObject> = serde_json::from_str(json)?;
+
+    let mut builds = HashMap::new();
+    let mut failures = Vec::new();
+    for object in objects {
+        let model = object.model.to_lowercase();
+        let Some(pk) = object.pk.as_i64().and_then(|pk| i32::try_from(pk).ok()) else {
+            continue;
+        };
+        if model.ends_with(".build") {
+            builds.insert(pk, object.fields);
+        } else if model.ends_with(".buildfailure") {
+            failures.push((pk, object.fields));
+        }
+    }
+
+    let mut rows: Vec<LegacyRow> = failures
+        .into_iter()
+        .map(|(failure_id, failure)| {
+            let build = failure
+                .get("BUILD")
+                .and_then(Value::as_i64)
+                .and_then(|build_id| builds.get(&(build_id as i32)))
+                .cloned();
+            LegacyRow {
+                failure_id,
+                failure,
+                build,
+            }
+        })
+        .collect();
+    rows.sort_by_key(|row| row.failure_id);
+    Ok(rows)
+}
+
+/// Quoted names of the legacy build and failure tables
+fn legacy_tables(table_prefix: &str) -> Result<(String, String), ImportError> {
+    let valid = !table_prefix.is_empty()
+        && table_prefix
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(ImportError::InvalidTablePrefix(table_prefix.to_string()));
+    }
+    Ok((
+        format!("\"{}_build\"", table_prefix),
+        format!("\"{}_buildfailure\"", table_prefix),
+    ))
+}
+
+/// Map a legacy failure and its build onto an error report, or explain why it can't be
+fn map_row(row: &LegacyRow) -> Result<MappedReport, String> {
+    let failure: LegacyFailure = serde_json::from_value(row.failure.clone())
+        .map_err(|e| format!("invalid failure fields: {}", e))?;
+    let build: LegacyBuild = serde_json::from_value(
+        row.build
+            .clone()
+            .ok_or_else(|| "build not found".to_string())?,
+    )
+    .map_err(|e| format!("invalid build fields: {}", e))?;
+
+    let created_at = parse_legacy_date(&build.date)
+        .ok_or_else(|| format!("invalid build date: {}", build.date))?;
+    let failure_package = match failure.recipe_version.as_deref().filter(|v| !v.is_empty()) {
+        Some(version) => format!("{}-{}", failure.recipe, version),
+        None => failure.recipe,
+    };
+    let non_empty = |value: Option<String>| value.filter(|value| !value.trim().is_empty());
+
+    let report = error_report::Model {
+        id: row.failure_id,
+        created_at,
+        machine: build.machine,
+        distro: build.distro,
+        distro_version: build.distro_version.unwrap_or_default(),
+        build_sys: build.build_sys,
+        nativelsbstring: build.nativelsbstring,
+        target_sys: build.target_sys,
+        failure_task: failure.task,
+        failure_package,
+        error_type: non_empty(build.error_type).unwrap_or_else(|| "recipe".to_string()),
+        // The legacy application kept only the failing task's log
+        error_details: failure.error_details,
+        log_data: String::new(),
+        submitter_name: non_empty(build.name),
+        submitter_email: non_empty(build.email),
+        bugzilla_link: None,
+        branch_commit: format!("{}: {}", build.branch, build.commit),
+        failure_group_id: None,
+        hidden_at: None,
+        hidden_reason: None,
+        submitter_ip: None,
+        submitter_token: None,
+    };
+    check_column_limits(&report)?;
+
+    let build_configuration = non_empty(build.bitbake_version).map(|bb_version| {
+        let layers = build
+            .layer_version
+            .as_deref()
+            .map(parse_layer_version)
+            .unwrap_or_default();
+        build_configuration::Model {
+            id: 0,
+            error_report_id: row.failure_id,
+            bb_version,
+            tune_features: None,
+            target_fpu: None,
+            meta_layers: serde_json::to_string(&layers).unwrap_or_else(|_| "[]".to_string()),
+        }
+    });
+
+    Ok(MappedReport {
+        report,
+        build_configuration,
+    })
+}
+
+fn check_column_limits(report: &error_report::Model) -> Result<(), String> {
+    let fields = serde_json::to_value(report).map_err(|e| e.to_string())?;
+    for (column, limit) in COLUMN_LIMITS {
+        let length = fields
+            .get(*column)
+            .and_then(Value::as_str)
+            .map_or(0, |value| value.chars().count());
+        if length > *limit {
+            return Err(format!("{} longer than {} characters", column, limit));
+        }
+    }
+    Ok(())
+}
+
+/// Django serializes dates as RFC 3339, or without an offset when time zone
+/// support is off, in which case UTC is assumed
+fn parse_legacy_date(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date.with_timezone(&Utc));
+    }
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|date| date.and_utc())
+}
+
+/// Layers from the `name = branch:commit` lines the build tools report
+fn parse_layer_version(value: &str) -> Vec<LayerInfo> {
+    value
+        .lines()
+        .filter_map(|line| {
+            let (name, revision) = line.split_once('=')?;
+            let (branch, commit) = match revision.trim().split_once(':') {
+                Some((branch, commit)) => (Some(branch.trim()), commit.trim()),
+                None => (None, revision.trim()),
+            };
+            Some(LayerInfo {
+                name: name.trim().to_string(),
+                path: String::new(),
+                commit: Some(commit.to_string()).filter(|commit| !commit.is_empty()),
+                branch: branch.map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DUMP: &str = r#"[
+        {"model": "Post.build", "pk": 7, "fields": {
+            "DATE": "2016-05-04T10:22:11Z", "MACHINE": "qemux86-64", "BRANCH": "master",
+            "COMMIT": "1a2b3c", "TARGET": "core-image-minimal", "DISTRO": "poky",
+            "NATIVELSBSTRING": "Ubuntu-14.04", "BUILD_SYS": "x86_64-linux",
+            "TARGET_SYS": "x86_64-poky-linux", "NAME": "", "EMAIL": "dev@example.com",
+            "LINK_BACK": null, "ERROR_TYPE": "recipe",
+            "BITBAKE_VERSION": "1.30.0",
+            "LAYER_VERSION": "meta = master:1a2b3c\nmeta-yocto = master:4d5e6f"
+        }},
+        {"model": "Post.buildfailure", "pk": 42, "fields": {
+            "TASK": "do_compile", "RECIPE": "busybox", "RECIPE_VERSION": "1.24.1-r0",
+            "ERROR_DETAILS": "ERROR: oe_runmake failed", "BUILD": 7
+        }},
+        {"model": "Post.buildfailure", "pk": 43, "fields": {
+            "TASK": "do_install", "RECIPE": "glibc", "RECIPE_VERSION": "2.23-r0",
+            "ERROR_DETAILS": "ERROR: install failed", "BUILD": 8
+        }}
+    ]"#;
+
+    #[test]
+    fn test_map_dumpdata() {
+        let rows = read_dumpdata(DUMP).unwrap();
+        assert_eq!(rows.len(), 2);
+
+        let mapped = map_row(&rows[0]).unwrap();
+        assert_eq!(mapped.report.id, 42);
+        assert_eq!(mapped.report.failure_package, "busybox-1.24.1-r0");
+        assert_eq!(mapped.report.branch_commit, "master: 1a2b3c");
+        assert_eq!(mapped.report.submitter_name, None);
+        assert_eq!(
+            mapped.report.submitter_email.as_deref(),
+            Some("dev@example.com")
+        );
+        assert_eq!(
+            mapped.report.created_at.to_rfc3339(),
+            "2016-05-04T10:22:11+00:00"
+        );
+
+        let config = mapped.build_configuration.unwrap();
+        assert_eq!(config.bb_version, "1.30.0");
+        let layers: Vec<LayerInfo> = serde_json::from_str(&config.meta_layers).unwrap();
+        assert_eq!(layers.len(), 2);
+        assert_eq!(layers[1].name, "meta-yocto");
+        assert_eq!(layers[1].commit.as_deref(), Some("4d5e6f"));
+
+        // Its build is not in the dump
+        assert_eq!(map_row(&rows[1]).unwrap_err(), "build not found");
+    }
+
+    #[test]
+    fn test_existing_report_is_import_of() {
+        let rows = read_dumpdata(DUMP).unwrap();
+        let report = map_row(&rows[0]).unwrap().report;
+        let mut existing = ExistingReport {
+            id: report.id,
+            created_at: report.created_at,
+            failure_task: report.failure_task.clone(),
+            failure_package: report.failure_package.clone(),
+            branch_commit: report.branch_commit.clone(),
+        };
+        assert!(existing.is_import_of(&report));
+
+        // A native submission that got the same ID
+        existing.failure_package = "openssl-3.0.13-r0".to_string();
+        assert!(!existing.is_import_of(&report));
+    }
+
+    #[test]
+    fn test_parse_legacy_date() {
+        assert!(parse_legacy_date("2016-05-04T10:22:11.123Z").is_some());
+        assert_eq!(
+            parse_legacy_date("2016-05-04T10:22:11")
+                .unwrap()
+                .to_rfc3339(),
+            "2016-05-04T10:22:11+00:00"
+        );
+        assert!(parse_legacy_date("yesterday").is_none());
+    }
+
+    #[test]
+    fn test_legacy_tables() {
+        assert_eq!(
+            legacy_tables("Post").unwrap(),
+            (
+                "\"Post_build\"".to_string(),
+                "\"Post_buildfailure\"".to_string()
+            )
+        );
+        assert!(legacy_tables("Post\"; DROP TABLE x").is_err());
+    }
+}
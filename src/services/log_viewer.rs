@@ -0,0 +1,237 @@
+use serde::Deserialize;
+
+/// Lines rendered per chunk unless `lines` asks for another amount
+const DEFAULT_CHUNK_LINES: usize = 500;
+const MAX_CHUNK_LINES: usize = 5000;
+/// Consecutive DEBUG lines folded into a collapsible section
+const DEBUG_FOLD_MIN_LINES: usize = 3;
+
+/// Range of log lines requested by the log view
+#[derive(Debug, Default, Deserialize)]
+pub struct LogParams {
+    /// First line, counting from 1
+    pub start: Option<usize>,
+    pub lines: Option<usize>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LogParamsError {
+    #[error("start must be at least 1 and lines between 1 and {MAX_CHUNK_LINES}")]
+    InvalidRange,
+}
+
+impl LogParams {
+    pub fn range(&self) -> Result<(usize, usize), LogParamsError> {
+        let start = self.start.unwrap_or(1);
+        let lines = self.lines.unwrap_or(DEFAULT_CHUNK_LINES);
+        if start == 0 || !(1..=MAX_CHUNK_LINES).contains(&lines) {
+            return Err(LogParamsError::InvalidRange);
+        }
+        Ok((start, lines))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineLevel {
+    Error,
+    Warning,
+    Debug,
+    Normal,
+}
+
+impl LineLevel {
+    /// Classify a line of a BitBake or task log; task output is prefixed with "| "
+    pub fn of(line: &str) -> Self {
+        let message = line.trim_start().trim_start_matches('|').trim_start();
+        let lower = message.to_ascii_lowercase();
+        if message.starts_with("ERROR")
+            || lower.contains(": error:")
+            || lower.contains(": fatal error:")
+        {
+            LineLevel::Error
+        } else if message.starts_with("WARNING") || lower.contains(": warning:") {
+            LineLevel::Warning
+        } else if message.starts_with("DEBUG") {
+            LineLevel::Debug
+        } else {
+            LineLevel::Normal
+        }
+    }
+
+    pub fn css_class(&self) -> &'static str {
+        match self {
+            LineLevel::Error => "log-error",
+            LineLevel::Warning => "log-warning",
+            LineLevel::Debug => "log-debug",
+            LineLevel::Normal => "",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogLine {
+    pub number: usize,
+    pub text: String,
+    pub level: LineLevel,
+}
+
+/// Run of lines; runs of DEBUG lines are folded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogBlock {
+    pub folded: bool,
+    pub lines: Vec<LogLine>,
+}
+
+impl LogBlock {
+    pub fn first_line(&self) -> usize {
+        self.lines.first().map_or(0, |line| line.number)
+    }
+
+    pub fn last_line(&self) -> usize {
+        self.lines.last().map_or(0, |line| line.number)
+    }
+}
+
+/// Consecutive lines of a log, ready to be rendered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogChunk {
+    /// Number of the first line in the chunk, or of the line after the log when empty
+    pub start: usize,
+    /// Number of the last line in the chunk, `start - 1` when empty
+    pub end: usize,
+    pub total_lines: usize,
+    pub blocks: Vec<LogBlock>,
+}
+
+impl LogChunk {
+    /// Lines `start..start + count` of `log`, numbered from 1
+    pub fn new(log: &str, start: usize, count: usize) -> Self {
+        let start = start.max(1);
+        let mut total_lines = 0;
+        let mut lines = Vec::new();
+        for (index, text) in log.lines().enumerate() {
+            total_lines += 1;
+            let number = index + 1;
+            if number >= start && number < start.saturating_add(count) {
+                lines.push(LogLine {
+                    number,
+                    text: text.to_string(),
+                    level: LineLevel::of(text),
+                });
+            }
+        }
+
+        Self {
+            start,
+            end: lines.last().map_or(start - 1, |line| line.number),
+            total_lines,
+            blocks: fold_debug_lines(lines),
+        }
+    }
+
+    /// The last `count` lines of `log`
+    pub fn tail(log: &str, count: usize) -> Self {
+        let total_lines = log.lines().count();
+        Self::new(log, total_lines.saturating_sub(count) + 1, count)
+    }
+
+    pub fn has_more(&self) -> bool {
+        self.end < self.total_lines
+    }
+}
+
+fn fold_debug_lines(lines: Vec<LogLine>) -> Vec<LogBlock> {
+    let mut blocks: Vec<LogBlock> = Vec::new();
+    let mut current: Vec<LogLine> = Vec::new();
+    let mut debug_run: Vec<LogLine> = Vec::new();
+
+    let mut flush_debug = |current: &mut Vec<LogLine>, debug_run: &mut Vec<LogLine>| {
+        if debug_run.len() >= DEBUG_FOLD_MIN_LINES {
+            if !current.is_empty() {
+                blocks.push(LogBlock {
+                    folded: false,
+                    lines: std::mem::take(current),
+                });
+            }
+            blocks.push(LogBlock {
+                folded: true,
+                lines: std::mem::take(debug_run),
+            });
+        } else {
+            current.append(debug_run);
+        }
+    };
+
+    for line in lines {
+        if line.level == LineLevel::Debug {
+            debug_run.push(line);
+        } else {
+            flush_debug(&mut current, &mut debug_run);
+            current.push(line);
+        }
+    }
+    flush_debug(&mut current, &mut debug_run);
+    if !current.is_empty() {
+        blocks.push(LogBlock {
+            folded: false,
+            lines: current,
+        });
+    }
+
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = "NOTE: Executing do_compile\n\
+        DEBUG: Executing shell function do_compile\n\
+        | DEBUG: Python function extend_recipe_sysroot finished\n\
+        DEBUG: cd build\n\
+        | main.c:12:5: error: 'foo' undeclared\n\
+        WARNING: QA issue\n\
+        DEBUG: one more\n\
+        ERROR: oe_runmake failed";
+
+    #[test]
+    fn test_line_levels() {
+        assert_eq!(LineLevel::of("ERROR: Task failed"), LineLevel::Error);
+        assert_eq!(
+            LineLevel::of("| ../src/io.c:40:1: fatal error: zlib.h: No such file"),
+            LineLevel::Error
+        );
+        assert_eq!(LineLevel::of("WARNING: QA issue"), LineLevel::Warning);
+        assert_eq!(LineLevel::of("| DEBUG: Executing"), LineLevel::Debug);
+        assert_eq!(LineLevel::of("NOTE: Running task"), LineLevel::Normal);
+    }
+
+    #[test]
+    fn test_chunk_folds_debug_runs() {
+        let chunk = LogChunk::new(LOG, 1, 100);
+        assert_eq!((chunk.start, chunk.end, chunk.total_lines), (1, 8, 8));
+        assert!(!chunk.has_more());
+
+        let folds: Vec<(bool, usize, usize)> = chunk
+            .blocks
+            .iter()
+            .map(|block| (block.folded, block.first_line(), block.last_line()))
+            .collect();
+        // A single DEBUG line is not worth folding
+        assert_eq!(folds, vec![(false, 1, 1), (true, 2, 4), (false, 5, 8)]);
+    }
+
+    #[test]
+    fn test_chunk_ranges() {
+        let chunk = LogChunk::new(LOG, 5, 2);
+        assert_eq!((chunk.start, chunk.end), (5, 6));
+        assert!(chunk.has_more());
+
+        let tail = LogChunk::tail(LOG, 3);
+        assert_eq!((tail.start, tail.end), (6, 8));
+
+        let past_end = LogChunk::new(LOG, 20, 10);
+        assert_eq!((past_end.start, past_end.end), (20, 19));
+        assert!(past_end.blocks.is_empty());
+    }
+}
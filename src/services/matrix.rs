@@ -0,0 +1,273 @@
+use sea_orm::{
+    sea_query::{Alias, Expr, SimpleExpr},
+    ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::models::{error_report, error_report::ErrorQuery, ErrorReport};
+use crate::services::failure_groups::VERSION_SUFFIX_PATTERN;
+
+const DEFAULT_MATRIX_ROWS: u64 = 25;
+const DEFAULT_MATRIX_COLUMNS: u64 = 15;
+const MAX_MATRIX_SIZE: u64 = 100;
+
+/// What the columns of the failure matrix are
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatrixColumns {
+    #[default]
+    Machine,
+    DistroVersion,
+}
+
+impl MatrixColumns {
+    fn column(self) -> error_report::Column {
+        match self {
+            MatrixColumns::Machine => error_report::Column::Machine,
+            MatrixColumns::DistroVersion => error_report::Column::DistroVersion,
+        }
+    }
+}
+
+impl std::fmt::Display for MatrixColumns {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            MatrixColumns::Machine => "machine",
+            MatrixColumns::DistroVersion => "distro_version",
+        })
+    }
+}
+
+/// Shape of the matrix; filters are taken from `ErrorQuery`
+#[derive(Debug, Default, Deserialize)]
+pub struct MatrixParams {
+    pub columns: Option<MatrixColumns>,
+    /// Number of recipes (rows) shown
+    pub rows: Option<u64>,
+    /// Number of machines or distro versions (columns) shown
+    pub cols: Option<u64>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MatrixParamsError {
+    #[error("rows and cols must be between 1 and {MAX_MATRIX_SIZE}")]
+    InvalidSize,
+}
+
+impl MatrixParams {
+    pub fn columns(&self) -> MatrixColumns {
+        self.columns.unwrap_or_default()
+    }
+
+    /// Number of rows and columns requested
+    pub fn size(&self) -> Result<(u64, u64), MatrixParamsError> {
+        let rows = self.rows.unwrap_or(DEFAULT_MATRIX_ROWS);
+        let cols = self.cols.unwrap_or(DEFAULT_MATRIX_COLUMNS);
+        let valid = 1..=MAX_MATRIX_SIZE;
+        if !valid.contains(&rows) || !valid.contains(&cols) {
+            return Err(MatrixParamsError::InvalidSize);
+        }
+        Ok((rows, cols))
+    }
+}
+
+/// Cross-tab of report counts: failing recipes against machines or distro versions.
+/// Rows are recipe names, so that all versions of a recipe are counted together.
+/// Only the most frequent recipes and columns are included.
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorMatrix {
+    pub columns_by: MatrixColumns,
+    pub rows: Vec<String>,
+    pub columns: Vec<String>,
+    /// `cells[row][column]`
+    pub cells: Vec<Vec<u64>>,
+    pub row_totals: Vec<u64>,
+    pub column_totals: Vec<u64>,
+    pub max_count: u64,
+}
+
+impl ErrorMatrix {
+    /// Background opacity of a heatmap cell, relative to the largest count
+    pub fn intensity(&self, count: &u64) -> String {
+        if *count == 0 || self.max_count == 0 {
+            return "0".to_string();
+        }
+        let ratio = *count as f64 / self.max_count as f64;
+        format!("{:.2}", 0.15 + 0.85 * ratio)
+    }
+
+    /// Query string of the error list behind a cell: the current filters, with
+    /// the recipe and column filters narrowed to the cell
+    pub fn cell_query(&self, filters: &ErrorQuery, recipe: &str, column: &str) -> String {
+        let mut query = filters.clone();
+        query.narrow(
+            "failure_package",
+            vec![recipe.to_string(), format!("{}-*", recipe)],
+        );
+        query.narrow(&self.columns_by.to_string(), vec![column.to_string()]);
+        query.filter_query()
+    }
+}
+
+/// Recipe name of the failing package, as computed by `recipe_name`
+fn recipe_expr() -> SimpleExpr {
+    Expr::cust_with_values(
+        "regexp_replace(error_reports.failure_package, $1, '')",
+        [VERSION_SUFFIX_PATTERN],
+    )
+}
+
+pub struct MatrixService {
+    db: DatabaseConnection,
+}
+
+impl MatrixService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    pub async fn get_matrix(
+        &self,
+        columns_by: MatrixColumns,
+        filter: &Condition,
+        max_rows: u64,
+        max_columns: u64,
+    ) -> Result<ErrorMatrix, DbErr> {
+        let column = columns_by.column();
+        let rows = self.top_values(recipe_expr(), filter, max_rows).await?;
+        let columns = self
+            .top_values(Expr::col(column).into(), filter, max_columns)
+            .await?;
+
+        let counts = if rows.is_empty() || columns.is_empty() {
+            Vec::new()
+        } else {
+            ErrorReport::find()
+                .select_only()
+                .column_as(recipe_expr(), "recipe")
+                .column(column)
+                .column_as(error_report::Column::Id.count(), "count")
+                .filter(filter.clone())
+                .filter(Expr::expr(recipe_expr()).is_in(rows.clone()))
+                .filter(Expr::col(column).is_in(columns.clone()))
+                .group_by(recipe_expr())
+                .group_by(column)
+                .into_tuple::<(String, String, i64)>()
+                .all(&self.db)
+                .await?
+        };
+
+        Ok(build_matrix(columns_by, rows, columns, counts))
+    }
+
+    async fn top_values(
+        &self,
+        value: SimpleExpr,
+        filter: &Condition,
+        limit: u64,
+    ) -> Result<Vec<String>, DbErr> {
+        let results = ErrorReport::find()
+            .select_only()
+            .column_as(value.clone(), "value")
+            .column_as(error_report::Column::Id.count(), "count")
+            .filter(filter.clone())
+            .group_by(value)
+            .order_by_desc(Expr::col(Alias::new("count")))
+            .order_by_asc(Expr::col(Alias::new("value")))
+            .limit(limit)
+            .into_tuple::<(String, i64)>()
+            .all(&self.db)
+            .await?;
+
+        Ok(results.into_iter().map(|(value, _)| value).collect())
+    }
+}
+
+/// Arrange `(row, column, count)` triples into a matrix with totals
+pub fn build_matrix(
+    columns_by: MatrixColumns,
+    rows: Vec<String>,
+    columns: Vec<String>,
+    counts: Vec<(String, String, i64)>,
+) -> ErrorMatrix {
+    let counts: HashMap<(String, String), u64> = counts
+        .into_iter()
+        .map(|(row, column, count)| ((row, column), count as u64))
+        .collect();
+
+    let cells: Vec<Vec<u64>> = rows
+        .iter()
+        .map(|row| {
+            columns
+                .iter()
+                .map(|column| {
+                    counts
+                        .get(&(row.clone(), column.clone()))
+                        .copied()
+                        .unwrap_or(0)
+                })
+                .collect()
+        })
+        .collect();
+
+    let row_totals = cells.iter().map(|row| row.iter().sum()).collect();
+    let column_totals = (0..columns.len())
+        .map(|index| cells.iter().map(|row| row[index]).sum())
+        .collect();
+    let max_count = cells.iter().flatten().copied().max().unwrap_or(0);
+
+    ErrorMatrix {
+        columns_by,
+        rows,
+        columns,
+        cells,
+        row_totals,
+        column_totals,
+        max_count,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_matrix() {
+        let matrix = build_matrix(
+            MatrixColumns::Machine,
+            vec!["busybox".to_string(), "glibc".to_string()],
+            vec!["qemuarm64".to_string(), "qemux86-64".to_string()],
+            vec![
+                ("busybox".to_string(), "qemuarm64".to_string(), 4),
+                ("busybox".to_string(), "qemux86-64".to_string(), 1),
+                ("glibc".to_string(), "qemux86-64".to_string(), 2),
+            ],
+        );
+
+        assert_eq!(matrix.cells, vec![vec![4, 1], vec![0, 2]]);
+        assert_eq!(matrix.row_totals, vec![5, 2]);
+        assert_eq!(matrix.column_totals, vec![4, 3]);
+        assert_eq!(matrix.max_count, 4);
+        assert_eq!(matrix.intensity(&4), "1.00");
+        assert_eq!(matrix.intensity(&0), "0");
+    }
+
+    #[test]
+    fn test_cell_query() {
+        let matrix = build_matrix(MatrixColumns::Machine, Vec::new(), Vec::new(), Vec::new());
+        let filters = ErrorQuery {
+            failure_package: vec!["busybox-1.36*".to_string(), "!busybox-dev*".to_string()],
+            machine: vec!["qemuarm".to_string()],
+            distro: vec!["poky".to_string(), "nodistro".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            matrix.cell_query(&filters, "busybox", "qemux86-64"),
+            "machine=qemux86-64&distro=poky&distro=nodistro\
+             &failure_package=%21busybox-dev*&failure_package=busybox&failure_package=busybox-*"
+        );
+    }
+}
@@ -1 +1,22 @@
+pub mod alerts;
+pub mod audit;
+pub mod comments;
+pub mod compare;
+pub mod error_list;
+pub mod export;
+pub mod facets;
+pub mod failure_groups;
+pub mod jobs;
+pub mod known_issues;
+pub mod legacy_import;
+pub mod log_viewer;
+pub mod matrix;
+pub mod moderation;
+pub mod notifications;
+pub mod regressions;
+pub mod retention;
+pub mod saved_searches;
 pub mod stats;
+pub mod stats_cache;
+pub mod submitters;
+pub mod tags;
@@ -0,0 +1,377 @@
+use chrono::Utc;
+use sea_orm::{
+    sea_query::{Expr, OnConflict},
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+
+use crate::models::{
+    error_report::{self, ErrorQuery},
+    moderation_action,
+    submission_ban::{self, BanData},
+    ErrorReport, ModerationAction, SubmissionBan,
+};
+
+/// Most reports a bulk action over every matching report may change
+pub const MAX_BULK_REPORTS: u64 = 1000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ModerationError {
+    #[error("{count} reports match; narrow the filters to at most {max}")]
+    TooManyReports { count: u64, max: u64 },
+    #[error("Database error: {0}")]
+    Database(#[from] DbErr),
+}
+
+/// Where a submission comes from, as checked against the bans
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SubmissionSource<'a> {
+    pub ip: Option<&'a str>,
+    pub email: Option<&'a str>,
+    pub token: Option<&'a str>,
+}
+
+pub struct ModerationService {
+    db: DatabaseConnection,
+}
+
+impl ModerationService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// IDs of every report matching the query, up to `MAX_BULK_REPORTS`
+    pub async fn matching_ids(&self, query: &ErrorQuery) -> Result<Vec<i32>, ModerationError> {
+        let count = ErrorReport::find()
+            .filter(query.condition())
+            .count(&self.db)
+            .await?;
+        if count > MAX_BULK_REPORTS {
+            return Err(ModerationError::TooManyReports {
+                count,
+                max: MAX_BULK_REPORTS,
+            });
+        }
+
+        Ok(ErrorReport::find()
+            .select_only()
+            .column(error_report::Column::Id)
+            .filter(query.condition())
+            .order_by_asc(error_report::Column::Id)
+            .into_tuple()
+            .all(&self.db)
+            .await?)
+    }
+
+    /// Hide visible reports from public pages; returns the IDs of the reports
+    /// that were hidden
+    pub async fn hide<C: ConnectionTrait + TransactionTrait>(
+        &self,
+        db: &C,
+        ids: &[i32],
+        actor: Option<&str>,
+        reason: Option<&str>,
+    ) -> Result<Vec<i32>, DbErr> {
+        let reason = non_empty(reason);
+        let txn = db.begin().await?;
+        let ids = report_ids(&txn, ids, Some(error_report::visible())).await?;
+
+        ErrorReport::update_many()
+            .col_expr(error_report::Column::HiddenAt, Expr::value(Utc::now()))
+            .col_expr(
+                error_report::Column::HiddenReason,
+                Expr::value(reason.map(str::to_string)),
+            )
+            .filter(error_report::Column::Id.is_in(ids.clone()))
+            .exec(&txn)
+            .await?;
+        for id in &ids {
+            record_action(
+                &txn,
+                moderation_action::ACTION_HIDE,
+                Some(*id),
+                None,
+                actor,
+                reason.unwrap_or_default(),
+            )
+            .await?;
+        }
+        txn.commit().await?;
+
+        Ok(ids)
+    }
+
+    /// Show hidden reports again; returns the IDs of the restored reports
+    pub async fn unhide<C: ConnectionTrait + TransactionTrait>(
+        &self,
+        db: &C,
+        ids: &[i32],
+        actor: Option<&str>,
+    ) -> Result<Vec<i32>, DbErr> {
+        let txn = db.begin().await?;
+        let ids = report_ids(
+            &txn,
+            ids,
+            Some(error_report::Column::HiddenAt.is_not_null()),
+        )
+        .await?;
+
+        ErrorReport::update_many()
+            .col_expr(
+                error_report::Column::HiddenAt,
+                Expr::value(Option::<chrono::DateTime<Utc>>::None),
+            )
+            .col_expr(
+                error_report::Column::HiddenReason,
+                Expr::value(Option::<String>::None),
+            )
+            .filter(error_report::Column::Id.is_in(ids.clone()))
+            .exec(&txn)
+            .await?;
+        for id in &ids {
+            record_action(
+                &txn,
+                moderation_action::ACTION_UNHIDE,
+                Some(*id),
+                None,
+                actor,
+                "",
+            )
+            .await?;
+        }
+        txn.commit().await?;
+
+        Ok(ids)
+    }
+
+    /// Delete reports along with their build configurations, tags, comments
+    /// and other dependent rows; returns the reports as they were before the
+    /// deletion
+    pub async fn delete<C: ConnectionTrait + TransactionTrait>(
+        &self,
+        db: &C,
+        ids: &[i32],
+        actor: Option<&str>,
+        reason: Option<&str>,
+    ) -> Result<Vec<error_report::Model>, DbErr> {
+        let txn = db.begin().await?;
+        let reports = ErrorReport::find()
+            .filter(error_report::Column::Id.is_in(ids.iter().copied()))
+            .order_by_asc(error_report::Column::Id)
+            .all(&txn)
+            .await?;
+
+        ErrorReport::delete_many()
+            .filter(error_report::Column::Id.is_in(reports.iter().map(|report| report.id)))
+            .exec(&txn)
+            .await?;
+        // The log keeps enough to tell what was removed
+        for report in &reports {
+            let summary = format!("{} on {}", report.failure_package, report.machine);
+            record_action(
+                &txn,
+                moderation_action::ACTION_DELETE,
+                Some(report.id),
+                None,
+                actor,
+                &describe(&summary, reason),
+            )
+            .await?;
+        }
+        txn.commit().await?;
+
+        Ok(reports)
+    }
+
+    pub async fn bans(&self) -> Result<Vec<submission_ban::Model>, DbErr> {
+        SubmissionBan::find()
+            .order_by_asc(submission_ban::Column::Kind)
+            .order_by_asc(submission_ban::Column::Value)
+            .all(&self.db)
+            .await
+    }
+
+    /// Reject future submissions from a source; the input is expected to have
+    /// passed validation. Banning a source twice keeps the first ban.
+    pub async fn ban<C: ConnectionTrait + TransactionTrait>(
+        &self,
+        db: &C,
+        data: BanData,
+    ) -> Result<submission_ban::Model, DbErr> {
+        let value = submission_ban::normalize_value(&data.kind, &data.value);
+        let reason = non_empty(data.reason.as_deref()).unwrap_or_default();
+
+        let txn = db.begin().await?;
+        SubmissionBan::insert(submission_ban::ActiveModel {
+            created_at: Set(Utc::now()),
+            kind: Set(data.kind.clone()),
+            value: Set(value.clone()),
+            reason: Set(reason.to_string()),
+            actor: Set(non_empty(data.actor.as_deref()).map(str::to_string)),
+            ..Default::default()
+        })
+        .on_conflict(
+            OnConflict::columns([submission_ban::Column::Kind, submission_ban::Column::Value])
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(&txn)
+        .await?;
+
+        let ban = SubmissionBan::find()
+            .filter(submission_ban::Column::Kind.eq(&data.kind))
+            .filter(submission_ban::Column::Value.eq(&value))
+            .one(&txn)
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound(format!("{} ban {}", data.kind, value)))?;
+        record_action(
+            &txn,
+            moderation_action::ACTION_BAN,
+            None,
+            Some(ban.id),
+            data.actor.as_deref(),
+            &describe(&format!("{} {}", ban.kind, ban.value), Some(reason)),
+        )
+        .await?;
+        txn.commit().await?;
+
+        Ok(ban)
+    }
+
+    /// Lift a ban; `None` when it does not exist
+    pub async fn unban<C: ConnectionTrait + TransactionTrait>(
+        &self,
+        db: &C,
+        id: i32,
+        actor: Option<&str>,
+    ) -> Result<Option<submission_ban::Model>, DbErr> {
+        let txn = db.begin().await?;
+        let Some(ban) = SubmissionBan::find_by_id(id).one(&txn).await? else {
+            return Ok(None);
+        };
+
+        SubmissionBan::delete_by_id(id).exec(&txn).await?;
+        record_action(
+            &txn,
+            moderation_action::ACTION_UNBAN,
+            None,
+            Some(ban.id),
+            actor,
+            &format!("{} {}", ban.kind, ban.value),
+        )
+        .await?;
+        txn.commit().await?;
+
+        Ok(Some(ban))
+    }
+
+    /// The ban matching a submission, if any
+    pub async fn find_ban(
+        &self,
+        source: SubmissionSource<'_>,
+    ) -> Result<Option<submission_ban::Model>, DbErr> {
+        let sources = [
+            (submission_ban::KIND_IP, source.ip),
+            (submission_ban::KIND_EMAIL, source.email),
+            (submission_ban::KIND_TOKEN, source.token),
+        ];
+        let mut matches = Condition::any();
+        for (kind, value) in sources {
+            if let Some(value) = value.filter(|value| !value.trim().is_empty()) {
+                matches = matches.add(
+                    Condition::all()
+                        .add(submission_ban::Column::Kind.eq(kind))
+                        .add(
+                            submission_ban::Column::Value
+                                .eq(submission_ban::normalize_value(kind, value)),
+                        ),
+                );
+            }
+        }
+        if matches.is_empty() {
+            return Ok(None);
+        }
+
+        SubmissionBan::find().filter(matches).one(&self.db).await
+    }
+
+    pub async fn recent_actions(&self, limit: u64) -> Result<Vec<moderation_action::Model>, DbErr> {
+        ModerationAction::find()
+            .order_by_desc(moderation_action::Column::CreatedAt)
+            .order_by_desc(moderation_action::Column::Id)
+            .limit(limit)
+            .all(&self.db)
+            .await
+    }
+}
+
+/// The given report IDs that exist and match `filter`
+async fn report_ids<C: ConnectionTrait>(
+    db: &C,
+    ids: &[i32],
+    filter: Option<sea_orm::sea_query::SimpleExpr>,
+) -> Result<Vec<i32>, DbErr> {
+    let mut query = ErrorReport::find()
+        .select_only()
+        .column(error_report::Column::Id)
+        .filter(error_report::Column::Id.is_in(ids.iter().copied()));
+    if let Some(filter) = filter {
+        query = query.filter(filter);
+    }
+    query
+        .order_by_asc(error_report::Column::Id)
+        .into_tuple()
+        .all(db)
+        .await
+}
+
+async fn record_action<C: ConnectionTrait>(
+    db: &C,
+    action: &str,
+    error_report_id: Option<i32>,
+    submission_ban_id: Option<i32>,
+    actor: Option<&str>,
+    details: &str,
+) -> Result<moderation_action::Model, DbErr> {
+    moderation_action::ActiveModel {
+        created_at: Set(Utc::now()),
+        action: Set(action.to_string()),
+        error_report_id: Set(error_report_id),
+        submission_ban_id: Set(submission_ban_id),
+        actor: Set(non_empty(actor).map(str::to_string)),
+        details: Set(details.to_string()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|value| !value.is_empty())
+}
+
+/// Action details followed by the reason given by the moderator, if any
+fn describe(summary: &str, reason: Option<&str>) -> String {
+    match non_empty(reason) {
+        Some(reason) => format!("{}: {}", summary, reason),
+        None => summary.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(describe("busybox on qemuarm", None), "busybox on qemuarm");
+        assert_eq!(
+            describe("busybox on qemuarm", Some(" ")),
+            "busybox on qemuarm"
+        );
+        assert_eq!(
+            describe("busybox on qemuarm", Some("leaked token")),
+            "busybox on qemuarm: leaked token"
+        );
+    }
+}
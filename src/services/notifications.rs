@@ -0,0 +1,93 @@
+use lettre::{
+    message::Mailbox, transport::smtp::authentication::Credentials, Message, SmtpTransport,
+    Transport,
+};
+
+use crate::config::EmailConfig;
+
+#[derive(Debug, thiserror::Error)]
+pub enum NotificationError {
+    #[error("Invalid email address: {0}")]
+    InvalidAddress(#[from] lettre::address::AddressError),
+    #[error("Failed to build email: {0}")]
+    Message(#[from] lettre::error::Error),
+    #[error("Failed to send email: {0}")]
+    Smtp(#[from] lettre::transport::smtp::Error),
+    #[error("Email task failed: {0}")]
+    Task(#[from] tokio::task::JoinError),
+}
+
+/// Delivers notifications by email, by default to a fixed list of recipients
+#[derive(Clone)]
+pub struct Notifier {
+    email: EmailConfig,
+    recipients: Vec<String>,
+    base_url: String,
+}
+
+impl Notifier {
+    pub fn new(email: EmailConfig, recipients: Vec<String>, base_url: String) -> Self {
+        Self {
+            email,
+            recipients,
+            base_url,
+        }
+    }
+
+    /// Root of the links included in notifications
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.recipients.is_empty()
+    }
+
+    pub async fn send(&self, subject: &str, body: &str) -> Result<(), NotificationError> {
+        self.send_to(&self.recipients, subject, body).await
+    }
+
+    /// Send to the given addresses instead of the configured recipients
+    pub async fn send_to(
+        &self,
+        recipients: &[String],
+        subject: &str,
+        body: &str,
+    ) -> Result<(), NotificationError> {
+        if recipients.is_empty() {
+            return Ok(());
+        }
+
+        let mut builder = Message::builder()
+            .from(self.email.from_address.parse::<Mailbox>()?)
+            .subject(subject);
+        for recipient in recipients {
+            builder = builder.to(recipient.parse::<Mailbox>()?);
+        }
+        let message = builder.body(body.to_string())?;
+
+        let transport = self.transport()?;
+        // lettre's SMTP transport is blocking
+        tokio::task::spawn_blocking(move || transport.send(&message)).await??;
+        Ok(())
+    }
+
+    /// STARTTLS with authentication when credentials are configured, otherwise
+    /// plain SMTP to a local relay
+    fn transport(&self) -> Result<SmtpTransport, NotificationError> {
+        let username = self.email.username.as_deref().filter(|u| !u.is_empty());
+        let transport = match username {
+            Some(username) => SmtpTransport::starttls_relay(&self.email.host)?
+                .port(self.email.port)
+                .credentials(Credentials::new(
+                    username.to_string(),
+                    self.email.password.clone().unwrap_or_default(),
+                ))
+                .build(),
+            None => SmtpTransport::builder_dangerous(&self.email.host)
+                .port(self.email.port)
+                .build(),
+        };
+        Ok(transport)
+    }
+}
@@ -0,0 +1,354 @@
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait, FromQueryResult, QueryFilter,
+    Statement,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::models::{
+    build_configuration::{self, LayerInfo},
+    failure_group, BuildConfiguration, FailureGroup,
+};
+
+const DEFAULT_REGRESSION_DAYS: u32 = 14;
+const MAX_REGRESSION_DAYS: u32 = 365;
+const MAX_REGRESSIONS: i64 = 100;
+
+/// Failure groups seen for the first time on a branch or release since `$1`.
+/// Only branches and releases with earlier reports count: a brand new branch
+/// has no history to regress from. `{scope}` is replaced by a fixed expression.
+const REGRESSIONS_SQL: &str = r#"
+WITH scoped AS (
+    SELECT id, created_at, failure_group_id, branch_commit, {scope} AS scope
+    FROM error_reports
+    WHERE hidden_at IS NULL
+),
+firsts AS (
+    SELECT failure_group_id, scope, MIN(created_at) AS first_seen, COUNT(*) AS report_count
+    FROM scoped
+    WHERE failure_group_id IS NOT NULL
+    GROUP BY failure_group_id, scope
+    HAVING MIN(created_at) >= $1
+)
+SELECT
+    f.failure_group_id,
+    f.scope,
+    f.first_seen,
+    f.report_count,
+    first_report.id AS first_report_id,
+    first_report.branch_commit AS first_branch_commit,
+    previous_report.id AS previous_report_id,
+    previous_report.branch_commit AS previous_branch_commit,
+    EXISTS (
+        SELECT 1 FROM error_reports e
+        WHERE e.failure_group_id = f.failure_group_id AND e.created_at < f.first_seen
+            AND e.hidden_at IS NULL
+    ) AS seen_elsewhere
+FROM firsts f
+JOIN LATERAL (
+    SELECT id, branch_commit FROM scoped s
+    WHERE s.failure_group_id = f.failure_group_id AND s.scope = f.scope
+    ORDER BY s.created_at, s.id
+    LIMIT 1
+) first_report ON TRUE
+JOIN LATERAL (
+    SELECT id, branch_commit FROM scoped s
+    WHERE s.scope = f.scope AND s.created_at < f.first_seen
+    ORDER BY s.created_at DESC, s.id DESC
+    LIMIT 1
+) previous_report ON TRUE
+ORDER BY f.first_seen DESC
+LIMIT $2
+"#;
+
+/// What a failure group has to be new for to count as a regression
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegressionScope {
+    /// The branch part of `branch_commit`
+    #[default]
+    Branch,
+    DistroVersion,
+}
+
+impl RegressionScope {
+    fn sql_expr(self) -> &'static str {
+        match self {
+            RegressionScope::Branch => "split_part(branch_commit, ':', 1)",
+            RegressionScope::DistroVersion => "distro_version",
+        }
+    }
+}
+
+impl std::fmt::Display for RegressionScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            RegressionScope::Branch => "branch",
+            RegressionScope::DistroVersion => "distro_version",
+        })
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RegressionParams {
+    pub scope: Option<RegressionScope>,
+    pub days: Option<u32>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RegressionParamsError {
+    #[error("days must be between 1 and {MAX_REGRESSION_DAYS}")]
+    InvalidRange,
+}
+
+impl RegressionParams {
+    pub fn scope(&self) -> RegressionScope {
+        self.scope.unwrap_or_default()
+    }
+
+    pub fn days(&self) -> Result<u32, RegressionParamsError> {
+        match self.days.unwrap_or(DEFAULT_REGRESSION_DAYS) {
+            days @ 1..=MAX_REGRESSION_DAYS => Ok(days),
+            _ => Err(RegressionParamsError::InvalidRange),
+        }
+    }
+}
+
+/// A layer whose commit differs between the last report before the regression
+/// and the first failing report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LayerChange {
+    pub name: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Regression {
+    pub failure_group_id: i32,
+    pub recipe: String,
+    pub failure_task: String,
+    pub error_type: String,
+    /// Branch name or distro version the group is new for
+    pub scope_value: String,
+    pub first_seen: DateTime<Utc>,
+    pub report_count: i64,
+    pub first_report_id: i32,
+    /// `branch_commit` of the earliest report of this failure
+    pub first_failing_commit: String,
+    pub previous_report_id: i32,
+    /// `branch_commit` of the latest report before the failure first appeared
+    pub previous_commit: String,
+    /// Whether the group was already known from other branches or releases
+    pub seen_elsewhere: bool,
+    pub layer_changes: Vec<LayerChange>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RegressionReport {
+    pub scope: RegressionScope,
+    pub days: u32,
+    pub since: DateTime<Utc>,
+    pub regressions: Vec<Regression>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct RegressionRow {
+    failure_group_id: i32,
+    scope: String,
+    first_seen: DateTime<Utc>,
+    report_count: i64,
+    first_report_id: i32,
+    first_branch_commit: String,
+    previous_report_id: i32,
+    previous_branch_commit: String,
+    seen_elsewhere: bool,
+}
+
+pub struct RegressionService {
+    db: DatabaseConnection,
+}
+
+impl RegressionService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Failure groups that first appeared on a branch or release in the last `days` days
+    pub async fn find_regressions(
+        &self,
+        scope: RegressionScope,
+        days: u32,
+    ) -> Result<RegressionReport, DbErr> {
+        let since = Utc::now() - Duration::days(days.into());
+        let sql = REGRESSIONS_SQL.replace("{scope}", scope.sql_expr());
+
+        let rows = RegressionRow::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            sql,
+            [since.into(), MAX_REGRESSIONS.into()],
+        ))
+        .all(&self.db)
+        .await?;
+
+        let group_ids: Vec<i32> = rows.iter().map(|row| row.failure_group_id).collect();
+        let groups: HashMap<i32, failure_group::Model> = FailureGroup::find()
+            .filter(failure_group::Column::Id.is_in(group_ids))
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|group| (group.id, group))
+            .collect();
+
+        let report_ids: Vec<i32> = rows
+            .iter()
+            .flat_map(|row| [row.first_report_id, row.previous_report_id])
+            .collect();
+        let layers: HashMap<i32, Vec<LayerInfo>> = BuildConfiguration::find()
+            .filter(build_configuration::Column::ErrorReportId.is_in(report_ids))
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|config| {
+                let layers = serde_json::from_str(&config.meta_layers).unwrap_or_default();
+                (config.error_report_id, layers)
+            })
+            .collect();
+
+        let regressions = rows
+            .into_iter()
+            .filter_map(|row| {
+                let group = groups.get(&row.failure_group_id)?;
+                let layer_changes = match (
+                    layers.get(&row.previous_report_id),
+                    layers.get(&row.first_report_id),
+                ) {
+                    (Some(before), Some(after)) => layer_changes(before, after),
+                    _ => Vec::new(),
+                };
+
+                Some(Regression {
+                    failure_group_id: group.id,
+                    recipe: group.recipe.clone(),
+                    failure_task: group.failure_task.clone(),
+                    error_type: group.error_type.clone(),
+                    scope_value: row.scope,
+                    first_seen: row.first_seen,
+                    report_count: row.report_count,
+                    first_report_id: row.first_report_id,
+                    first_failing_commit: row.first_branch_commit,
+                    previous_report_id: row.previous_report_id,
+                    previous_commit: row.previous_branch_commit,
+                    seen_elsewhere: row.seen_elsewhere,
+                    layer_changes,
+                })
+            })
+            .collect();
+
+        Ok(RegressionReport {
+            scope,
+            days,
+            since,
+            regressions,
+        })
+    }
+}
+
+/// Layers added, removed or moved to another commit between two builds
+pub fn layer_changes(before: &[LayerInfo], after: &[LayerInfo]) -> Vec<LayerChange> {
+    let before_commits: HashMap<&str, Option<&String>> = before
+        .iter()
+        .map(|layer| (layer.name.as_str(), layer.commit.as_ref()))
+        .collect();
+    let after_names: Vec<&str> = after.iter().map(|layer| layer.name.as_str()).collect();
+
+    let mut changes: Vec<LayerChange> = after
+        .iter()
+        .filter_map(|layer| {
+            let previous = before_commits.get(layer.name.as_str());
+            if previous == Some(&layer.commit.as_ref()) {
+                return None;
+            }
+            Some(LayerChange {
+                name: layer.name.clone(),
+                before: previous.copied().flatten().cloned(),
+                after: layer.commit.clone(),
+            })
+        })
+        .collect();
+
+    changes.extend(
+        before
+            .iter()
+            .filter(|layer| !after_names.contains(&layer.name.as_str()))
+            .map(|layer| LayerChange {
+                name: layer.name.clone(),
+                before: layer.commit.clone(),
+                after: None,
+            }),
+    );
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(name: &str, commit: &str) -> LayerInfo {
+        LayerInfo {
+            name: name.to_string(),
+            path: format!("/path/to/{}", name),
+            commit: Some(commit.to_string()),
+            branch: Some("master".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_layer_changes() {
+        let before = vec![
+            layer("meta", "abc123"),
+            layer("meta-poky", "def456"),
+            layer("meta-old", "111111"),
+        ];
+        let after = vec![
+            layer("meta", "abc999"),
+            layer("meta-poky", "def456"),
+            layer("meta-new", "222222"),
+        ];
+
+        assert_eq!(
+            layer_changes(&before, &after),
+            vec![
+                LayerChange {
+                    name: "meta".to_string(),
+                    before: Some("abc123".to_string()),
+                    after: Some("abc999".to_string()),
+                },
+                LayerChange {
+                    name: "meta-new".to_string(),
+                    before: None,
+                    after: Some("222222".to_string()),
+                },
+                LayerChange {
+                    name: "meta-old".to_string(),
+                    before: Some("111111".to_string()),
+                    after: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_params() {
+        let params = RegressionParams::default();
+        assert_eq!(params.scope(), RegressionScope::Branch);
+        assert_eq!(params.days().unwrap(), DEFAULT_REGRESSION_DAYS);
+
+        let params: RegressionParams =
+            serde_json::from_str(r#"{"scope": "distro_version", "days": 0}"#).unwrap();
+        assert_eq!(params.scope(), RegressionScope::DistroVersion);
+        assert!(params.days().is_err());
+    }
+}
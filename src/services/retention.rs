@@ -0,0 +1,192 @@
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{
+    sea_query::Expr, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait,
+    PaginatorTrait, QueryFilter,
+};
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::{error, info};
+
+use crate::{
+    config::RetentionConfig,
+    models::{error_report, ErrorReport},
+    services::stats_cache::StatsCache,
+};
+
+/// Placeholder stored in place of logs dropped by the retention policy
+pub const PURGED_LOG_DATA: &str = "[log data removed by retention policy]";
+
+#[derive(Debug, Serialize)]
+pub struct PurgeReport {
+    pub dry_run: bool,
+    pub log_data_cutoff: Option<DateTime<Utc>>,
+    pub report_cutoff: Option<DateTime<Utc>>,
+    pub log_data_cleared: u64,
+    pub reports_deleted: u64,
+}
+
+pub struct RetentionService {
+    db: DatabaseConnection,
+    config: RetentionConfig,
+}
+
+impl RetentionService {
+    pub fn new(db: DatabaseConnection, config: RetentionConfig) -> Self {
+        Self { db, config }
+    }
+
+    /// Apply the retention rules, or only count what they would remove when `dry_run` is set
+    pub async fn purge(&self, dry_run: bool) -> Result<PurgeReport, DbErr> {
+        let now = Utc::now();
+        let log_data_cutoff = self
+            .config
+            .log_data_days
+            .map(|days| now - Duration::days(days.into()));
+        let report_cutoff = self
+            .config
+            .report_days
+            .map(|days| now - Duration::days(days.into()));
+
+        // Reports are deleted first so their logs aren't needlessly rewritten
+        let reports_deleted = match report_cutoff {
+            Some(cutoff) if dry_run => {
+                ErrorReport::find()
+                    .filter(expired_reports(cutoff, self.config.keep_bug_linked))
+                    .count(&self.db)
+                    .await?
+            }
+            Some(cutoff) => {
+                ErrorReport::delete_many()
+                    .filter(expired_reports(cutoff, self.config.keep_bug_linked))
+                    .exec(&self.db)
+                    .await?
+                    .rows_affected
+            }
+            None => 0,
+        };
+
+        let log_data_cleared = match log_data_cutoff {
+            Some(cutoff) if dry_run => {
+                let mut query = ErrorReport::find().filter(expired_logs(cutoff));
+                // Reports about to be deleted are already counted above
+                if let Some(report_cutoff) = report_cutoff {
+                    query = query.filter(Condition::not(expired_reports(
+                        report_cutoff,
+                        self.config.keep_bug_linked,
+                    )));
+                }
+                query.count(&self.db).await?
+            }
+            Some(cutoff) => {
+                ErrorReport::update_many()
+                    .col_expr(error_report::Column::LogData, Expr::value(PURGED_LOG_DATA))
+                    .filter(expired_logs(cutoff))
+                    .exec(&self.db)
+                    .await?
+                    .rows_affected
+            }
+            None => 0,
+        };
+
+        Ok(PurgeReport {
+            dry_run,
+            log_data_cutoff,
+            report_cutoff,
+            log_data_cleared,
+            reports_deleted,
+        })
+    }
+
+    /// Run the purge on the configured interval until the process exits.
+    /// Cached statistics are dropped whenever reports were deleted.
+    pub async fn run_periodically(self, cache: Arc<StatsCache>) {
+        let period =
+            std::time::Duration::from_secs(self.config.purge_interval_hours.saturating_mul(3600));
+        let mut interval = tokio::time::interval(period);
+
+        loop {
+            interval.tick().await;
+
+            match self.purge(false).await {
+                Ok(report) => {
+                    if report.reports_deleted > 0 {
+                        cache.invalidate();
+                    }
+                    info!(
+                        "Retention purge: {} reports deleted, {} logs cleared",
+                        report.reports_deleted, report.log_data_cleared
+                    );
+                }
+                Err(e) => error!("Retention purge failed: {:?}", e),
+            }
+        }
+    }
+}
+
+/// Reports old enough to delete. Reports being triaged, i.e. matched to a known
+/// issue or carrying comments or tags, are always kept.
+fn expired_reports(cutoff: DateTime<Utc>, keep_bug_linked: bool) -> Condition {
+    let mut condition = Condition::all().add(error_report::Column::CreatedAt.lt(cutoff));
+    if keep_bug_linked {
+        condition = condition.add(error_report::Column::BugzillaLink.is_null());
+    }
+    for table in ["error_report_known_issues", "comments", "error_report_tags"] {
+        condition = condition.add(Expr::cust(format!(
+            "NOT EXISTS (SELECT 1 FROM {table} WHERE {table}.error_report_id = error_reports.id)"
+        )));
+    }
+    condition
+}
+
+fn expired_logs(cutoff: DateTime<Utc>) -> Condition {
+    Condition::all()
+        .add(error_report::Column::CreatedAt.lt(cutoff))
+        .add(error_report::Column::LogData.ne(PURGED_LOG_DATA))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::{DbBackend, QueryTrait};
+
+    fn where_clause(condition: Condition) -> String {
+        let sql = ErrorReport::find()
+            .filter(condition)
+            .build(DbBackend::Postgres)
+            .to_string();
+        sql.split_once(" WHERE ").unwrap().1.to_string()
+    }
+
+    #[test]
+    fn test_expired_reports() {
+        let cutoff = "2024-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+
+        let kept_if_linked = where_clause(expired_reports(cutoff, true));
+        assert!(kept_if_linked
+            .contains(r#""error_reports"."created_at" < '2024-01-01 00:00:00 +00:00'"#));
+        assert!(kept_if_linked.contains(r#""error_reports"."bugzilla_link" IS NULL"#));
+        for table in ["error_report_known_issues", "comments", "error_report_tags"] {
+            assert!(kept_if_linked.contains(&format!(
+                "NOT EXISTS (SELECT 1 FROM {table} WHERE {table}.error_report_id = error_reports.id)"
+            )));
+        }
+
+        let all_old = where_clause(expired_reports(cutoff, false));
+        assert!(!all_old.contains("bugzilla_link"));
+        assert!(all_old.contains("NOT EXISTS (SELECT 1 FROM comments"));
+    }
+
+    #[test]
+    fn test_expired_logs() {
+        let cutoff = "2024-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let condition = where_clause(expired_logs(cutoff));
+
+        assert!(
+            condition.contains(r#""error_reports"."created_at" < '2024-01-01 00:00:00 +00:00'"#)
+        );
+        assert!(condition.contains(&format!(
+            r#""error_reports"."log_data" <> '{}'"#,
+            PURGED_LOG_DATA
+        )));
+    }
+}
@@ -0,0 +1,495 @@
+use chrono::{Duration, Utc};
+use futures::future::try_join_all;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, QueryTrait, Set,
+};
+use serde::Serialize;
+use tracing::warn;
+
+use crate::{
+    config::EmailVisibility,
+    models::{
+        error_report::{self, ErrorQuery},
+        saved_search::{self, SavedSearchData},
+        saved_search_subscription, ErrorReport, SavedSearch, SavedSearchSubscription,
+    },
+};
+
+/// Hex digits of the random identifier in the short URL
+const SLUG_LENGTH: usize = 10;
+/// Confirmation messages an address is sent per day, across all searches
+const MAX_CONFIRMATIONS_PER_DAY: u64 = 5;
+
+/// A pinned search with the number of reports it currently matches
+#[derive(Debug, Clone, Serialize)]
+pub struct PinnedSearch {
+    pub search: saved_search::Model,
+    pub count: u64,
+}
+
+/// A saved search as listed on the searches page
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedSearchEntry {
+    pub search: saved_search::Model,
+    pub subscribers: u64,
+}
+
+pub struct SavedSearchService {
+    db: DatabaseConnection,
+    email_visibility: Option<EmailVisibility>,
+}
+
+impl SavedSearchService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self {
+            db,
+            email_visibility: None,
+        }
+    }
+
+    /// How submitter emails are shown publicly, which decides whether searches
+    /// may match reports by email address; see `ErrorQuery::email_visibility`
+    pub fn with_email_visibility(mut self, email_visibility: EmailVisibility) -> Self {
+        self.email_visibility = Some(email_visibility);
+        self
+    }
+
+    /// Shared searches, plus the private ones of `owner` when given
+    pub async fn list(&self, owner: Option<&str>) -> Result<Vec<SavedSearchEntry>, DbErr> {
+        let mut visible = Condition::any().add(saved_search::Column::Shared.eq(true));
+        if let Some(owner) = owner.map(str::trim).filter(|owner| !owner.is_empty()) {
+            visible = visible.add(saved_search::Column::Owner.eq(owner));
+        }
+        self.list_matching(visible).await
+    }
+
+    /// Every search, private ones included, for the admin pages
+    pub async fn list_all(&self) -> Result<Vec<SavedSearchEntry>, DbErr> {
+        self.list_matching(Condition::all()).await
+    }
+
+    async fn list_matching(&self, condition: Condition) -> Result<Vec<SavedSearchEntry>, DbErr> {
+        let searches = SavedSearch::find()
+            .filter(condition)
+            .order_by_asc(saved_search::Column::Name)
+            .all(&self.db)
+            .await?;
+
+        let counts: Vec<(i32, i64)> = SavedSearchSubscription::find()
+            .select_only()
+            .column(saved_search_subscription::Column::SavedSearchId)
+            .column_as(saved_search_subscription::Column::Id.count(), "count")
+            .filter(saved_search_subscription::Column::ConfirmedAt.is_not_null())
+            .group_by(saved_search_subscription::Column::SavedSearchId)
+            .into_tuple()
+            .all(&self.db)
+            .await?;
+
+        Ok(searches
+            .into_iter()
+            .map(|search| SavedSearchEntry {
+                subscribers: counts
+                    .iter()
+                    .find(|(id, _)| *id == search.id)
+                    .map_or(0, |(_, count)| *count as u64),
+                search,
+            })
+            .collect())
+    }
+
+    /// Pinned searches with their live report counts, for the index page
+    pub async fn pinned_with_counts(&self) -> Result<Vec<PinnedSearch>, DbErr> {
+        let searches = SavedSearch::find()
+            .filter(saved_search::Column::Pinned.eq(true))
+            .order_by_asc(saved_search::Column::Name)
+            .all(&self.db)
+            .await?;
+
+        try_join_all(searches.into_iter().map(|search| async move {
+            let count = match self.parse_query(&search) {
+                Some(query) => {
+                    ErrorReport::find()
+                        .filter(query.condition())
+                        .count(&self.db)
+                        .await?
+                }
+                None => 0,
+            };
+            Ok(PinnedSearch { search, count })
+        }))
+        .await
+    }
+
+    pub async fn get(&self, id: i32) -> Result<Option<saved_search::Model>, DbErr> {
+        SavedSearch::find_by_id(id).one(&self.db).await
+    }
+
+    pub async fn get_by_slug(&self, slug: &str) -> Result<Option<saved_search::Model>, DbErr> {
+        SavedSearch::find()
+            .filter(saved_search::Column::Slug.eq(slug))
+            .one(&self.db)
+            .await
+    }
+
+    /// Save a search; the query is expected to have passed validation
+    pub async fn create<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        data: SavedSearchData,
+    ) -> Result<saved_search::Model, DbErr> {
+        let query = normalize_query(&data.query).unwrap_or_default();
+
+        saved_search::ActiveModel {
+            created_at: Set(Utc::now()),
+            slug: Set(new_slug()),
+            name: Set(data.name.trim().to_string()),
+            query: Set(query),
+            owner: Set(data
+                .owner
+                .map(|owner| owner.trim().to_string())
+                .filter(|owner| !owner.is_empty())),
+            shared: Set(data.shared.is_some()),
+            pinned: Set(data.pinned.is_some()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+    }
+
+    pub async fn delete<C: ConnectionTrait>(&self, db: &C, id: i32) -> Result<bool, DbErr> {
+        let result = SavedSearch::delete_by_id(id).exec(db).await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Ask to email `email` about new reports matching the search. Nothing is
+    /// sent to the address until the owner follows the link in a confirmation
+    /// message; the subscription to send it for is returned. Repeated requests
+    /// resend it at most once a day, and an address gets at most
+    /// `MAX_CONFIRMATIONS_PER_DAY` confirmations, so the form can't be used to
+    /// flood a mailbox.
+    pub async fn subscribe(
+        &self,
+        id: i32,
+        email: &str,
+    ) -> Result<Option<saved_search_subscription::Model>, DbErr> {
+        let email = email.trim().to_lowercase();
+        let now = Utc::now();
+        let day_ago = now - Duration::days(1);
+
+        let recent_confirmations = SavedSearchSubscription::find()
+            .filter(saved_search_subscription::Column::Email.eq(&email))
+            .filter(saved_search_subscription::Column::ConfirmationSentAt.gt(day_ago))
+            .count(&self.db)
+            .await?;
+        if recent_confirmations >= MAX_CONFIRMATIONS_PER_DAY {
+            return Ok(None);
+        }
+
+        let existing = SavedSearchSubscription::find()
+            .filter(saved_search_subscription::Column::SavedSearchId.eq(id))
+            .filter(saved_search_subscription::Column::Email.eq(&email))
+            .one(&self.db)
+            .await?;
+        match existing {
+            None => saved_search_subscription::ActiveModel {
+                saved_search_id: Set(id),
+                email: Set(email),
+                token: Set(new_token()),
+                created_at: Set(now),
+                confirmation_sent_at: Set(now),
+                confirmed_at: Set(None),
+                ..Default::default()
+            }
+            .insert(&self.db)
+            .await
+            .map(Some),
+            Some(subscription)
+                if subscription.confirmed_at.is_none()
+                    && subscription.confirmation_sent_at <= day_ago =>
+            {
+                let mut active: saved_search_subscription::ActiveModel = subscription.into();
+                active.confirmation_sent_at = Set(now);
+                active.update(&self.db).await.map(Some)
+            }
+            Some(_) => Ok(None),
+        }
+    }
+
+    /// The subscription a confirmation or unsubscribe link stands for, with
+    /// its search
+    pub async fn find_subscription(
+        &self,
+        token: &str,
+    ) -> Result<Option<(saved_search_subscription::Model, saved_search::Model)>, DbErr> {
+        let found = SavedSearchSubscription::find()
+            .filter(saved_search_subscription::Column::Token.eq(token))
+            .find_also_related(SavedSearch)
+            .one(&self.db)
+            .await?;
+        Ok(found.and_then(|(subscription, search)| Some((subscription, search?))))
+    }
+
+    /// Start notifying a subscription; confirming twice has no effect
+    pub async fn confirm<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        subscription: saved_search_subscription::Model,
+    ) -> Result<saved_search_subscription::Model, DbErr> {
+        if subscription.confirmed_at.is_some() {
+            return Ok(subscription);
+        }
+        let mut active: saved_search_subscription::ActiveModel = subscription.into();
+        active.confirmed_at = Set(Some(Utc::now()));
+        active.update(db).await
+    }
+
+    pub async fn unsubscribe<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        subscription_id: i32,
+    ) -> Result<bool, DbErr> {
+        let result = SavedSearchSubscription::delete_by_id(subscription_id)
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    pub async fn get_subscription(
+        &self,
+        subscription_id: i32,
+    ) -> Result<Option<saved_search_subscription::Model>, DbErr> {
+        SavedSearchSubscription::find_by_id(subscription_id)
+            .one(&self.db)
+            .await
+    }
+
+    /// Confirmed subscriptions of a search
+    pub async fn subscribers(
+        &self,
+        id: i32,
+    ) -> Result<Vec<saved_search_subscription::Model>, DbErr> {
+        SavedSearchSubscription::find()
+            .filter(saved_search_subscription::Column::SavedSearchId.eq(id))
+            .filter(saved_search_subscription::Column::ConfirmedAt.is_not_null())
+            .order_by_asc(saved_search_subscription::Column::Email)
+            .all(&self.db)
+            .await
+    }
+
+    /// Searches with at least one subscriber that the given report matches
+    pub async fn subscribed_matches(
+        &self,
+        report: &error_report::Model,
+    ) -> Result<Vec<saved_search::Model>, DbErr> {
+        let searches = SavedSearch::find()
+            .filter(
+                saved_search::Column::Id.in_subquery(
+                    SavedSearchSubscription::find()
+                        .select_only()
+                        .column(saved_search_subscription::Column::SavedSearchId)
+                        .filter(saved_search_subscription::Column::ConfirmedAt.is_not_null())
+                        .into_query(),
+                ),
+            )
+            .order_by_asc(saved_search::Column::Id)
+            .all(&self.db)
+            .await?;
+
+        let mut matched = Vec::new();
+        for search in searches {
+            let Some(query) = self.parse_query(&search) else {
+                continue;
+            };
+            // Evaluate the filters in the database so that they behave exactly
+            // as on the error list
+            let hits = ErrorReport::find()
+                .filter(error_report::Column::Id.eq(report.id))
+                .filter(query.condition())
+                .count(&self.db)
+                .await?;
+            if hits > 0 {
+                matched.push(search);
+            }
+        }
+        Ok(matched)
+    }
+
+    fn parse_query(&self, search: &saved_search::Model) -> Option<ErrorQuery> {
+        let mut query = parse_query(search)?;
+        query.email_visibility = self.email_visibility;
+        Some(query)
+    }
+}
+
+/// Subject and body of the notification sent to one subscriber of a search
+pub fn saved_search_message(
+    search: &saved_search::Model,
+    report: &error_report::Model,
+    subscription: &saved_search_subscription::Model,
+    base_url: &str,
+) -> (String, String) {
+    let subject = format!(
+        "[{}] {}:{} failed on {}",
+        search.name, report.failure_package, report.failure_task, report.machine
+    );
+    let body = format!(
+        "A new error report matches the saved search \"{}\".\n\n\
+         Package: {}\nTask: {}\nMachine: {}\nDistro: {} {}\n\n\
+         {}/Errors/Details/{}/\n\n\
+         All matching reports: {}/s/{}\n\
+         Unsubscribe: {}\n",
+        search.name,
+        report.failure_package,
+        report.failure_task,
+        report.machine,
+        report.distro,
+        report.distro_version,
+        base_url,
+        report.id,
+        base_url,
+        search.slug,
+        subscription_url(base_url, subscription, "unsubscribe")
+    );
+    (subject, body)
+}
+
+/// Subject and body of the message asking the owner of an address to confirm
+/// a subscription
+pub fn confirmation_message(
+    search: &saved_search::Model,
+    subscription: &saved_search_subscription::Model,
+    base_url: &str,
+) -> (String, String) {
+    let subject = format!("Confirm your subscription to \"{}\"", search.name);
+    let body = format!(
+        "Someone asked to email {} about every new error report matching the saved\n\
+         search \"{}\" ({}/s/{}).\n\n\
+         To start receiving these emails, confirm here:\n{}\n\n\
+         If you didn't ask for this, ignore this message and nothing more will be sent.\n",
+        subscription.email,
+        search.name,
+        base_url,
+        search.slug,
+        subscription_url(base_url, subscription, "confirm")
+    );
+    (subject, body)
+}
+
+/// Link to confirm or cancel a subscription, carrying its secret token
+pub fn subscription_url(
+    base_url: &str,
+    subscription: &saved_search_subscription::Model,
+    action: &str,
+) -> String {
+    format!(
+        "{}/Searches/subscriptions/{}/{}",
+        base_url, subscription.token, action
+    )
+}
+
+/// The filters of a stored search; stored queries are normalized when saved,
+/// so this only fails if the query syntax changed since
+pub fn parse_query(search: &saved_search::Model) -> Option<ErrorQuery> {
+    match ErrorQuery::from_query_string(&search.query) {
+        Ok(query) => Some(query),
+        Err(e) => {
+            warn!("Saved search {} has an invalid query: {}", search.id, e);
+            None
+        }
+    }
+}
+
+/// The filters, sort order and page size of an error list query string,
+/// without the page number and empty parameters
+pub fn normalize_query(query: &str) -> Result<String, serde_html_form::de::Error> {
+    ErrorQuery::from_query_string(query).map(|query| query.listing_query())
+}
+
+fn new_slug() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..SLUG_LENGTH].to_string()
+}
+
+/// 64 hex digits from two random UUIDs, for subscription links
+fn new_token() -> String {
+    format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_query() {
+        assert_eq!(
+            normalize_query("?page=4&machine=qemuarm&distro=&sort=-id").unwrap(),
+            "machine=qemuarm&sort=-id"
+        );
+        assert_eq!(normalize_query("").unwrap(), "");
+        assert!(normalize_query("id_from=abc").is_err());
+    }
+
+    #[test]
+    fn test_subscription_links() {
+        let search = saved_search::Model {
+            id: 3,
+            created_at: Utc::now(),
+            slug: "0123456789".to_string(),
+            name: "qemuarm failures".to_string(),
+            query: "machine=qemuarm".to_string(),
+            owner: None,
+            shared: true,
+            pinned: false,
+        };
+        let subscription = saved_search_subscription::Model {
+            id: 7,
+            saved_search_id: 3,
+            email: "dev@example.com".to_string(),
+            token: new_token(),
+            created_at: Utc::now(),
+            confirmation_sent_at: Utc::now(),
+            confirmed_at: None,
+        };
+        assert_eq!(subscription.token.len(), 64);
+        assert_ne!(subscription.token, new_token());
+
+        let (_, body) = confirmation_message(&search, &subscription, "https://errors.example.com");
+        assert!(body.contains(&format!(
+            "https://errors.example.com/Searches/subscriptions/{}/confirm",
+            subscription.token
+        )));
+        assert!(body.contains("dev@example.com"));
+    }
+
+    #[test]
+    fn test_is_owned_by() {
+        let mut search = saved_search::Model {
+            id: 3,
+            created_at: Utc::now(),
+            slug: new_slug(),
+            name: "qemuarm failures".to_string(),
+            query: "machine=qemuarm".to_string(),
+            owner: Some("jdoe".to_string()),
+            shared: true,
+            pinned: false,
+        };
+        assert!(search.is_owned_by(Some(" jdoe ")));
+        assert!(!search.is_owned_by(Some("someone")));
+        assert!(!search.is_owned_by(None));
+
+        search.owner = None;
+        assert!(!search.is_owned_by(Some("")));
+    }
+
+    #[test]
+    fn test_new_slug() {
+        let slug = new_slug();
+        assert_eq!(slug.len(), SLUG_LENGTH);
+        assert!(slug.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(slug, new_slug());
+    }
+}
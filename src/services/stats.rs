@@ -1,32 +1,247 @@
-use chrono::{DateTime, Duration, Utc};
+use chrono::{
+    DateTime, Datelike, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike,
+    Utc,
+};
+use chrono_tz::Tz;
 use sea_orm::{
-    Alias, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, Expr, PaginatorTrait, QueryFilter,
-    QueryOrder, QuerySelect,
+    sea_query::{Alias, Expr, Func, SimpleExpr},
+    ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+use tracing::{error, info};
+
+use crate::{
+    models::{
+        error_report::{self, ErrorQuery},
+        stats_rollup, ErrorReport, StatsRollup,
+    },
+    services::stats_cache::StatsCache,
 };
-use serde::Serialize;
 
-use crate::models::{error_report, ErrorReport};
+/// Number of entries in each "top" list when not configured otherwise
+pub const DEFAULT_TOP_N: u64 = 10;
+const MAX_TOP_N: u64 = 100;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ErrorStats {
     pub total_errors: u64,
     pub errors_by_type: Vec<(String, u64)>,
     pub errors_by_machine: Vec<(String, u64)>,
     pub errors_by_distro: Vec<(String, u64)>,
     pub errors_by_package: Vec<(String, u64)>,
+    /// Host distributions (`nativelsbstring`) of the build machines
+    pub errors_by_host_distro: Vec<(String, u64)>,
+    pub errors_by_build_sys: Vec<(String, u64)>,
     pub recent_errors: Vec<error_report::Model>,
     pub daily_stats: Vec<DailyStats>,
+    pub series: SeriesInfo,
     pub top_submitters: Vec<(String, u64)>,
     pub error_trends: ErrorTrends,
+    /// When these numbers were computed
+    pub generated_at: DateTime<Utc>,
+    /// Seconds since `generated_at` when served from the stats cache
+    pub cache_age_seconds: i64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DailyStats {
     pub date: String,
     pub count: u64,
 }
 
-#[derive(Debug, Serialize)]
+/// Describes how `daily_stats` was bucketed
+#[derive(Debug, Clone, Serialize)]
+pub struct SeriesInfo {
+    pub granularity: Granularity,
+    pub days: u32,
+    pub tz: String,
+}
+
+/// Bucket size for the error count time series
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    Hour,
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+impl Granularity {
+    fn sql_unit(self) -> &'static str {
+        match self {
+            Granularity::Hour => "hour",
+            Granularity::Day => "day",
+            Granularity::Week => "week",
+            Granularity::Month => "month",
+        }
+    }
+
+    fn label_format(self) -> &'static str {
+        match self {
+            Granularity::Hour => "%Y-%m-%d %H:00",
+            Granularity::Day | Granularity::Week => "%Y-%m-%d",
+            Granularity::Month => "%Y-%m",
+        }
+    }
+
+    /// Start of the bucket containing `time`; weeks start on Monday like `date_trunc`
+    fn truncate(self, time: NaiveDateTime) -> NaiveDateTime {
+        let date = time.date();
+        match self {
+            Granularity::Hour => date.and_hms_opt(time.hour(), 0, 0).unwrap_or(time),
+            Granularity::Day => date.and_time(NaiveTime::MIN),
+            Granularity::Week => (date
+                - Duration::days(date.weekday().num_days_from_monday().into()))
+            .and_time(NaiveTime::MIN),
+            Granularity::Month => date.with_day(1).unwrap_or(date).and_time(NaiveTime::MIN),
+        }
+    }
+
+    fn next(self, bucket: NaiveDateTime) -> NaiveDateTime {
+        match self {
+            Granularity::Hour => bucket + Duration::hours(1),
+            Granularity::Day => bucket + Duration::days(1),
+            Granularity::Week => bucket + Duration::weeks(1),
+            Granularity::Month => bucket
+                .checked_add_months(Months::new(1))
+                .unwrap_or(bucket + Duration::days(31)),
+        }
+    }
+}
+
+impl std::fmt::Display for Granularity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.sql_unit())
+    }
+}
+
+/// Query parameters shaping the statistics; filters are taken from `ErrorQuery`
+#[derive(Debug, Default, Deserialize)]
+pub struct StatsParams {
+    pub granularity: Option<Granularity>,
+    pub days: Option<u32>,
+    pub tz: Option<String>,
+    pub top: Option<u64>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StatsParamsError {
+    #[error("Unknown time zone: {0}")]
+    UnknownTimeZone(String),
+    #[error("days must be between 1 and {MAX_SERIES_DAYS}")]
+    InvalidRange,
+    #[error("Too many buckets requested; use a coarser granularity or shorter range")]
+    TooManyBuckets,
+    #[error("top must be between 1 and {MAX_TOP_N}")]
+    InvalidTop,
+}
+
+const MAX_SERIES_DAYS: u32 = 3660;
+const MAX_SERIES_BUCKETS: i64 = 2000;
+
+/// Range, bucket size and time zone of the error count time series
+#[derive(Debug, Clone)]
+pub struct TimeSeriesOptions {
+    pub granularity: Granularity,
+    pub days: u32,
+    pub tz: Tz,
+}
+
+impl Default for TimeSeriesOptions {
+    fn default() -> Self {
+        Self {
+            granularity: Granularity::Day,
+            days: 30,
+            tz: Tz::UTC,
+        }
+    }
+}
+
+impl TimeSeriesOptions {
+    pub fn from_params(params: &StatsParams) -> Result<Self, StatsParamsError> {
+        let defaults = Self::default();
+        let granularity = params.granularity.unwrap_or(defaults.granularity);
+        let days = params.days.unwrap_or(defaults.days);
+        let tz = match params.tz.as_deref().filter(|tz| !tz.is_empty()) {
+            Some(name) => name
+                .parse()
+                .map_err(|_| StatsParamsError::UnknownTimeZone(name.to_string()))?,
+            None => defaults.tz,
+        };
+
+        if days == 0 || days > MAX_SERIES_DAYS {
+            return Err(StatsParamsError::InvalidRange);
+        }
+        if granularity == Granularity::Hour && i64::from(days) * 24 > MAX_SERIES_BUCKETS {
+            return Err(StatsParamsError::TooManyBuckets);
+        }
+
+        Ok(Self {
+            granularity,
+            days,
+            tz,
+        })
+    }
+}
+
+/// Everything that shapes a statistics request
+#[derive(Debug, Clone)]
+pub struct StatsOptions {
+    pub series: TimeSeriesOptions,
+    pub top: u64,
+    pub filter: Condition,
+    /// Identifies equivalent requests in the stats cache
+    pub cache_key: String,
+}
+
+impl StatsOptions {
+    pub fn new(
+        params: &StatsParams,
+        query: &ErrorQuery,
+        default_top: u64,
+    ) -> Result<Self, StatsParamsError> {
+        let top = params.top.unwrap_or(default_top);
+        if top == 0 || top > MAX_TOP_N {
+            return Err(StatsParamsError::InvalidTop);
+        }
+
+        let series = TimeSeriesOptions::from_params(params)?;
+        Ok(Self {
+            cache_key: cache_key(query, &series, top),
+            series,
+            top,
+            filter: query.filters(),
+        })
+    }
+
+    /// Unfiltered statistics with the default time series
+    pub fn unfiltered(top: u64) -> Self {
+        let series = TimeSeriesOptions::default();
+        Self {
+            cache_key: cache_key(&ErrorQuery::default(), &series, top),
+            series,
+            top,
+            filter: Condition::all(),
+        }
+    }
+}
+
+fn cache_key(query: &ErrorQuery, series: &TimeSeriesOptions, top: u64) -> String {
+    format!(
+        "{}|{}|{}|{}|{}",
+        query.filter_query(),
+        series.granularity,
+        series.days,
+        series.tz.name(),
+        top
+    )
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct ErrorTrends {
     pub this_week: u64,
     pub last_week: u64,
@@ -38,50 +253,102 @@ pub struct ErrorTrends {
 
 pub struct StatsService {
     db: DatabaseConnection,
+    use_rollups: bool,
 }
 
 impl StatsService {
     pub fn new(db: DatabaseConnection) -> Self {
-        Self { db }
+        Self {
+            db,
+            use_rollups: false,
+        }
+    }
+
+    /// Serve unfiltered totals and top-N lists from `stats_daily_rollups`
+    pub fn with_rollups(mut self, enabled: bool) -> Self {
+        self.use_rollups = enabled;
+        self
+    }
+
+    /// Like `get_error_stats`, but reuses results from `cache` while they are fresh
+    pub async fn get_cached_error_stats(
+        &self,
+        cache: &StatsCache,
+        options: &StatsOptions,
+    ) -> Result<ErrorStats, DbErr> {
+        if let Some(stats) = cache.get(&options.cache_key) {
+            return Ok(stats);
+        }
+
+        let stats = self.get_error_stats(options).await?;
+        cache.insert(options.cache_key.clone(), &stats);
+        Ok(stats)
     }
 
-    pub async fn get_error_stats(&self) -> Result<ErrorStats, DbErr> {
+    /// Compute every aggregate, restricted to the reports matching `options.filter`.
+    /// Hidden reports are never counted, and `stats_daily_rollups` leaves them out too.
+    pub async fn get_error_stats(&self, options: &StatsOptions) -> Result<ErrorStats, DbErr> {
+        let filter = &options.filter;
+        let top = options.top;
+        let series = &options.series;
+        let from_rollups = self.use_rollups && filter.is_empty();
+
         // Total errors
-        let total_errors = ErrorReport::find().count(&self.db).await?;
+        let total_errors = if from_rollups {
+            self.get_rollup_total().await?
+        } else {
+            ErrorReport::find()
+                .filter(error_report::visible())
+                .filter(filter.clone())
+                .count(&self.db)
+                .await?
+        };
 
         // Errors by type
         let errors_by_type = self
-            .get_grouped_stats(error_report::Column::ErrorType)
+            .get_grouped_stats(error_report::Column::ErrorType, filter, top)
             .await?;
 
         // Errors by machine
         let errors_by_machine = self
-            .get_grouped_stats(error_report::Column::Machine)
+            .get_grouped_stats(error_report::Column::Machine, filter, top)
             .await?;
 
         // Errors by distro
-        let errors_by_distro = self.get_grouped_stats(error_report::Column::Distro).await?;
+        let errors_by_distro = self
+            .get_grouped_stats(error_report::Column::Distro, filter, top)
+            .await?;
 
         // Errors by package (top failures)
         let errors_by_package = self
-            .get_grouped_stats(error_report::Column::FailurePackage)
+            .get_grouped_stats(error_report::Column::FailurePackage, filter, top)
+            .await?;
+
+        // Errors by build host
+        let errors_by_host_distro = self
+            .get_grouped_stats(error_report::Column::Nativelsbstring, filter, top)
+            .await?;
+        let errors_by_build_sys = self
+            .get_grouped_stats(error_report::Column::BuildSys, filter, top)
             .await?;
 
         // Recent errors
         let recent_errors = ErrorReport::find()
+            .filter(error_report::visible())
+            .filter(filter.clone())
             .order_by_desc(error_report::Column::CreatedAt)
             .limit(10)
             .all(&self.db)
             .await?;
 
-        // Daily stats for the last 30 days
-        let daily_stats = self.get_daily_stats().await?;
+        // Error counts over the requested range
+        let daily_stats = self.get_daily_stats(series, filter).await?;
 
         // Top submitters (by email)
-        let top_submitters = self.get_top_submitters().await?;
+        let top_submitters = self.get_top_submitters(filter, top).await?;
 
         // Error trends
-        let error_trends = self.get_error_trends().await?;
+        let error_trends = self.get_error_trends(filter).await?;
 
         Ok(ErrorStats {
             total_errors,
@@ -89,24 +356,43 @@ impl StatsService {
             errors_by_machine,
             errors_by_distro,
             errors_by_package,
+            errors_by_host_distro,
+            errors_by_build_sys,
             recent_errors,
             daily_stats,
+            series: SeriesInfo {
+                granularity: series.granularity,
+                days: series.days,
+                tz: series.tz.name().to_string(),
+            },
             top_submitters,
             error_trends,
+            generated_at: Utc::now(),
+            cache_age_seconds: 0,
         })
     }
 
     async fn get_grouped_stats(
         &self,
         column: error_report::Column,
+        filter: &Condition,
+        top: u64,
     ) -> Result<Vec<(String, u64)>, DbErr> {
+        if self.use_rollups && filter.is_empty() {
+            if let Some(rollup_column) = rollup_column(column) {
+                return self.get_rollup_grouped_stats(rollup_column, top).await;
+            }
+        }
+
         let results = ErrorReport::find()
+            .filter(error_report::visible())
             .select_only()
             .column(column)
             .column_as(error_report::Column::Id.count(), "count")
+            .filter(filter.clone())
             .group_by(column)
             .order_by_desc(Expr::col(Alias::new("count")))
-            .limit(10)
+            .limit(top)
             .into_tuple::<(String, i64)>()
             .all(&self.db)
             .await?;
@@ -117,41 +403,74 @@ impl StatsService {
             .collect())
     }
 
-    async fn get_daily_stats(&self) -> Result<Vec<DailyStats>, DbErr> {
-        let thirty_days_ago = Utc::now() - Duration::days(30);
+    async fn get_daily_stats(
+        &self,
+        series: &TimeSeriesOptions,
+        filter: &Condition,
+    ) -> Result<Vec<DailyStats>, DbErr> {
+        if self.use_rollups
+            && filter.is_empty()
+            && series.granularity == Granularity::Day
+            && series.tz == Tz::UTC
+        {
+            return self.get_rollup_daily_stats(series.days).await;
+        }
 
-        // For now, return a simple implementation
-        // In production, you might want to use raw SQL for better date handling
-        let all_errors = ErrorReport::find()
-            .filter(error_report::Column::CreatedAt.gte(thirty_days_ago))
+        let granularity = series.granularity;
+        let now_local = Utc::now().with_timezone(&series.tz).naive_local();
+        let first_bucket = granularity.truncate(now_local - Duration::days(series.days.into()));
+        let last_bucket = granularity.truncate(now_local);
+
+        // The first bucket may start at a local time skipped by a DST change
+        let since = series
+            .tz
+            .from_local_datetime(&first_bucket)
+            .earliest()
+            .map(|start| start.with_timezone(&Utc))
+            .unwrap_or_else(|| Utc::now() - Duration::days(i64::from(series.days) + 1));
+
+        let rows = ErrorReport::find()
+            .filter(error_report::visible())
+            .select_only()
+            .column_as(
+                bucket_expr(self.db.get_database_backend(), granularity, series.tz),
+                "bucket",
+            )
+            .column_as(error_report::Column::Id.count(), "count")
+            .filter(error_report::Column::CreatedAt.gte(since))
+            .filter(filter.clone())
+            .group_by(Expr::col(Alias::new("bucket")))
+            .into_tuple::<(String, i64)>()
             .all(&self.db)
             .await?;
 
-        // Group by date manually
-        let mut daily_counts = std::collections::HashMap::new();
-        for error in all_errors {
-            let date_str = error.created_at.format("%Y-%m-%d").to_string();
-            *daily_counts.entry(date_str).or_insert(0) += 1;
-        }
-
-        let mut results: Vec<DailyStats> = daily_counts
+        let counts = rows
             .into_iter()
-            .map(|(date, count)| DailyStats { date, count })
+            .filter_map(|(bucket, count)| {
+                NaiveDateTime::parse_from_str(&bucket, BUCKET_FORMAT)
+                    .ok()
+                    .map(|bucket| (bucket, count as u64))
+            })
             .collect();
 
-        results.sort_by(|a, b| a.date.cmp(&b.date));
-        Ok(results)
+        Ok(zero_filled(granularity, first_bucket, last_bucket, &counts))
     }
 
-    async fn get_top_submitters(&self) -> Result<Vec<(String, u64)>, DbErr> {
+    async fn get_top_submitters(
+        &self,
+        filter: &Condition,
+        top: u64,
+    ) -> Result<Vec<(String, u64)>, DbErr> {
         let results = ErrorReport::find()
+            .filter(error_report::visible())
             .select_only()
             .column(error_report::Column::SubmitterEmail)
             .column_as(error_report::Column::Id.count(), "count")
             .filter(error_report::Column::SubmitterEmail.is_not_null())
+            .filter(filter.clone())
             .group_by(error_report::Column::SubmitterEmail)
             .order_by_desc(Expr::col(Alias::new("count")))
-            .limit(10)
+            .limit(top)
             .into_tuple::<(Option<String>, i64)>()
             .all(&self.db)
             .await?;
@@ -162,7 +481,7 @@ impl StatsService {
             .collect())
     }
 
-    async fn get_error_trends(&self) -> Result<ErrorTrends, DbErr> {
+    async fn get_error_trends(&self, filter: &Condition) -> Result<ErrorTrends, DbErr> {
         let now = Utc::now();
         let week_ago = now - Duration::weeks(1);
         let two_weeks_ago = now - Duration::weeks(2);
@@ -171,12 +490,16 @@ impl StatsService {
 
         // This week
         let this_week = ErrorReport::find()
+            .filter(error_report::visible())
+            .filter(filter.clone())
             .filter(error_report::Column::CreatedAt.gte(week_ago))
             .count(&self.db)
             .await?;
 
         // Last week
         let last_week = ErrorReport::find()
+            .filter(error_report::visible())
+            .filter(filter.clone())
             .filter(error_report::Column::CreatedAt.gte(two_weeks_ago))
             .filter(error_report::Column::CreatedAt.lt(week_ago))
             .count(&self.db)
@@ -184,12 +507,16 @@ impl StatsService {
 
         // This month
         let this_month = ErrorReport::find()
+            .filter(error_report::visible())
+            .filter(filter.clone())
             .filter(error_report::Column::CreatedAt.gte(month_ago))
             .count(&self.db)
             .await?;
 
         // Last month
         let last_month = ErrorReport::find()
+            .filter(error_report::visible())
+            .filter(filter.clone())
             .filter(error_report::Column::CreatedAt.gte(two_months_ago))
             .filter(error_report::Column::CreatedAt.lt(month_ago))
             .count(&self.db)
@@ -218,21 +545,295 @@ impl StatsService {
         })
     }
 
+    async fn get_rollup_total(&self) -> Result<u64, DbErr> {
+        let total = StatsRollup::find()
+            .select_only()
+            .column_as(sum_of_counts(), "total")
+            .into_tuple::<Option<i64>>()
+            .one(&self.db)
+            .await?;
+
+        Ok(total.flatten().unwrap_or(0) as u64)
+    }
+
+    async fn get_rollup_grouped_stats(
+        &self,
+        column: stats_rollup::Column,
+        top: u64,
+    ) -> Result<Vec<(String, u64)>, DbErr> {
+        let results = StatsRollup::find()
+            .select_only()
+            .column(column)
+            .column_as(sum_of_counts(), "count")
+            .group_by(column)
+            .order_by_desc(Expr::col(Alias::new("count")))
+            .limit(top)
+            .into_tuple::<(String, i64)>()
+            .all(&self.db)
+            .await?;
+
+        Ok(results
+            .into_iter()
+            .map(|(name, count)| (name, count as u64))
+            .collect())
+    }
+
+    async fn get_rollup_daily_stats(&self, days: u32) -> Result<Vec<DailyStats>, DbErr> {
+        let today = Utc::now().date_naive();
+        let first_day = today - Duration::days(days.into());
+
+        let rows = StatsRollup::find()
+            .select_only()
+            .column(stats_rollup::Column::Day)
+            .column_as(sum_of_counts(), "count")
+            .filter(stats_rollup::Column::Day.gte(first_day))
+            .group_by(stats_rollup::Column::Day)
+            .into_tuple::<(NaiveDate, i64)>()
+            .all(&self.db)
+            .await?;
+
+        let counts = rows
+            .into_iter()
+            .map(|(day, count)| (day.and_time(NaiveTime::MIN), count as u64))
+            .collect();
+
+        Ok(zero_filled(
+            Granularity::Day,
+            first_day.and_time(NaiveTime::MIN),
+            today.and_time(NaiveTime::MIN),
+            &counts,
+        ))
+    }
+
+    /// Recompute `stats_daily_rollups` without blocking readers
+    pub async fn refresh_rollups(&self) -> Result<(), DbErr> {
+        self.db
+            .execute_unprepared("REFRESH MATERIALIZED VIEW CONCURRENTLY stats_daily_rollups")
+            .await
+            .map(|_| ())
+    }
+
+    /// Refresh the rollups on the given interval until the process exits,
+    /// dropping cached results after each refresh
+    pub async fn run_rollup_refresh(self, period: std::time::Duration, cache: Arc<StatsCache>) {
+        let mut interval = tokio::time::interval(period);
+
+        loop {
+            interval.tick().await;
+
+            match self.refresh_rollups().await {
+                Ok(()) => {
+                    cache.invalidate();
+                    info!("Refreshed statistics rollups");
+                }
+                Err(e) => error!("Failed to refresh statistics rollups: {:?}", e),
+            }
+        }
+    }
+
     pub async fn get_machine_stats(&self) -> Result<Vec<(String, u64)>, DbErr> {
-        self.get_grouped_stats(error_report::Column::Machine).await
+        self.get_grouped_stats(
+            error_report::Column::Machine,
+            &Condition::all(),
+            DEFAULT_TOP_N,
+        )
+        .await
     }
 
     pub async fn get_distro_stats(&self) -> Result<Vec<(String, u64)>, DbErr> {
-        self.get_grouped_stats(error_report::Column::Distro).await
+        self.get_grouped_stats(
+            error_report::Column::Distro,
+            &Condition::all(),
+            DEFAULT_TOP_N,
+        )
+        .await
     }
 
     pub async fn get_error_type_stats(&self) -> Result<Vec<(String, u64)>, DbErr> {
-        self.get_grouped_stats(error_report::Column::ErrorType)
-            .await
+        self.get_grouped_stats(
+            error_report::Column::ErrorType,
+            &Condition::all(),
+            DEFAULT_TOP_N,
+        )
+        .await
     }
 
     pub async fn get_package_failure_stats(&self) -> Result<Vec<(String, u64)>, DbErr> {
-        self.get_grouped_stats(error_report::Column::FailurePackage)
-            .await
+        self.get_grouped_stats(
+            error_report::Column::FailurePackage,
+            &Condition::all(),
+            DEFAULT_TOP_N,
+        )
+        .await
+    }
+}
+
+/// `SUM(count)` over the rollup; Postgres returns NUMERIC for sums of BIGINT
+fn sum_of_counts() -> SimpleExpr {
+    Func::cast_as(
+        Func::sum(Expr::col(stats_rollup::Column::Count)),
+        Alias::new("BIGINT"),
+    )
+    .into()
+}
+
+fn rollup_column(column: error_report::Column) -> Option<stats_rollup::Column> {
+    match column {
+        error_report::Column::Machine => Some(stats_rollup::Column::Machine),
+        error_report::Column::Distro => Some(stats_rollup::Column::Distro),
+        error_report::Column::ErrorType => Some(stats_rollup::Column::ErrorType),
+        error_report::Column::FailurePackage => Some(stats_rollup::Column::FailurePackage),
+        _ => None,
+    }
+}
+
+/// Format of the bucket start returned by `bucket_expr`
+const BUCKET_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// SQL expression truncating `created_at` to the start of its bucket in the
+/// given time zone, rendered as text so every backend returns the same shape.
+/// SQLite has no time zone support, so buckets are computed in UTC there.
+fn bucket_expr(backend: DbBackend, granularity: Granularity, tz: Tz) -> SimpleExpr {
+    match backend {
+        DbBackend::Postgres => Expr::cust_with_values(
+            "to_char(date_trunc($1, created_at AT TIME ZONE $2), 'YYYY-MM-DD HH24:MI:SS')",
+            [granularity.sql_unit(), tz.name()],
+        ),
+        DbBackend::MySql => {
+            let local = "CONVERT_TZ(created_at, '+00:00', ?)";
+            match granularity {
+                Granularity::Hour => Expr::cust_with_values(
+                    format!("DATE_FORMAT({}, '%Y-%m-%d %H:00:00')", local),
+                    [tz.name()],
+                ),
+                Granularity::Day => Expr::cust_with_values(
+                    format!("DATE_FORMAT({}, '%Y-%m-%d 00:00:00')", local),
+                    [tz.name()],
+                ),
+                Granularity::Week => Expr::cust_with_values(
+                    format!(
+                        "DATE_FORMAT(DATE_SUB({0}, INTERVAL WEEKDAY({0}) DAY), '%Y-%m-%d 00:00:00')",
+                        local
+                    ),
+                    [tz.name(), tz.name()],
+                ),
+                Granularity::Month => Expr::cust_with_values(
+                    format!("DATE_FORMAT({}, '%Y-%m-01 00:00:00')", local),
+                    [tz.name()],
+                ),
+            }
+        }
+        DbBackend::Sqlite => Expr::cust(match granularity {
+            Granularity::Hour => "strftime('%Y-%m-%d %H:00:00', created_at)",
+            Granularity::Day => "strftime('%Y-%m-%d 00:00:00', created_at)",
+            Granularity::Week => {
+                "strftime('%Y-%m-%d 00:00:00', created_at, 'weekday 0', '-6 days')"
+            }
+            Granularity::Month => "strftime('%Y-%m-01 00:00:00', created_at)",
+        }),
+    }
+}
+
+/// One entry per bucket from `first` to `last`, with zero for buckets without errors
+fn zero_filled(
+    granularity: Granularity,
+    first: NaiveDateTime,
+    last: NaiveDateTime,
+    counts: &HashMap<NaiveDateTime, u64>,
+) -> Vec<DailyStats> {
+    let mut results = Vec::new();
+    let mut bucket = first;
+    while bucket <= last {
+        results.push(DailyStats {
+            date: bucket.format(granularity.label_format()).to_string(),
+            count: counts.get(&bucket).copied().unwrap_or(0),
+        });
+        bucket = granularity.next(bucket);
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(y: i32, m: u32, d: u32, h: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, 30, 15)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_truncate() {
+        // 2024-03-14 is a Thursday
+        let time = at(2024, 3, 14, 17);
+        assert_eq!(
+            Granularity::Hour.truncate(time).to_string(),
+            "2024-03-14 17:00:00"
+        );
+        assert_eq!(
+            Granularity::Day.truncate(time).to_string(),
+            "2024-03-14 00:00:00"
+        );
+        assert_eq!(
+            Granularity::Week.truncate(time).to_string(),
+            "2024-03-11 00:00:00"
+        );
+        assert_eq!(
+            Granularity::Month.truncate(time).to_string(),
+            "2024-03-01 00:00:00"
+        );
+    }
+
+    #[test]
+    fn test_zero_filled() {
+        let first = Granularity::Month.truncate(at(2023, 11, 20, 0));
+        let last = Granularity::Month.truncate(at(2024, 2, 3, 0));
+        let counts = HashMap::from([(Granularity::Month.truncate(at(2024, 1, 9, 0)), 7)]);
+
+        let series = zero_filled(Granularity::Month, first, last, &counts);
+        let labels: Vec<_> = series.iter().map(|s| (s.date.as_str(), s.count)).collect();
+        assert_eq!(
+            labels,
+            vec![
+                ("2023-11", 0),
+                ("2023-12", 0),
+                ("2024-01", 7),
+                ("2024-02", 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_time_series_options() {
+        let params = StatsParams {
+            granularity: Some(Granularity::Week),
+            days: Some(90),
+            tz: Some("Europe/Berlin".to_string()),
+            top: None,
+        };
+        let options = TimeSeriesOptions::from_params(&params).unwrap();
+        assert_eq!(options.tz, Tz::Europe__Berlin);
+
+        let bad_tz = StatsParams {
+            tz: Some("Mars/Olympus".to_string()),
+            ..Default::default()
+        };
+        assert!(TimeSeriesOptions::from_params(&bad_tz).is_err());
+
+        let too_many = StatsParams {
+            granularity: Some(Granularity::Hour),
+            days: Some(365),
+            ..Default::default()
+        };
+        assert!(TimeSeriesOptions::from_params(&too_many).is_err());
+
+        let no_top = StatsParams {
+            top: Some(0),
+            ..Default::default()
+        };
+        assert!(StatsOptions::new(&no_top, &ErrorQuery::default(), DEFAULT_TOP_N).is_err());
     }
 }
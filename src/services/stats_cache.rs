@@ -0,0 +1,125 @@
+use chrono::{Duration, Utc};
+use std::{collections::HashMap, sync::RwLock};
+
+use crate::services::stats::ErrorStats;
+
+/// Upper bound on cached filter combinations; expired entries are dropped first
+const MAX_ENTRIES: usize = 256;
+
+/// In-memory cache of computed statistics keyed by `StatsOptions::cache_key`.
+/// Entries expire after the TTL and are dropped whenever a report is submitted.
+pub struct StatsCache {
+    ttl: Duration,
+    entries: RwLock<HashMap<String, ErrorStats>>,
+}
+
+impl StatsCache {
+    /// A zero TTL disables caching
+    pub fn new(ttl: std::time::Duration) -> Self {
+        Self {
+            ttl: Duration::from_std(ttl).unwrap_or(Duration::zero()),
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Fresh cached statistics, with `cache_age_seconds` set to their age
+    pub fn get(&self, key: &str) -> Option<ErrorStats> {
+        let entries = self.entries.read().ok()?;
+        let stats = entries.get(key)?;
+
+        let age = Utc::now() - stats.generated_at;
+        if age >= self.ttl {
+            return None;
+        }
+
+        let mut stats = stats.clone();
+        stats.cache_age_seconds = age.num_seconds();
+        Some(stats)
+    }
+
+    pub fn insert(&self, key: String, stats: &ErrorStats) {
+        if self.ttl <= Duration::zero() {
+            return;
+        }
+        let Ok(mut entries) = self.entries.write() else {
+            return;
+        };
+
+        if entries.len() >= MAX_ENTRIES {
+            let now = Utc::now();
+            entries.retain(|_, cached| now - cached.generated_at < self.ttl);
+            if entries.len() >= MAX_ENTRIES {
+                entries.clear();
+            }
+        }
+        entries.insert(key, stats.clone());
+    }
+
+    /// Drop every cached result, e.g. after a new report was stored
+    pub fn invalidate(&self) {
+        if let Ok(mut entries) = self.entries.write() {
+            entries.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::stats::{ErrorTrends, Granularity, SeriesInfo};
+
+    fn create_stats(total_errors: u64) -> ErrorStats {
+        ErrorStats {
+            total_errors,
+            errors_by_type: vec![],
+            errors_by_machine: vec![],
+            errors_by_distro: vec![],
+            errors_by_package: vec![],
+            errors_by_host_distro: vec![],
+            errors_by_build_sys: vec![],
+            recent_errors: vec![],
+            daily_stats: vec![],
+            series: SeriesInfo {
+                granularity: Granularity::Day,
+                days: 30,
+                tz: "UTC".to_string(),
+            },
+            top_submitters: vec![],
+            error_trends: ErrorTrends {
+                this_week: 0,
+                last_week: 0,
+                this_month: 0,
+                last_month: 0,
+                week_over_week_change: 0.0,
+                month_over_month_change: 0.0,
+            },
+            generated_at: Utc::now(),
+            cache_age_seconds: 0,
+        }
+    }
+
+    #[test]
+    fn test_hit_and_invalidate() {
+        let cache = StatsCache::new(std::time::Duration::from_secs(60));
+        cache.insert("all".to_string(), &create_stats(42));
+
+        assert_eq!(cache.get("all").unwrap().total_errors, 42);
+        assert!(cache.get("machine=qemuarm64").is_none());
+
+        cache.invalidate();
+        assert!(cache.get("all").is_none());
+    }
+
+    #[test]
+    fn test_expired_entries_are_ignored() {
+        let cache = StatsCache::new(std::time::Duration::from_secs(60));
+        let mut stats = create_stats(1);
+        stats.generated_at = Utc::now() - Duration::minutes(5);
+        cache.insert("all".to_string(), &stats);
+        assert!(cache.get("all").is_none());
+
+        let disabled = StatsCache::new(std::time::Duration::ZERO);
+        disabled.insert("all".to_string(), &create_stats(1));
+        assert!(disabled.get("all").is_none());
+    }
+}
@@ -0,0 +1,356 @@
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    sea_query::{extension::postgres::PgExpr, Alias, Expr, Func, IntoColumnRef, LikeExpr},
+    ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, LoaderTrait,
+    QueryFilter, QueryOrder, QuerySelect, TransactionTrait,
+};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::models::{
+    audit_event, build_configuration, error_report, error_report::escape_like, moderation_action,
+    saved_search_subscription, submission_ban, AuditEvent, BuildConfiguration, ErrorReport,
+    ModerationAction, SavedSearchSubscription, SubmissionBan,
+};
+
+const REDACTED_EMAIL: &str = "[REDACTED_EMAIL]";
+/// Lets the erasure rewrite the snapshots of recorded audit events, which are
+/// otherwise append-only; see migrations/013_audit_event_erasure.sql
+const ALLOW_AUDIT_ERASURE_SQL: &str = "SET LOCAL audit_events.erasure = 'on'";
+
+/// Everything stored about a submitter, as returned by a data export request
+#[derive(Debug, Serialize)]
+pub struct SubmitterExport {
+    pub email: String,
+    pub exported_at: DateTime<Utc>,
+    pub reports: Vec<SubmitterReport>,
+    /// Reports submitted by someone else whose details or logs mention the email
+    pub mentioned_in_reports: Vec<i32>,
+    pub saved_search_subscriptions: Vec<saved_search_subscription::Model>,
+    /// Email bans on the address
+    pub submission_bans: Vec<submission_ban::Model>,
+    /// Moderation log entries that mention the email
+    pub moderation_actions: Vec<moderation_action::Model>,
+    /// Audit events whose recorded entity states mention the email
+    pub audit_events: Vec<SubmitterAuditEvent>,
+}
+
+/// The recorded change of an audit event, without who made it
+#[derive(Debug, Serialize)]
+pub struct SubmitterAuditEvent {
+    pub id: i64,
+    pub created_at: DateTime<Utc>,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Option<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl From<audit_event::Model> for SubmitterAuditEvent {
+    fn from(event: audit_event::Model) -> Self {
+        Self {
+            id: event.id,
+            created_at: event.created_at,
+            action: event.action,
+            entity_type: event.entity_type,
+            entity_id: event.entity_id,
+            before: event.before,
+            after: event.after,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubmitterReport {
+    #[serde(flatten)]
+    pub report: error_report::Model,
+    /// Left out when serializing reports elsewhere, so listed explicitly
+    pub submitter_ip: Option<String>,
+    pub submitter_token: Option<String>,
+    pub build_configurations: Vec<build_configuration::Model>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErasureSummary {
+    /// Not serialized, so recording the summary doesn't keep the erased address
+    #[serde(skip_serializing)]
+    pub email: String,
+    pub reports_anonymized: u64,
+    pub reports_scrubbed: u64,
+    pub subscriptions_deleted: u64,
+    pub bans_lifted: u64,
+    pub moderation_actions_scrubbed: u64,
+    pub audit_events_scrubbed: u64,
+}
+
+pub struct SubmitterService {
+    db: DatabaseConnection,
+}
+
+impl SubmitterService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Collect every report, subscription, ban and log entry tied to the given
+    /// email address
+    pub async fn export(&self, email: &str) -> Result<SubmitterExport, DbErr> {
+        let email = email.trim();
+
+        let reports = ErrorReport::find()
+            .filter(submitter_email_matches(email))
+            .order_by_asc(error_report::Column::Id)
+            .all(&self.db)
+            .await?;
+        let build_configurations = reports.load_many(BuildConfiguration, &self.db).await?;
+
+        let mentioned_in_reports = ErrorReport::find()
+            .select_only()
+            .column(error_report::Column::Id)
+            .filter(mentions_email(email))
+            .filter(submitter_email_matches(email).not())
+            .order_by_asc(error_report::Column::Id)
+            .into_tuple::<i32>()
+            .all(&self.db)
+            .await?;
+
+        let saved_search_subscriptions = SavedSearchSubscription::find()
+            .filter(email_column_matches(
+                saved_search_subscription::Column::Email,
+                email,
+            ))
+            .order_by_asc(saved_search_subscription::Column::Id)
+            .all(&self.db)
+            .await?;
+        let submission_bans = SubmissionBan::find()
+            .filter(email_ban(email))
+            .all(&self.db)
+            .await?;
+        let moderation_actions = ModerationAction::find()
+            .filter(Expr::col(moderation_action::Column::Details).ilike(mention_pattern(email)))
+            .order_by_asc(moderation_action::Column::Id)
+            .all(&self.db)
+            .await?;
+        let audit_events = AuditEvent::find()
+            .filter(audit_snapshot_mentions(email))
+            .order_by_asc(audit_event::Column::Id)
+            .all(&self.db)
+            .await?;
+
+        Ok(SubmitterExport {
+            email: email.to_string(),
+            exported_at: Utc::now(),
+            reports: reports
+                .into_iter()
+                .zip(build_configurations)
+                .map(|(report, build_configurations)| SubmitterReport {
+                    submitter_ip: report.submitter_ip.clone(),
+                    submitter_token: report.submitter_token.clone(),
+                    report,
+                    build_configurations,
+                })
+                .collect(),
+            mentioned_in_reports,
+            saved_search_subscriptions,
+            submission_bans,
+            moderation_actions,
+            audit_events: audit_events.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    /// Remove the email (and the name, address and token submitted alongside it)
+    /// from every report, and scrub any occurrence of the email from error details
+    /// and logs, whatever its capitalization. Subscriptions and email bans of the
+    /// address are deleted, and the email is scrubbed from the moderation log and
+    /// the entity states recorded in the audit log.
+    pub async fn erase<C: ConnectionTrait + TransactionTrait>(
+        &self,
+        db: &C,
+        email: &str,
+    ) -> Result<ErasureSummary, DbErr> {
+        let email = email.trim();
+        let txn = db.begin().await?;
+
+        let anonymized = ErrorReport::update_many()
+            .col_expr(
+                error_report::Column::SubmitterName,
+                Expr::value(Option::<String>::None),
+            )
+            .col_expr(
+                error_report::Column::SubmitterEmail,
+                Expr::value(Option::<String>::None),
+            )
+            .col_expr(
+                error_report::Column::SubmitterIp,
+                Expr::value(Option::<String>::None),
+            )
+            .col_expr(
+                error_report::Column::SubmitterToken,
+                Expr::value(Option::<String>::None),
+            )
+            .filter(submitter_email_matches(email))
+            .exec(&txn)
+            .await?;
+
+        let scrubbed = ErrorReport::update_many()
+            .col_expr(
+                error_report::Column::ErrorDetails,
+                replace_email(error_report::Column::ErrorDetails, email),
+            )
+            .col_expr(
+                error_report::Column::LogData,
+                replace_email(error_report::Column::LogData, email),
+            )
+            .filter(mentions_email(email))
+            .exec(&txn)
+            .await?;
+
+        let subscriptions = SavedSearchSubscription::delete_many()
+            .filter(email_column_matches(
+                saved_search_subscription::Column::Email,
+                email,
+            ))
+            .exec(&txn)
+            .await?;
+        let bans = SubmissionBan::delete_many()
+            .filter(email_ban(email))
+            .exec(&txn)
+            .await?;
+        let moderation_actions = ModerationAction::update_many()
+            .col_expr(
+                moderation_action::Column::Details,
+                replace_email(moderation_action::Column::Details, email),
+            )
+            .filter(Expr::col(moderation_action::Column::Details).ilike(mention_pattern(email)))
+            .exec(&txn)
+            .await?;
+
+        txn.execute_unprepared(ALLOW_AUDIT_ERASURE_SQL).await?;
+        let audit_events = AuditEvent::update_many()
+            .col_expr(
+                audit_event::Column::Before,
+                replace_email_in_json(audit_event::Column::Before, email),
+            )
+            .col_expr(
+                audit_event::Column::After,
+                replace_email_in_json(audit_event::Column::After, email),
+            )
+            .filter(audit_snapshot_mentions(email))
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(ErasureSummary {
+            email: email.to_string(),
+            reports_anonymized: anonymized.rows_affected,
+            reports_scrubbed: scrubbed.rows_affected,
+            subscriptions_deleted: subscriptions.rows_affected,
+            bans_lifted: bans.rows_affected,
+            moderation_actions_scrubbed: moderation_actions.rows_affected,
+            audit_events_scrubbed: audit_events.rows_affected,
+        })
+    }
+}
+
+fn submitter_email_matches(email: &str) -> Condition {
+    email_column_matches(error_report::Column::SubmitterEmail, email)
+}
+
+fn email_column_matches(column: impl IntoColumnRef, email: &str) -> Condition {
+    Condition::all().add(Expr::expr(Func::lower(Expr::col(column))).eq(email.to_lowercase()))
+}
+
+/// Ban values are stored in lower case
+fn email_ban(email: &str) -> Condition {
+    Condition::all()
+        .add(submission_ban::Column::Kind.eq(submission_ban::KIND_EMAIL))
+        .add(submission_ban::Column::Value.eq(email.to_lowercase()))
+}
+
+fn mention_pattern(email: &str) -> LikeExpr {
+    LikeExpr::new(format!("%{}%", escape_like(email))).escape('\\')
+}
+
+/// Email addresses are case-insensitive, so any capitalization of the email is matched
+fn mentions_email(email: &str) -> Condition {
+    Condition::any()
+        .add(Expr::col(error_report::Column::ErrorDetails).ilike(mention_pattern(email)))
+        .add(Expr::col(error_report::Column::LogData).ilike(mention_pattern(email)))
+}
+
+/// Events whose recorded entity states contain the email anywhere
+fn audit_snapshot_mentions(email: &str) -> Condition {
+    let as_text = |column| Expr::col(column).cast_as(Alias::new("text"));
+    Condition::any()
+        .add(as_text(audit_event::Column::Before).ilike(mention_pattern(email)))
+        .add(as_text(audit_event::Column::After).ilike(mention_pattern(email)))
+}
+
+fn replace_email(column: impl IntoColumnRef, email: &str) -> sea_orm::sea_query::SimpleExpr {
+    Func::cust(Alias::new("regexp_replace"))
+        .arg(Expr::col(column))
+        .arg(regex::escape(email))
+        .arg(REDACTED_EMAIL)
+        .arg("gi")
+        .into()
+}
+
+/// Replace the email in every string of a JSON value
+fn replace_email_in_json(
+    column: audit_event::Column,
+    email: &str,
+) -> sea_orm::sea_query::SimpleExpr {
+    Expr::expr(
+        Func::cust(Alias::new("regexp_replace"))
+            .arg(Expr::col(column).cast_as(Alias::new("text")))
+            .arg(regex::escape(email))
+            .arg(REDACTED_EMAIL)
+            .arg("gi"),
+    )
+    .cast_as(Alias::new("jsonb"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::{DbBackend, QueryTrait};
+
+    #[test]
+    fn test_scrub_matches_any_capitalization() {
+        let email = "Jane.Doe_1@Example.com";
+        let statement = ErrorReport::update_many()
+            .col_expr(
+                error_report::Column::LogData,
+                replace_email(error_report::Column::LogData, email),
+            )
+            .filter(mentions_email(email))
+            .build(DbBackend::Postgres)
+            .to_string();
+
+        assert!(statement.contains(
+            r#"regexp_replace("log_data", E'Jane\\.Doe_1@Example\\.com', '[REDACTED_EMAIL]', 'gi')"#
+        ));
+        assert!(
+            statement.contains(r#""log_data" ILIKE (E'%Jane.Doe\\_1@Example.com%' ESCAPE E'\\')"#)
+        );
+    }
+
+    #[test]
+    fn test_scrub_audit_snapshots() {
+        let statement = AuditEvent::update_many()
+            .col_expr(
+                audit_event::Column::Before,
+                replace_email_in_json(audit_event::Column::Before, "jane@example.com"),
+            )
+            .filter(audit_snapshot_mentions("jane@example.com"))
+            .build(DbBackend::Postgres)
+            .to_string();
+
+        assert!(statement.contains(
+            r#"CAST(regexp_replace(CAST("before" AS text), E'jane@example\\.com', '[REDACTED_EMAIL]', 'gi') AS jsonb)"#
+        ));
+        assert!(statement.contains(r#"CAST("after" AS text) ILIKE"#));
+    }
+}
@@ -0,0 +1,157 @@
+use chrono::Utc;
+use sea_orm::{
+    sea_query::OnConflict, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    JoinType, QueryFilter, QueryOrder, QuerySelect, RelationTrait, Set,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::models::{
+    error_report_tag,
+    tag::{self, normalize_name},
+    ErrorReportTag, Tag,
+};
+
+/// A tag with the number of reports carrying it
+#[derive(Debug, Clone, Serialize)]
+pub struct TagCount {
+    pub name: String,
+    pub count: i64,
+}
+
+pub struct TagService {
+    db: DatabaseConnection,
+}
+
+impl TagService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Tags in use, most frequent first
+    pub async fn list(&self) -> Result<Vec<TagCount>, DbErr> {
+        let rows: Vec<(String, i64)> = Tag::find()
+            .select_only()
+            .column(tag::Column::Name)
+            .column_as(error_report_tag::Column::Id.count(), "count")
+            .join(JoinType::InnerJoin, tag::Relation::ErrorReportTag.def())
+            .group_by(tag::Column::Name)
+            .order_by_desc(error_report_tag::Column::Id.count())
+            .order_by_asc(tag::Column::Name)
+            .into_tuple()
+            .all(&self.db)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(name, count)| TagCount { name, count })
+            .collect())
+    }
+
+    /// Tag names of a report in alphabetical order
+    pub async fn tags_for_report(&self, error_report_id: i32) -> Result<Vec<String>, DbErr> {
+        Ok(self
+            .tags_for_reports(&[error_report_id])
+            .await?
+            .remove(&error_report_id)
+            .unwrap_or_default())
+    }
+
+    /// Tag names of each of the given reports; untagged reports are left out
+    pub async fn tags_for_reports(
+        &self,
+        error_report_ids: &[i32],
+    ) -> Result<HashMap<i32, Vec<String>>, DbErr> {
+        if error_report_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let rows: Vec<(i32, String)> = ErrorReportTag::find()
+            .select_only()
+            .column(error_report_tag::Column::ErrorReportId)
+            .column(tag::Column::Name)
+            .join(JoinType::InnerJoin, error_report_tag::Relation::Tag.def())
+            .filter(error_report_tag::Column::ErrorReportId.is_in(error_report_ids.to_vec()))
+            .order_by_asc(tag::Column::Name)
+            .into_tuple()
+            .all(&self.db)
+            .await?;
+
+        let mut tags: HashMap<i32, Vec<String>> = HashMap::new();
+        for (error_report_id, name) in rows {
+            tags.entry(error_report_id).or_default().push(name);
+        }
+        Ok(tags)
+    }
+
+    /// Attach a tag to a report, creating the tag on first use. Adding a tag
+    /// twice has no effect.
+    pub async fn add<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        error_report_id: i32,
+        name: &str,
+    ) -> Result<(), DbErr> {
+        let name = normalize_name(name);
+
+        Tag::insert(tag::ActiveModel {
+            name: Set(name.clone()),
+            created_at: Set(Utc::now()),
+            ..Default::default()
+        })
+        .on_conflict(
+            OnConflict::column(tag::Column::Name)
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+
+        let tag = Tag::find()
+            .filter(tag::Column::Name.eq(&name))
+            .one(db)
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound(format!("tag {}", name)))?;
+
+        ErrorReportTag::insert(error_report_tag::ActiveModel {
+            error_report_id: Set(error_report_id),
+            tag_id: Set(tag.id),
+            created_at: Set(Utc::now()),
+            ..Default::default()
+        })
+        .on_conflict(
+            OnConflict::columns([
+                error_report_tag::Column::ErrorReportId,
+                error_report_tag::Column::TagId,
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+        Ok(())
+    }
+
+    /// Detach a tag from a report. The tag itself is kept for reuse.
+    pub async fn remove<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        error_report_id: i32,
+        name: &str,
+    ) -> Result<bool, DbErr> {
+        let Some(tag) = Tag::find()
+            .filter(tag::Column::Name.eq(normalize_name(name)))
+            .one(db)
+            .await?
+        else {
+            return Ok(false);
+        };
+
+        let result = ErrorReportTag::delete_many()
+            .filter(error_report_tag::Column::ErrorReportId.eq(error_report_id))
+            .filter(error_report_tag::Column::TagId.eq(tag.id))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+}
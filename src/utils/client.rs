@@ -0,0 +1,142 @@
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header, request::Parts, HeaderMap},
+};
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+};
+
+use crate::AppState;
+
+/// Header a submission client may send to identify itself, so that its
+/// reports can be banned together
+pub const SUBMISSION_TOKEN_HEADER: &str = "x-submission-token";
+
+/// Address a request came from. With `trust_forwarded_for`, the last address
+/// in `X-Forwarded-For` takes precedence over the peer, which is then the proxy.
+/// Proxies append the address they saw, so earlier entries come from the client
+/// and can not be trusted.
+pub fn client_ip(
+    headers: &HeaderMap,
+    peer: Option<SocketAddr>,
+    trust_forwarded_for: bool,
+) -> Option<String> {
+    let forwarded = trust_forwarded_for
+        .then(|| {
+            headers
+                .get_all("x-forwarded-for")
+                .iter()
+                .next_back()?
+                .to_str()
+                .ok()
+        })
+        .flatten()
+        .and_then(|value| value.rsplit(',').next())
+        .and_then(|last| last.trim().parse::<IpAddr>().ok());
+
+    forwarded
+        .or_else(|| peer.map(|peer| peer.ip()))
+        .map(|ip| ip.to_canonical().to_string())
+}
+
+/// The submission token sent with a request, if any
+pub fn submission_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(SUBMISSION_TOKEN_HEADER)?
+        .to_str()
+        .ok()
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .map(str::to_string)
+}
+
+/// Longest user agent kept with an audit event
+const MAX_USER_AGENT_CHARS: usize = 500;
+
+/// Where a request came from, as recorded in the audit log
+#[derive(Debug, Clone, Default)]
+pub struct RequestMeta {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub method: String,
+    pub path: String,
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for RequestMeta {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(peer)| *peer);
+        let user_agent = parts
+            .headers
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.chars().take(MAX_USER_AGENT_CHARS).collect());
+
+        Ok(Self {
+            ip: client_ip(&parts.headers, peer, state.config.trust_forwarded_for),
+            user_agent,
+            method: parts.method.to_string(),
+            path: parts.uri.path().to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_ip() {
+        let peer: SocketAddr = "10.0.0.2:50000".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "203.0.113.7".parse().unwrap());
+
+        assert_eq!(
+            client_ip(&headers, Some(peer), true).as_deref(),
+            Some("203.0.113.7")
+        );
+        // The header is trivially forged unless a proxy sets it
+        assert_eq!(
+            client_ip(&headers, Some(peer), false).as_deref(),
+            Some("10.0.0.2")
+        );
+
+        // A client sending its own header only controls the leading entries
+        headers.insert(
+            "x-forwarded-for",
+            "192.0.2.99, 203.0.113.7".parse().unwrap(),
+        );
+        assert_eq!(
+            client_ip(&headers, Some(peer), true).as_deref(),
+            Some("203.0.113.7")
+        );
+        headers.append("x-forwarded-for", "198.51.100.4".parse().unwrap());
+        assert_eq!(
+            client_ip(&headers, Some(peer), true).as_deref(),
+            Some("198.51.100.4")
+        );
+
+        headers.insert("x-forwarded-for", "203.0.113.7, unknown".parse().unwrap());
+        assert_eq!(
+            client_ip(&headers, Some(peer), true).as_deref(),
+            Some("10.0.0.2")
+        );
+
+        let mapped: SocketAddr = "[::ffff:192.0.2.1]:80".parse().unwrap();
+        assert_eq!(
+            client_ip(&HeaderMap::new(), Some(mapped), false).as_deref(),
+            Some("192.0.2.1")
+        );
+        assert_eq!(client_ip(&HeaderMap::new(), None, false), None);
+    }
+}
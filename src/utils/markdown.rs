@@ -0,0 +1,42 @@
+//! Markdown rendering for user-written text such as comments
+
+use pulldown_cmark::{html, Options, Parser};
+
+/// Render markdown to HTML that is safe to embed in a page. Raw HTML in the
+/// source is allowed through the parser but stripped of scripts, event
+/// handlers and other unsafe markup by ammonia afterwards.
+pub fn render_markdown(source: &str) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, Parser::new_ext(source, options));
+    ammonia::clean(&unsafe_html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_markdown() {
+        let html = render_markdown(
+            "Fixed in **meta-oe** `abc123`, see [bug](https://bugzilla.yoctoproject.org/1)",
+        );
+        assert!(html.contains("<strong>meta-oe</strong>"));
+        assert!(html.contains("<code>abc123</code>"));
+        assert!(html.contains(r#"href="https://bugzilla.yoctoproject.org/1""#));
+    }
+
+    #[test]
+    fn test_render_markdown_strips_unsafe_html() {
+        let html = render_markdown(
+            "<script>alert(1)</script>\n\n<img src=x onerror=alert(1)> [link](javascript:alert(1))",
+        );
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("onerror"));
+        assert!(!html.contains("javascript:"));
+    }
+}
@@ -1 +1,5 @@
+pub mod client;
+pub mod markdown;
+pub mod privacy;
+pub mod redaction;
 pub mod validation;
@@ -0,0 +1,163 @@
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+
+use crate::{config::EmailVisibility, models::error_report, services::stats::ErrorStats};
+
+/// Prefix of published email hashes, by which the `submitter` filter tells them
+/// from names
+pub const EMAIL_HASH_PREFIX: &str = "hmac:";
+/// Hex digits of the HMAC that are published: 128 bits, half the digest
+pub const EMAIL_HASH_LENGTH: usize = 32;
+/// Block size of SHA-256, which HMAC pads its key to
+const BLOCK_SIZE: usize = 64;
+
+static EMAIL_HASH_KEY: OnceLock<EmailHashKey> = OnceLock::new();
+
+/// HMAC-SHA256 key for email hashes, kept as the inner and outer padded keys
+/// so the database can compute the same HMAC with its `sha256` function
+pub struct EmailHashKey {
+    inner_pad: [u8; BLOCK_SIZE],
+    outer_pad: [u8; BLOCK_SIZE],
+}
+
+impl EmailHashKey {
+    pub fn new(key: &[u8]) -> Self {
+        let mut block = [0u8; BLOCK_SIZE];
+        if key.len() > BLOCK_SIZE {
+            block[..32].copy_from_slice(&Sha256::digest(key));
+        } else {
+            block[..key.len()].copy_from_slice(key);
+        }
+        Self {
+            inner_pad: block.map(|byte| byte ^ 0x36),
+            outer_pad: block.map(|byte| byte ^ 0x5c),
+        }
+    }
+
+    /// HMAC-SHA256 of a message
+    pub fn hmac(&self, message: &[u8]) -> [u8; 32] {
+        let inner = Sha256::new()
+            .chain_update(self.inner_pad)
+            .chain_update(message)
+            .finalize();
+        Sha256::new()
+            .chain_update(self.outer_pad)
+            .chain_update(inner)
+            .finalize()
+            .into()
+    }
+
+    pub fn inner_pad(&self) -> &[u8] {
+        &self.inner_pad
+    }
+
+    pub fn outer_pad(&self) -> &[u8] {
+        &self.outer_pad
+    }
+}
+
+/// Set the secret email hashes are keyed with; later calls have no effect
+pub fn init_email_hash_key(key: &str) {
+    EMAIL_HASH_KEY.get_or_init(|| EmailHashKey::new(key.as_bytes()));
+}
+
+/// The configured email hash key. The configuration requires one when emails
+/// are hashed; without it, a random key keeps hashes from being reversed but
+/// changes them on every restart.
+pub fn email_hash_key() -> &'static EmailHashKey {
+    EMAIL_HASH_KEY.get_or_init(|| EmailHashKey::new(uuid::Uuid::new_v4().as_bytes()))
+}
+
+/// Hash an email address so repeat submitters can still be recognised
+/// without publishing the address itself. The hash is keyed with a secret, so
+/// it can't be checked against a list of guessed addresses.
+pub fn hash_email(email: &str) -> String {
+    let digest = email_hash_key().hmac(email.trim().to_lowercase().as_bytes());
+    format!(
+        "{}{}",
+        EMAIL_HASH_PREFIX,
+        &hex::encode(digest)[..EMAIL_HASH_LENGTH]
+    )
+}
+
+/// Apply the configured visibility to a submitter email
+pub fn mask_email(visibility: EmailVisibility, email: &str) -> Option<String> {
+    match visibility {
+        EmailVisibility::Show => Some(email.to_string()),
+        EmailVisibility::Hide => None,
+        EmailVisibility::Hash => Some(hash_email(email)),
+    }
+}
+
+/// Mask the submitter email of a report before it is shown publicly
+pub fn mask_report(visibility: EmailVisibility, report: &mut error_report::Model) {
+    report.submitter_email = report
+        .submitter_email
+        .as_deref()
+        .and_then(|email| mask_email(visibility, email));
+}
+
+/// Mask the `(email, count)` pairs of the top submitters statistic. Hidden
+/// emails are dropped entirely since the list is meaningless without them.
+pub fn mask_submitters(
+    visibility: EmailVisibility,
+    submitters: Vec<(String, u64)>,
+) -> Vec<(String, u64)> {
+    submitters
+        .into_iter()
+        .filter_map(|(email, count)| mask_email(visibility, &email).map(|masked| (masked, count)))
+        .collect()
+}
+
+/// Mask every submitter email contained in the public statistics
+pub fn mask_stats(visibility: EmailVisibility, stats: &mut ErrorStats) {
+    stats.top_submitters = mask_submitters(visibility, std::mem::take(&mut stats.top_submitters));
+    for report in &mut stats.recent_errors {
+        mask_report(visibility, report);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_email_is_stable_and_case_insensitive() {
+        let hashed = hash_email("Test@Example.com");
+        assert_eq!(hashed, hash_email(" test@example.com"));
+        assert!(hashed.starts_with(EMAIL_HASH_PREFIX));
+        assert_eq!(hashed.len(), EMAIL_HASH_PREFIX.len() + EMAIL_HASH_LENGTH);
+        assert!(!hashed.contains("example"));
+    }
+
+    #[test]
+    fn test_hmac() {
+        // RFC 4231 test cases 2 and 6, the latter with a key longer than a block
+        assert_eq!(
+            hex::encode(EmailHashKey::new(b"Jefe").hmac(b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            hex::encode(
+                EmailHashKey::new(&[0xaa; 131])
+                    .hmac(b"Test Using Larger Than Block-Size Key - Hash Key First")
+            ),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn test_mask_submitters() {
+        let submitters = vec![("test@example.com".to_string(), 3)];
+
+        assert_eq!(
+            mask_submitters(EmailVisibility::Show, submitters.clone()),
+            submitters
+        );
+        assert!(mask_submitters(EmailVisibility::Hide, submitters.clone()).is_empty());
+        assert_eq!(
+            mask_submitters(EmailVisibility::Hash, submitters)[0].0,
+            hash_email("test@example.com")
+        );
+    }
+}
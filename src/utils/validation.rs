@@ -1,4 +1,14 @@
-use crate::models::error_report::ErrorSubmissionData;
+use crate::models::{
+    comment::CommentData,
+    error_report::{ErrorQuery, ErrorSubmissionData},
+    failure_group::{MergeGroupData, SplitGroupData},
+    failure_group_rule::FailureGroupRuleData,
+    known_issue::KnownIssueData,
+    moderation_action::{ModerationData, ACTION_DELETE, ACTION_HIDE, ACTION_UNHIDE},
+    saved_search::SavedSearchData,
+    submission_ban::{BanData, KIND_EMAIL, KIND_IP, KIND_TOKEN},
+    tag::normalize_name,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum ValidationError {
@@ -14,6 +24,14 @@ pub enum ValidationError {
     InvalidCharacters { field: String },
     #[error("Invalid email format")]
     InvalidEmail,
+    #[error("Field '{field}' is not a valid regular expression")]
+    InvalidPattern { field: String },
+    #[error("Field '{field}' is not a valid error list query: {reason}")]
+    InvalidQuery { field: String, reason: String },
+    #[error("Field '{field}' must be an http or https URL")]
+    InvalidUrl { field: String },
+    #[error("Field '{field}' has an unsupported value")]
+    InvalidChoice { field: String },
 }
 
 pub fn validate_error_submission(data: &ErrorSubmissionData) -> Result<(), ValidationError> {
@@ -60,6 +78,196 @@ pub fn validate_error_submission(data: &ErrorSubmissionData) -> Result<(), Valid
     Ok(())
 }
 
+pub fn validate_known_issue(data: &KnownIssueData) -> Result<(), ValidationError> {
+    validate_required_field(&data.title, "title")?;
+    validate_required_field(&data.explanation, "explanation")?;
+    validate_max_length(&data.title, "title", 200)?;
+
+    if let Some(link) = &data.link {
+        validate_max_length(link, "link", 500)?;
+        // The link is rendered as an anchor on public pages, so other schemes
+        // such as `javascript:` must not get through
+        let link = link.trim();
+        if !link.is_empty() {
+            let is_web_url = url::Url::parse(link)
+                .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.has_host());
+            if !is_web_url {
+                return Err(ValidationError::InvalidUrl {
+                    field: "link".to_string(),
+                });
+            }
+        }
+    }
+
+    let patterns = [
+        (&data.error_details_pattern, "error_details_pattern"),
+        (&data.log_data_pattern, "log_data_pattern"),
+        (&data.failure_task_pattern, "failure_task_pattern"),
+    ];
+
+    // At least one matcher is required, otherwise the issue would match every report
+    if patterns
+        .iter()
+        .all(|(pattern, _)| pattern.as_deref().unwrap_or_default().trim().is_empty())
+    {
+        return Err(ValidationError::RequiredField {
+            field: "pattern".to_string(),
+        });
+    }
+
+    // Patterns are stored trimmed, so they are checked the way they will be matched
+    for (pattern, field_name) in patterns {
+        if let Some(pattern) = pattern.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+            if regex::Regex::new(pattern).is_err() {
+                return Err(ValidationError::InvalidPattern {
+                    field: field_name.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn validate_saved_search(data: &SavedSearchData) -> Result<(), ValidationError> {
+    validate_required_field(&data.name, "name")?;
+    validate_max_length(&data.name, "name", 200)?;
+    validate_max_length(&data.query, "query", 4000)?;
+
+    if let Some(owner) = &data.owner {
+        validate_max_length(owner, "owner", 255)?;
+    }
+
+    if let Err(e) = ErrorQuery::from_query_string(&data.query) {
+        return Err(ValidationError::InvalidQuery {
+            field: "query".to_string(),
+            reason: e.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+pub fn validate_subscription_email(email: &str) -> Result<(), ValidationError> {
+    validate_required_field(email, "email")?;
+    validate_max_length(email, "email", 255)?;
+    validate_email(email)
+}
+
+pub fn validate_comment(data: &CommentData) -> Result<(), ValidationError> {
+    validate_required_field(&data.body, "body")?;
+    validate_max_length(&data.body, "body", 10000)?;
+
+    if let Some(author) = &data.author {
+        validate_max_length(author, "author", 100)?;
+    }
+
+    Ok(())
+}
+
+/// Tag names may contain letters, digits and `-`, `_`, `.` and `:`
+pub fn validate_tag(name: &str) -> Result<(), ValidationError> {
+    let name = normalize_name(name);
+    validate_required_field(&name, "name")?;
+    validate_max_length(&name, "name", 50)?;
+
+    if !name
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+    {
+        return Err(ValidationError::InvalidCharacters {
+            field: "name".to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+pub fn validate_submission_token(token: &str) -> Result<(), ValidationError> {
+    validate_max_length(token, "submission_token", 100)
+}
+
+pub fn validate_moderation(data: &ModerationData) -> Result<(), ValidationError> {
+    if ![ACTION_HIDE, ACTION_UNHIDE, ACTION_DELETE].contains(&data.action.as_str()) {
+        return Err(ValidationError::InvalidChoice {
+            field: "action".to_string(),
+        });
+    }
+    if data.all_matching.is_none() && data.report_id.is_empty() {
+        return Err(ValidationError::RequiredField {
+            field: "report_id".to_string(),
+        });
+    }
+    if let Some(reason) = &data.reason {
+        validate_max_length(reason, "reason", 500)?;
+    }
+    if let Some(actor) = &data.actor {
+        validate_max_length(actor, "actor", 100)?;
+    }
+    Ok(())
+}
+
+pub fn validate_ban(data: &BanData) -> Result<(), ValidationError> {
+    validate_required_field(&data.value, "value")?;
+    let value = data.value.trim();
+    match data.kind.as_str() {
+        KIND_IP => {
+            if value.parse::<std::net::IpAddr>().is_err() {
+                return Err(ValidationError::InvalidCharacters {
+                    field: "value".to_string(),
+                });
+            }
+        }
+        KIND_EMAIL => {
+            validate_max_length(value, "value", 255)?;
+            validate_email(value)?;
+        }
+        KIND_TOKEN => validate_submission_token(value)?,
+        _ => {
+            return Err(ValidationError::InvalidChoice {
+                field: "kind".to_string(),
+            })
+        }
+    }
+    if let Some(actor) = &data.actor {
+        validate_max_length(actor, "actor", 100)?;
+    }
+    Ok(())
+}
+
+pub fn validate_merge_group(data: &MergeGroupData) -> Result<(), ValidationError> {
+    validate_group_change(data.actor.as_deref(), data.note.as_deref())
+}
+
+pub fn validate_split_group(data: &SplitGroupData) -> Result<(), ValidationError> {
+    if data.report_id.is_empty() {
+        return Err(ValidationError::RequiredField {
+            field: "report_id".to_string(),
+        });
+    }
+    validate_group_change(data.actor.as_deref(), data.note.as_deref())
+}
+
+pub fn validate_group_rule(data: &FailureGroupRuleData) -> Result<(), ValidationError> {
+    validate_required_field(&data.recipe, "recipe")?;
+    validate_required_field(&data.failure_task, "failure_task")?;
+    validate_required_field(&data.error_type, "error_type")?;
+    validate_max_length(&data.recipe, "recipe", 200)?;
+    validate_max_length(&data.failure_task, "failure_task", 200)?;
+    validate_max_length(&data.error_type, "error_type", 100)?;
+    validate_group_change(data.actor.as_deref(), None)
+}
+
+fn validate_group_change(actor: Option<&str>, note: Option<&str>) -> Result<(), ValidationError> {
+    if let Some(actor) = actor {
+        validate_max_length(actor, "actor", 100)?;
+    }
+    if let Some(note) = note {
+        validate_max_length(note, "note", 1000)?;
+    }
+    Ok(())
+}
+
 fn validate_required_field(value: &str, field_name: &str) -> Result<(), ValidationError> {
     if value.trim().is_empty() {
         return Err(ValidationError::RequiredField {
@@ -118,7 +326,10 @@ fn validate_no_harmful_content(content: &str) -> Result<(), ValidationError> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::error_report::ErrorSubmissionData;
+    use crate::models::{
+        error_report::ErrorSubmissionData, failure_group::SplitGroupData,
+        known_issue::KnownIssueData, saved_search::SavedSearchData, submission_ban::BanData,
+    };
 
     fn create_valid_submission() -> ErrorSubmissionData {
         ErrorSubmissionData {
@@ -173,4 +384,137 @@ mod tests {
         submission.error_details = "<script>alert('xss')</script>".to_string();
         assert!(validate_error_submission(&submission).is_err());
     }
+
+    fn create_valid_known_issue() -> KnownIssueData {
+        KnownIssueData {
+            actor: None,
+            title: "Host gcc too new".to_string(),
+            explanation: "Use a supported host distro".to_string(),
+            link: None,
+            error_details_pattern: Some(r"error: .*-Werror".to_string()),
+            log_data_pattern: None,
+            failure_task_pattern: Some("do_compile".to_string()),
+            enabled: Some("on".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_valid_known_issue() {
+        assert!(validate_known_issue(&create_valid_known_issue()).is_ok());
+    }
+
+    #[test]
+    fn test_known_issue_without_patterns() {
+        let mut issue = create_valid_known_issue();
+        issue.error_details_pattern = Some("  ".to_string());
+        issue.failure_task_pattern = None;
+        assert!(validate_known_issue(&issue).is_err());
+    }
+
+    #[test]
+    fn test_known_issue_invalid_regex() {
+        let mut issue = create_valid_known_issue();
+        issue.log_data_pattern = Some("No space left (on device".to_string());
+        assert!(matches!(
+            validate_known_issue(&issue),
+            Err(ValidationError::InvalidPattern { .. })
+        ));
+
+        // Only valid before the trailing space is trimmed off
+        issue.log_data_pattern = Some(r"timed out\ ".to_string());
+        assert!(matches!(
+            validate_known_issue(&issue),
+            Err(ValidationError::InvalidPattern { .. })
+        ));
+    }
+
+    #[test]
+    fn test_known_issue_link() {
+        let mut issue = create_valid_known_issue();
+        issue.link = Some("https://bugzilla.yoctoproject.org/show_bug.cgi?id=1".to_string());
+        assert!(validate_known_issue(&issue).is_ok());
+
+        for link in [
+            "javascript:alert(document.cookie)",
+            " JavaScript:alert(1)",
+            "data:text/html,<script>alert(1)</script>",
+            "//example.com/issue",
+            "example.com/issue",
+        ] {
+            issue.link = Some(link.to_string());
+            assert!(
+                matches!(
+                    validate_known_issue(&issue),
+                    Err(ValidationError::InvalidUrl { .. })
+                ),
+                "{link} was accepted"
+            );
+        }
+    }
+
+    #[test]
+    fn test_saved_search_query() {
+        let mut search = SavedSearchData {
+            name: "ARM compile failures".to_string(),
+            query: "machine=qemuarm*&failure_task=do_compile&sort=-created_at".to_string(),
+            owner: None,
+            shared: Some("on".to_string()),
+            pinned: None,
+        };
+        assert!(validate_saved_search(&search).is_ok());
+
+        search.query = "sort=size".to_string();
+        assert!(matches!(
+            validate_saved_search(&search),
+            Err(ValidationError::InvalidQuery { .. })
+        ));
+    }
+
+    #[test]
+    fn test_tag_names() {
+        assert!(validate_tag("needs triage").is_ok());
+        assert!(validate_tag("host:ubuntu-22.04").is_ok());
+        assert!(validate_tag("   ").is_err());
+        assert!(validate_tag("<b>").is_err());
+        assert!(validate_tag(&"a".repeat(51)).is_err());
+    }
+
+    #[test]
+    fn test_split_group_requires_reports() {
+        let mut split = SplitGroupData {
+            report_id: Vec::new(),
+            actor: Some("triager".to_string()),
+            note: None,
+        };
+        assert!(matches!(
+            validate_split_group(&split),
+            Err(ValidationError::RequiredField { .. })
+        ));
+
+        split.report_id = vec![12, 15];
+        assert!(validate_split_group(&split).is_ok());
+
+        split.note = Some("x".repeat(1001));
+        assert!(validate_split_group(&split).is_err());
+    }
+
+    #[test]
+    fn test_ban_values() {
+        let ban = |kind: &str, value: &str| BanData {
+            kind: kind.to_string(),
+            value: value.to_string(),
+            reason: None,
+            actor: None,
+        };
+        assert!(validate_ban(&ban("ip", "203.0.113.7")).is_ok());
+        assert!(validate_ban(&ban("ip", "2001:db8::1")).is_ok());
+        assert!(validate_ban(&ban("ip", "203.0.113.0/24")).is_err());
+        assert!(validate_ban(&ban("email", "Spammer@Example.com")).is_ok());
+        assert!(validate_ban(&ban("email", "spammer")).is_err());
+        assert!(validate_ban(&ban("token", &"t".repeat(101))).is_err());
+        assert!(matches!(
+            validate_ban(&ban("machine", "qemuarm")),
+            Err(ValidationError::InvalidChoice { .. })
+        ));
+    }
 }